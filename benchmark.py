@@ -0,0 +1,103 @@
+"""
+文字起こし速度のベンチマークモジュール（CLI --benchmark）
+生成した音声（または指定した音声ファイル）を設定のモデルで文字起こしし、
+モデルのロード時間・文字起こし時間・実時間比を計測する。cpu_threads やモデルの選択に使う
+"""
+
+import time
+from collections.abc import Callable
+from dataclasses import dataclass
+from pathlib import Path
+
+import numpy as np
+from scipy.io import wavfile
+
+from config import VoiceNoteConfig, is_english_only_model, resolve_model_settings
+from transcriber import TARGET_SAMPLE_RATE, apply_download_timeout, load_model
+
+# 生成するサンプル音声の長さ（秒）
+SAMPLE_SECONDS = 30
+
+
+@dataclass(frozen=True)
+class BenchmarkResult:
+    """スレッド数1件ぶんの計測結果。"""
+
+    cpu_threads: int
+    load_seconds: float
+    transcribe_seconds: float
+    audio_seconds: float
+
+    @property
+    def realtime_factor(self) -> float:
+        """音声1秒あたりの文字起こし秒数。1 未満なら実時間より速い。"""
+        return self.transcribe_seconds / self.audio_seconds if self.audio_seconds else 0.0
+
+
+def parse_thread_counts(text: str) -> list[int]:
+    """`1,2,4` 形式のスレッド数の指定を解析する。0 は CTranslate2 の既定スレッド数。
+
+    Raises:
+        ValueError: 0 以上の整数のカンマ区切りでない場合
+    """
+    try:
+        counts = [int(item) for item in text.split(",") if item.strip()]
+    except ValueError:
+        counts = []
+    if not counts or any(count < 0 for count in counts):
+        raise ValueError(f"スレッド数は 0 以上の整数をカンマ区切りで指定してください: {text}")
+    return counts
+
+
+def generate_sample(path: Path, seconds: float = SAMPLE_SECONDS) -> Path:
+    """音声に似た合成音（抑揚のある倍音を音節ごとに区切ったもの）を 16kHz モノラルで書き出す。"""
+    t = np.arange(int(seconds * TARGET_SAMPLE_RATE)) / TARGET_SAMPLE_RATE
+    pitch = 140 + 30 * np.sin(2 * np.pi * 0.5 * t)
+    phase = 2 * np.pi * np.cumsum(pitch) / TARGET_SAMPLE_RATE
+    voice = sum(np.sin(k * phase) / k for k in range(1, 6))
+    syllables = np.clip(np.sin(2 * np.pi * 4 * t), 0, None)
+    noise = np.random.default_rng(0).standard_normal(len(t))
+    audio = voice * syllables + 0.02 * noise
+    audio_int16 = (audio / np.max(np.abs(audio)) * 0.8 * 32767).astype(np.int16)
+    wavfile.write(path, TARGET_SAMPLE_RATE, audio_int16)
+    return path
+
+
+def run_benchmark(
+    audio_path: Path,
+    config: VoiceNoteConfig,
+    thread_counts: list[int],
+    progress_callback: Callable[[str], None] | None = None,
+) -> list[BenchmarkResult]:
+    """thread_counts の各スレッド数でモデルをロードし直し、audio_path を文字起こしして計測する。
+
+    結果を比べやすくするため、VAD と temperature のフォールバックは使わずに音声全体を
+    デコードする。モデル・language・beam_size・best_of は設定（モデル別上書き後）に従う。
+    """
+    config = resolve_model_settings(config)
+    language = "en" if is_english_only_model(config.whisper_model) else config.language
+    apply_download_timeout(config.model_download_timeout)
+    results = []
+    for threads in thread_counts:
+        if progress_callback:
+            progress_callback(f"cpu_threads={threads or '自動'} で計測中...")
+        # 前回ロードしたモデルを使い回さず、ロード時間も毎回計測する
+        load_model.cache_clear()
+        started = time.perf_counter()
+        model = load_model(config.whisper_model, threads)
+        loaded = time.perf_counter()
+        segments, info = model.transcribe(
+            str(audio_path),
+            language=language,
+            beam_size=config.beam_size,
+            best_of=config.best_of,
+            temperature=0.0,
+            condition_on_previous_text=False,
+            vad_filter=False,
+        )
+        # セグメントはジェネレータで、取り出すときにデコードされる
+        for _ in segments:
+            pass
+        finished = time.perf_counter()
+        results.append(BenchmarkResult(threads, loaded - started, finished - loaded, info.duration))
+    return results
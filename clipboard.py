@@ -0,0 +1,47 @@
+"""
+クリップボード連携モジュール
+追加の依存ライブラリを使わず、OS 標準（または定番）のコマンドでテキストをコピーする
+"""
+
+import shutil
+import subprocess
+import sys
+
+# Linux で順に探すコマンド（Wayland → X11）
+_LINUX_COMMANDS = (
+    ["wl-copy"],
+    ["xclip", "-selection", "clipboard"],
+    ["xsel", "--clipboard", "--input"],
+)
+
+
+def clipboard_command(platform: str = sys.platform) -> list[str] | None:
+    """クリップボードへコピーするコマンドを返す。見つからなければ None。"""
+    if platform == "darwin":
+        return ["pbcopy"]
+    if platform == "win32":
+        return ["clip"]
+    for command in _LINUX_COMMANDS:
+        if shutil.which(command[0]):
+            return command
+    return None
+
+
+def copy_to_clipboard(text: str, platform: str = sys.platform):
+    """テキストをシステムのクリップボードにコピーする。
+
+    Raises:
+        RuntimeError: コピーに使えるコマンドが無い、または実行に失敗した場合
+    """
+    command = clipboard_command(platform)
+    if command is None:
+        raise RuntimeError(
+            "クリップボードにコピーするコマンドが見つかりません"
+            "（wl-clipboard / xclip / xsel のいずれかをインストールしてください）"
+        )
+    # Windows の clip は UTF-16 で渡さないと日本語が化ける
+    data = text.encode("utf-16" if platform == "win32" else "utf-8")
+    try:
+        subprocess.run(command, input=data, check=True, capture_output=True)
+    except (OSError, subprocess.CalledProcessError) as e:
+        raise RuntimeError(f"クリップボードへのコピーに失敗しました: {e}") from e
@@ -5,22 +5,183 @@
 
 import json
 import os
-from dataclasses import asdict, dataclass, fields
-from pathlib import Path
+import shlex
+import sys
+from dataclasses import asdict, dataclass, field, fields, replace
+from pathlib import Path, PureWindowsPath
 
 from rich.console import Console
+from rich.live import Live
 from rich.panel import Panel
 from rich.prompt import Prompt
 
+from errors import VoiceNoteError
+from note_writer import (
+    DEFAULT_AUDIO_LINK_TEMPLATE,
+    DEFAULT_TIMESTAMP_FORMAT,
+    OUTPUT_FORMATS,
+    check_append_to,
+    check_audio_link_template,
+    check_callout_type,
+    check_index_name,
+    check_timestamp_format,
+    normalize_tags,
+    template_root,
+)
+
 console = Console()
 
-CONFIG_PATH = Path.home() / ".config" / "voicenote" / "config.json"
+
+def home_dir() -> Path | None:
+    """ホームフォルダを返す。決められない環境（HOME が無いコンテナなど）では None。"""
+    try:
+        home = Path.home()
+    except (RuntimeError, KeyError):
+        return None
+    # Python 3.11 以前は決められないと "~" をそのまま返す
+    return None if str(home) == "~" else home
 
 
-class InvalidConfigError(Exception):
+def config_dir() -> Path:
+    """設定ファイルを置くフォルダを決める。
+
+    次の順に、最初に決まったものを使う:
+    1. 環境変数 VOICENOTE_CONFIG_DIR
+    2. ホームフォルダの ~/.config/voicenote
+    3. 環境変数 XDG_CONFIG_HOME の下の voicenote
+    4. カレントフォルダの .voicenote（最後の手段）
+    """
+    override = os.environ.get("VOICENOTE_CONFIG_DIR", "").strip()
+    if override:
+        return Path(override).expanduser()
+    home = home_dir()
+    if home is not None:
+        return home / ".config" / "voicenote"
+    xdg = os.environ.get("XDG_CONFIG_HOME", "").strip()
+    if xdg:
+        return Path(xdg) / "voicenote"
+    return Path.cwd() / ".voicenote"
+
+
+CONFIG_PATH = config_dir() / "config.json"
+
+
+def default_recording_dir() -> Path:
+    """録音ファイルの既定の保存先（ホームフォルダの Desktop）。ホームが無ければカレントフォルダ。"""
+    home = home_dir()
+    return home / "Desktop" if home is not None else Path.cwd()
+
+# 設定画面・初回設定で選べる Whisper モデル。`.en` は英語専用（英語なら小さくても高精度）
+WHISPER_MODELS = (
+    "tiny",
+    "base",
+    "small",
+    "medium",
+    "large-v2",
+    "large-v3",
+    "tiny.en",
+    "base.en",
+    "small.en",
+    "medium.en",
+)
+# 初回設定でのモデルの説明
+_MODEL_DESCRIPTIONS = {
+    "tiny": "最速・精度低",
+    "base": "高速・精度中",
+    "small": "標準",
+    "medium": "精度高・時間かかる",
+    "large-v2": "高精度・日本語に強い・時間かかる",
+    "large-v3": "最高精度・最も時間かかる",
+}
+# モデルの初回ダウンロードサイズ（MB、おおよそ）。空き容量の確認と選択時の警告に使う
+MODEL_DOWNLOAD_MB = {
+    "tiny": 75,
+    "base": 145,
+    "small": 485,
+    "medium": 1530,
+    "large-v2": 3090,
+    "large-v3": 3090,
+    "tiny.en": 75,
+    "base.en": 145,
+    "small.en": 485,
+    "medium.en": 1530,
+}
+# extra_destinations の各保存先で指定できる項目（save_folder は必須）
+DESTINATION_KEYS = ("save_folder", "output_format")
+
+# モデルごとに上書きできる設定項目
+MODEL_OVERRIDE_KEYS = ("language", "beam_size", "cpu_threads")
+# model_overrides にエントリが無いモデルに適用する組み込みの既定値。
+# 小さいモデルはビームサーチの効果が薄く遅くなるだけなので greedy (beam_size=1) にする。
+BUILTIN_MODEL_OVERRIDES: dict[str, dict] = {
+    "tiny": {"beam_size": 1},
+    "base": {"beam_size": 1},
+    "tiny.en": {"beam_size": 1},
+    "base.en": {"beam_size": 1},
+}
+# resample_quality: リサンプリング品質（fast / balanced / high）
+RESAMPLE_QUALITIES = ("fast", "balanced", "high")
+# resampler で上書きできるリサンプリングFIRフィルタのパラメータと、その (最小, 最大)。
+# half_length: フィルタ半長（変換比の大きい方に対する倍率）、cutoff: 出力のナイキスト周波数に
+# 対するカットオフの比、kaiser_beta: Kaiser 窓の β（大きいほど阻止域の減衰が大きい）
+RESAMPLER_BOUNDS = {"half_length": (1, 64), "cutoff": (0.5, 1.0), "kaiser_beta": (0.0, 20.0)}
+# recording_format: 録音の保存形式（wav / flac / opus）
+RECORDING_FORMATS = ("wav", "flac", "opus")
+# segment_separator: pause（2秒以上の間で段落分け）/ blank（空行）/ single（改行）/
+# bullet（箇条書き）
+SEGMENT_SEPARATORS = ("pause", "blank", "single", "bullet")
+# hallucination_filter: off（無効）/ last（末尾のセグメントのみ）/ all（すべてのセグメント）
+HALLUCINATION_FILTERS = ("off", "last", "all")
+# 無音で終わる日本語の録音で Whisper が付け足しがちな定型句（hallucination_phrases の既定値）
+DEFAULT_HALLUCINATION_PHRASES = (
+    "ご視聴ありがとうございました",
+    "ご視聴いただきありがとうございました",
+    "最後までご視聴いただきありがとうございました",
+    "チャンネル登録よろしくお願いします",
+    "チャンネル登録お願いします",
+    "次回もお楽しみに",
+)
+# 話し言葉に多いフィラー（filler_words の既定値）
+DEFAULT_FILLER_WORDS = (
+    "えーと",
+    "えっと",
+    "ええと",
+    "えー",
+    "あのー",
+    "うーん",
+    "あー",
+    "んー",
+    "um",
+    "uh",
+)
+# presets（--preset）で切り替えられる文字起こし関連の設定項目。保存先などは切り替えない
+PRESET_KEYS = (
+    "whisper_model",
+    "transcription_mode",
+    "language",
+    "detect_languages",
+    "multilingual",
+    "beam_size",
+    "best_of",
+    "temperature",
+    "cpu_threads",
+    "vad_filter",
+    "vad_min_silence_ms",
+    "vad_max_chunk_seconds",
+    "no_speech_threshold",
+    "suppress_non_speech",
+    "dedupe",
+)
+
+
+class InvalidConfigError(VoiceNoteError):
     """設定ファイルが存在するが読み込み・パースに失敗した場合に送出する。"""
 
 
+class ConfigSaveError(VoiceNoteError, RuntimeError):
+    """設定ファイルを保存できない場合に送出する。"""
+
+
 @dataclass(frozen=True)
 class VoiceNoteConfig:
     """アプリケーション設定。全フィールドのデフォルト値をここに集約する。"""
@@ -28,16 +189,158 @@ class VoiceNoteConfig:
     save_folder: str = ""
     whisper_model: str = "small"
     transcription_mode: str = "local"
+    language: str | None = None
+    detect_languages: list[str] = field(default_factory=list)
+    multilingual: bool = False
+    beam_size: int = 5
+    best_of: int = 5
+    temperature: float | None = None
+    cpu_threads: int = 0
+    model_download_timeout: float = 30.0
+    model_overrides: dict[str, dict] = field(default_factory=dict)
+    presets: dict[str, dict] = field(default_factory=dict)
     vad_filter: bool = True
+    vad_min_silence_ms: int = 2000
+    vad_max_chunk_seconds: float = 0.0
+    chunk_minutes: float = 0.0
+    chunk_overlap_seconds: float = 2.0
+    no_speech_threshold: float = 0.6
+    suppress_non_speech: bool = True
+    dedupe: bool = False
+    hallucination_filter: str = "off"
+    hallucination_phrases: list[str] = field(
+        default_factory=lambda: list(DEFAULT_HALLUCINATION_PHRASES)
+    )
+    filler_filter: bool = False
+    filler_words: list[str] = field(default_factory=lambda: list(DEFAULT_FILLER_WORDS))
+    input_device: str | None = None
+    capture_sample_rate: int = 16000
+    buffer_frames: int | None = None
+    gain_db: float = 0.0
+    stereo_capture: bool = False
+    recording_format: str = "wav"
+    trim_silence: bool = False
+    trim_threshold: float = 0.01
+    stop_on_silence_seconds: float = 0.0
+    highpass_hz: float = 0.0
+    resample_quality: str = "high"
+    resampler: dict[str, float] = field(default_factory=dict)
+    temp_dir: str | None = None
+    keep_temp: bool = False
+    min_free_disk_mb: int = 500
     format_mode: str = "rule"
+    postprocess_command: str | None = None
+    summary_command: str | None = None
+    output_format: str = "md"
+    escape_markdown: bool = False
+    wrap_callout: bool = False
+    callout_type: str = "note"
+    callout_folded: bool = False
+    segment_separator: str = "pause"
+    timestamp_format: str = DEFAULT_TIMESTAMP_FORMAT
+    chapter_gap_seconds: float = 0.0
+    split_gap_seconds: float = 0.0
+    dual_output: bool = False
+    audio_links: bool = False
+    audio_link_template: str = DEFAULT_AUDIO_LINK_TEMPLATE
+    frontmatter_extra: dict[str, str] = field(default_factory=dict)
+    extra_tags: list[str] = field(default_factory=list)
+    note_header: str = ""
+    note_footer: str = ""
+    append_to: str | None = None
+    extra_destinations: list[dict[str, str]] = field(default_factory=list)
+    post_save_command: str | None = None
+    post_save_required: bool = False
     openai_api_key: str | None = None
+    editor: str | None = None
+    log_file: str | None = None
+    incremental_save: bool = False
+    skip_empty: bool = True
+    min_transcript_chars: int = 1
+    batch_index_name: str = "{timestamp}_index"
 
     @classmethod
     def from_dict(cls, data: dict) -> "VoiceNoteConfig":
-        """dict から生成する。旧フォーマット（vault_path）のマイグレーションも行う。"""
+        """dict から生成する。旧フォーマット（vault_path）のマイグレーションも行う。
+
+        Raises:
+            ValueError: timestamp_format が安全なファイル名を生成しない場合、
+                callout_type にコールアウトの種類として使えない文字が含まれる場合、
+                batch_index_name がフォルダの区切りや `..` を含む場合、
+                append_to が保存先フォルダからの相対パスでない場合、
+                audio_link_template が {file} を含まない・未知のプレースホルダを含む場合、
+                frontmatter_extra の値が文字列・数値・真偽値でない場合、
+                extra_tags がリストでない・空のタグや空白を含むタグがある場合、
+                hallucination_filter・recording_format・segment_separator・output_format・
+                resample_quality が不明な値の場合、
+                input_device が文字列でない場合、
+                model_download_timeout が正の数でない場合、chunk_overlap_seconds・highpass_hz・
+                chapter_gap_seconds が負の場合、
+                resampler・extra_destinations・presets の項目が不正な場合。
+        """
         migrated = _migrate_legacy(data)
         known_keys = {f.name for f in fields(cls)}
-        return cls(**{k: v for k, v in migrated.items() if k in known_keys})
+        config = cls(**{k: v for k, v in migrated.items() if k in known_keys})
+        check_timestamp_format(config.timestamp_format)
+        check_callout_type(config.callout_type)
+        check_audio_link_template(config.audio_link_template)
+        check_index_name(config.batch_index_name)
+        if config.append_to:
+            check_append_to(config.append_to)
+        if config.input_device is not None and not isinstance(config.input_device, str):
+            raise ValueError("input_device はデバイス名（またはIDの文字列）で指定してください")
+        if (
+            isinstance(config.model_download_timeout, bool)
+            or not isinstance(config.model_download_timeout, (int, float))
+            or config.model_download_timeout <= 0
+        ):
+            raise ValueError("model_download_timeout は 0 より大きい秒数で指定してください")
+        if (
+            isinstance(config.chunk_overlap_seconds, bool)
+            or not isinstance(config.chunk_overlap_seconds, (int, float))
+            or config.chunk_overlap_seconds < 0
+        ):
+            raise ValueError("chunk_overlap_seconds は 0 以上の秒数で指定してください")
+        if (
+            isinstance(config.highpass_hz, bool)
+            or not isinstance(config.highpass_hz, (int, float))
+            or config.highpass_hz < 0
+        ):
+            raise ValueError("highpass_hz は 0 以上の周波数（Hz、0 で無効）で指定してください")
+        if (
+            isinstance(config.chapter_gap_seconds, bool)
+            or not isinstance(config.chapter_gap_seconds, (int, float))
+            or config.chapter_gap_seconds < 0
+        ):
+            raise ValueError("chapter_gap_seconds は 0 以上の秒数（0 で無効）で指定してください")
+        for key, value in config.frontmatter_extra.items():
+            if not isinstance(value, (str, int, float, bool)):
+                raise ValueError(f"frontmatter_extra の値は文字列で指定してください: {key}")
+        if not isinstance(config.extra_tags, list):
+            raise ValueError('extra_tags はタグのリストで指定してください（例: ["meeting"]）')
+        normalize_tags(config.extra_tags)
+        if config.hallucination_filter not in HALLUCINATION_FILTERS:
+            raise ValueError(
+                f"hallucination_filter は {' / '.join(HALLUCINATION_FILTERS)} のいずれかです"
+            )
+        if config.recording_format not in RECORDING_FORMATS:
+            raise ValueError(f"recording_format は {' / '.join(RECORDING_FORMATS)} のいずれかです")
+        if config.segment_separator not in SEGMENT_SEPARATORS:
+            raise ValueError(
+                f"segment_separator は {' / '.join(SEGMENT_SEPARATORS)} のいずれかです"
+            )
+        if config.resample_quality not in RESAMPLE_QUALITIES:
+            raise ValueError(
+                f"resample_quality は {' / '.join(RESAMPLE_QUALITIES)} のいずれかです"
+            )
+        if config.output_format not in OUTPUT_FORMATS:
+            raise ValueError(f"output_format は {' / '.join(OUTPUT_FORMATS)} のいずれかです")
+        _check_resampler(config.resampler)
+        for destination in config.extra_destinations:
+            _check_destination(destination)
+        for name, preset in config.presets.items():
+            _check_preset(name, preset)
+        return config
 
     def to_dict(self) -> dict:
         """JSON保存用の dict に変換する。未設定の openai_api_key は含めない。"""
@@ -47,6 +350,70 @@ class VoiceNoteConfig:
         return data
 
 
+def _check_resampler(resampler: dict):
+    """resampler を検証する。
+
+    Raises:
+        ValueError: 未知の項目や、数値でない・範囲外の値を含む場合
+    """
+    if not isinstance(resampler, dict):
+        raise ValueError("resampler は項目と値の辞書で指定してください")
+    unknown = set(resampler) - set(RESAMPLER_BOUNDS)
+    if unknown:
+        raise ValueError(
+            f"resampler に指定できない項目です: {', '.join(sorted(unknown))}"
+            f"（{' / '.join(RESAMPLER_BOUNDS)} のみ）"
+        )
+    for key, value in resampler.items():
+        low, high = RESAMPLER_BOUNDS[key]
+        integer = isinstance(low, int)
+        if (
+            isinstance(value, bool)
+            or not isinstance(value, int if integer else (int, float))
+            or not low <= value <= high
+        ):
+            kind = "整数" if integer else "数値"
+            raise ValueError(f"resampler の {key} は {low}〜{high} の{kind}で指定してください")
+
+
+def _check_destination(destination: dict):
+    """extra_destinations の1件を検証する。
+
+    Raises:
+        ValueError: save_folder が無い、未知の項目や不明な保存形式を含む場合
+    """
+    if not isinstance(destination, dict) or not destination.get("save_folder"):
+        raise ValueError(f"extra_destinations の各項目には save_folder が必要です: {destination}")
+    unknown = set(destination) - set(DESTINATION_KEYS)
+    if unknown:
+        raise ValueError(
+            f"extra_destinations に指定できない項目です: {', '.join(sorted(unknown))}"
+            f"（{' / '.join(DESTINATION_KEYS)} のみ）"
+        )
+    output_format = destination.get("output_format")
+    if output_format is not None and output_format not in OUTPUT_FORMATS:
+        raise ValueError(
+            f"extra_destinations の保存形式が不明です: {output_format}"
+            f"（{' / '.join(OUTPUT_FORMATS)} のいずれか）"
+        )
+
+
+def _check_preset(name: str, preset: dict):
+    """presets の1件を検証する。
+
+    Raises:
+        ValueError: 切り替えられない項目を含む場合
+    """
+    if not isinstance(preset, dict):
+        raise ValueError(f"presets の各項目は設定項目の辞書で指定してください: {name}")
+    unknown = set(preset) - set(PRESET_KEYS)
+    if unknown:
+        raise ValueError(
+            f"プリセット '{name}' に指定できない項目です: {', '.join(sorted(unknown))}"
+            f"（{' / '.join(PRESET_KEYS)} のみ）"
+        )
+
+
 def _migrate_legacy(config: dict) -> dict:
     """旧フォーマット（vault_path + save_folder）を新フォーマットに変換する。"""
     if "vault_path" in config and "save_folder" in config:
@@ -79,13 +446,13 @@ def load_config(config_path: Path) -> VoiceNoteConfig | None:
 
 
 def save_config(config_path: Path, config: VoiceNoteConfig):
-    """設定ファイルを保存する。失敗した場合はRuntimeErrorを送出。"""
+    """設定ファイルを保存する。失敗した場合はConfigSaveErrorを送出。"""
     try:
         config_path.parent.mkdir(parents=True, exist_ok=True)
         with open(config_path, "w", encoding="utf-8") as f:
             json.dump(config.to_dict(), f, ensure_ascii=False, indent=2)
     except Exception as e:
-        raise RuntimeError(f"設定ファイルの保存エラー: {e}") from e
+        raise ConfigSaveError(f"設定ファイルの保存エラー: {e}") from e
 
 
 def resolve_api_key(config: VoiceNoteConfig) -> str | None:
@@ -93,8 +460,268 @@ def resolve_api_key(config: VoiceNoteConfig) -> str | None:
     return os.environ.get("OPENAI_API_KEY") or config.openai_api_key
 
 
-def configure_interactive() -> VoiceNoteConfig:
-    """対話的に設定を入力する（CLI用）"""
+def resolve_editor(config: VoiceNoteConfig) -> list[str] | None:
+    """設定の editor を優先し、なければ環境変数 EDITOR からエディタ起動コマンドを組み立てる。"""
+    command = config.editor or os.environ.get("EDITOR")
+    if not command:
+        return None
+    return shlex.split(command) or None
+
+
+def parse_meta_items(items: list[str]) -> dict[str, str]:
+    """CLI の `--meta key=value` を frontmatter_extra 用の dict に変換する。
+
+    値には `=` を含めてよい（最初の `=` で区切る）。
+
+    Raises:
+        ValueError: `=` が無い、またはキーが空の場合
+    """
+    meta: dict[str, str] = {}
+    for item in items:
+        key, sep, value = item.partition("=")
+        if not sep or not key.strip():
+            raise ValueError(f"--meta は key=value の形式で指定してください: {item}")
+        meta[key.strip()] = value.strip()
+    return meta
+
+
+def is_english_only_model(model_name: str) -> bool:
+    """英語専用（`.en`）の Whisper モデルか。"""
+    return model_name.endswith(".en")
+
+
+def model_language_warning(config: VoiceNoteConfig) -> str | None:
+    """英語専用モデルに英語以外の言語の設定を組み合わせていれば、その警告を返す。
+
+    英語専用モデルは英語として文字起こしするため、英語以外の language・detect_languages の
+    候補や multilingual は効かない。モデル別上書き後の設定で判定する（ローカルモードのみ）。
+    """
+    config = resolve_model_settings(config)
+    if config.transcription_mode != "local" or not is_english_only_model(config.whisper_model):
+        return None
+    ignored = []
+    if config.language not in (None, "en"):
+        ignored.append(f"language '{config.language}'")
+    if config.language is None and any(lang != "en" for lang in config.detect_languages):
+        ignored.append(f"detect_languages（{', '.join(config.detect_languages)}）での言語判定")
+    if config.language is None and config.multilingual:
+        ignored.append("multilingual での言語の切り替え")
+    if not ignored:
+        return None
+    return (
+        f"'{config.whisper_model}' は英語専用モデルのため、{'・'.join(ignored)}ではなく"
+        "英語として文字起こしします（英語以外は small などの多言語モデルを選んでください）"
+    )
+
+
+def model_download_warning(model_name: str) -> str | None:
+    """初回ダウンロードが大きいモデルなら、選択時に表示する警告を返す。"""
+    if not model_name.startswith("large"):
+        return None
+    # large-v1 や large-v3-turbo など表に無いモデルはサイズを省く
+    size_mb = MODEL_DOWNLOAD_MB.get(model_name)
+    size = f"約{size_mb // 1000}GBの" if size_mb else "大きな"
+    return (
+        f"'{model_name}' は初回の文字起こし時に{size}モデルを"
+        "ダウンロードします。従量課金の回線ではご注意ください"
+    )
+
+
+def resolve_model_settings(config: VoiceNoteConfig) -> VoiceNoteConfig:
+    """選択中の whisper_model 向けの上書き設定（language / beam_size / cpu_threads）を適用する。
+
+    `model_overrides` にそのモデルのエントリがあればそれを、無ければ組み込みの既定値を使う。
+    どちらも全体の設定より優先する。上書きできない項目は無視する。
+    """
+    overrides = config.model_overrides.get(config.whisper_model)
+    if overrides is None:
+        overrides = BUILTIN_MODEL_OVERRIDES.get(config.whisper_model, {})
+    return replace(config, **{k: v for k, v in overrides.items() if k in MODEL_OVERRIDE_KEYS})
+
+
+def builtin_beam_size_notice(config: VoiceNoteConfig) -> str | None:
+    """組み込みの既定値（BUILTIN_MODEL_OVERRIDES）が設定の beam_size を変える場合の知らせ。
+
+    model_overrides にそのモデルのエントリがあれば（プリセットで固定した場合も）None。
+    """
+    model = config.whisper_model
+    if model in config.model_overrides:
+        return None
+    beam_size = BUILTIN_MODEL_OVERRIDES.get(model, {}).get("beam_size")
+    if beam_size is None or beam_size == config.beam_size:
+        return None
+    return (
+        f"モデル '{model}' は組み込みの既定値で beam_size {beam_size} で文字起こしします"
+        f"（設定の beam_size は {config.beam_size}）。model_overrides に {model} の beam_size を"
+        "設定すると、その値を使いこの表示は出なくなります"
+    )
+
+
+def apply_preset(config: VoiceNoteConfig, name: str) -> VoiceNoteConfig:
+    """presets の name の設定を全体の設定に重ねる（--preset）。
+
+    プリセットで指定した language / beam_size / cpu_threads は、model_overrides や
+    組み込みの既定値よりも優先する。
+
+    Raises:
+        ValueError: name のプリセットが無い場合
+    """
+    if name not in config.presets:
+        available = ", ".join(config.presets) or "なし"
+        raise ValueError(f"プリセットが見つかりません: {name}（設定済み: {available}）")
+    preset = config.presets[name]
+    config = replace(config, **preset)
+    pinned = {k: v for k, v in preset.items() if k in MODEL_OVERRIDE_KEYS}
+    if not pinned:
+        return config
+    model = config.whisper_model
+    overrides = config.model_overrides.get(model)
+    if overrides is None:
+        overrides = BUILTIN_MODEL_OVERRIDES.get(model, {})
+    return replace(
+        config, model_overrides={**config.model_overrides, model: {**overrides, **pinned}}
+    )
+
+
+def obsidian_config_paths(platform: str = sys.platform) -> list[Path]:
+    """Obsidian が開いたことのある Vault を記録する obsidian.json の候補（存在は確認しない）。
+
+    Linux は通常のインストールのほか、Flatpak・Snap 版の場所も含める。
+    """
+    home = home_dir()
+    if platform == "win32":
+        appdata = os.environ.get("APPDATA", "").strip()
+        return [Path(appdata) / "obsidian" / "obsidian.json"] if appdata else []
+    if home is None:
+        return []
+    if platform == "darwin":
+        return [home / "Library" / "Application Support" / "obsidian" / "obsidian.json"]
+    xdg = os.environ.get("XDG_CONFIG_HOME", "").strip()
+    return [
+        (Path(xdg) if xdg else home / ".config") / "obsidian" / "obsidian.json",
+        home / ".var" / "app" / "md.obsidian.Obsidian" / "config" / "obsidian" / "obsidian.json",
+        home / "snap" / "obsidian" / "current" / ".config" / "obsidian" / "obsidian.json",
+    ]
+
+
+def known_obsidian_vaults(config_paths: list[Path] | None = None) -> list[Path]:
+    """obsidian.json に記録された Vault のうち、今もあるフォルダを最近開いた順に返す。
+
+    obsidian.json が無い・読めない・形式が違う場合は、その候補を飛ばす（見つからなければ空）。
+    """
+    vaults: dict[Path, float] = {}
+    for path in obsidian_config_paths() if config_paths is None else config_paths:
+        try:
+            entries = json.loads(Path(path).read_text(encoding="utf-8"))["vaults"].values()
+        except (OSError, ValueError, KeyError, TypeError, AttributeError):
+            continue
+        for entry in entries:
+            if not isinstance(entry, dict) or not isinstance(entry.get("path"), str):
+                continue
+            vault = Path(entry["path"])
+            ts = entry.get("ts")
+            if vault.is_dir():
+                opened = float(ts) if isinstance(ts, (int, float)) else 0.0
+                vaults[vault] = max(opened, vaults.get(vault, 0.0))
+    return sorted(vaults, key=lambda vault: vaults[vault], reverse=True)
+
+
+def vault_root(path: Path) -> Path:
+    """Vault の `.obsidian` フォルダを指定された場合は、Vault 本体のフォルダにする。"""
+    return path.parent if path.name == ".obsidian" else path
+
+
+def _ask_vault_folder(vaults: list[Path]) -> Path | None:
+    """見つかった Vault から保存先を選ばせる。パスを入力する場合は None を返す。
+
+    Vault 内の保存先フォルダ（recordings/voice のような入れ子も含む）がまだ無ければ作る。
+    """
+    console.print("\n[bold]保存先の Obsidian Vault を選択してください:[/bold]")
+    for i, vault in enumerate(vaults, start=1):
+        console.print(f"  {i}. {vault.name:<20} [dim]{vault}[/dim]")
+    manual = str(len(vaults) + 1)
+    console.print(f"  {manual}. パスを入力する")
+
+    vault_map = {str(i): vault for i, vault in enumerate(vaults, start=1)}
+    while True:
+        choice = Prompt.ask("[bold]選択[/bold]", default="1")
+        if choice == manual:
+            return None
+        if choice in vault_map:
+            vault = vault_map[choice]
+            break
+        console.print(f"[red]✗ 1-{manual}の数字を入力してください。[/red]")
+    while True:
+        subfolder = Prompt.ask(
+            "[bold]Vault 内の保存先フォルダ[/bold]（例: recordings/{year}/{month}）",
+            default="recordings",
+        ).strip()
+        # 保存先が Vault の外に出ないよう、絶対パスと ".." を含むパスは受け付けない
+        # （PureWindowsPath は "/" と "\\" の両方を区切りとして扱う）
+        path = PureWindowsPath(subfolder)
+        if path.drive or path.root or ".." in path.parts:
+            console.print(
+                "[red]✗ Vault 内の相対パスを入力してください（絶対パスや .. は使えません）。[/red]"
+            )
+            continue
+        try:
+            template_root(vault / subfolder).mkdir(parents=True, exist_ok=True)
+        except OSError as e:
+            console.print(f"[red]✗ 保存先フォルダを作成できません: {e}[/red]")
+            continue
+        return vault / subfolder
+
+
+def _level_meter(dbfs: float, floor: float, width: int = 30) -> str:
+    """マイクテスト中に表示するレベルメーター（floor〜0 dBFS）。"""
+    filled = round((dbfs - floor) / -floor * width)
+    bar = f"[green]{'█' * filled}[/green][dim]{'░' * (width - filled)}[/dim]"
+    return f"入力レベル {bar} {dbfs:6.1f} dBFS"
+
+
+def _mic_test(config: VoiceNoteConfig):
+    """設定の入力デバイスと録音設定で試し録音し、ピークレベルと結果を表示する。"""
+    try:
+        # 設定の読み書きだけなら録音ライブラリ（PortAudio）は要らないため、使うときだけ読み込む
+        from recorder import LEVEL_FLOOR_DBFS, level_dbfs, run_mic_test
+
+        def meter(level: float) -> str:
+            return _level_meter(level_dbfs(level), LEVEL_FLOOR_DBFS)
+
+        with Live(meter(0.0), console=console, transient=True) as live:
+            result = run_mic_test(
+                config.input_device,
+                on_level=lambda level: live.update(meter(level)),
+                sample_rate=config.capture_sample_rate,
+                buffer_frames=config.buffer_frames,
+                gain_db=config.gain_db,
+                channels=2 if config.stereo_capture else 1,
+            )
+    except Exception as e:
+        console.print(f"[yellow]⚠ 入力デバイスを開けません: {e}[/yellow]")
+        return
+    console.print(f"  入力デバイス: {result.device_name}（ピーク {result.peak_dbfs:.1f} dBFS）")
+    if result.warning:
+        console.print(f"[yellow]⚠ {result.warning}[/yellow]")
+        console.print("[dim]別の入力デバイスは --device NAME --save-device で指定できます。[/dim]")
+    else:
+        console.print("[green]✓ マイクから音声を受信できました[/green]")
+
+
+def _default_model_choice(previous: VoiceNoteConfig | None) -> str:
+    """モデル選択メニューの既定の番号。設定済みのモデルがあればそれ、無ければ small。"""
+    model = previous.whisper_model if previous else "small"
+    if model not in WHISPER_MODELS:
+        model = "small"
+    return str(WHISPER_MODELS.index(model) + 1)
+
+
+def configure_interactive(previous: VoiceNoteConfig | None = None) -> VoiceNoteConfig:
+    """対話的に設定を入力する（CLI用）
+
+    previous（設定し直す前の設定）を渡すと、モデル選択の既定値をそのモデルにし、
+    対話で尋ねない項目はその値を引き継ぐ。
+    """
     console.print(
         Panel.fit(
             "[bold cyan]初回設定[/bold cyan]\n設定項目を入力してください。",
@@ -102,13 +729,19 @@ def configure_interactive() -> VoiceNoteConfig:
         )
     )
 
-    # 保存先フォルダ（絶対パス）
+    # 保存先フォルダ（Obsidian の Vault が見つかれば一覧から選べる。無ければ絶対パスを入力）
+    vaults = known_obsidian_vaults()
+    vault_folder = _ask_vault_folder(vaults) if vaults else None
     while True:
-        save_folder = Prompt.ask(
-            "[bold]保存先フォルダの絶対パス[/bold]（例: /Users/xxx/Obsidian/recordings）"
-        )
-        save_folder_path = Path(save_folder).expanduser().resolve()
-        if save_folder_path.parent.exists():
+        if vault_folder is not None:
+            save_folder_path, vault_folder = vault_folder, None
+        else:
+            save_folder = Prompt.ask(
+                "[bold]保存先フォルダの絶対パス[/bold]"
+                "（例: /Users/xxx/Obsidian/recordings/{year}/{month}）"
+            )
+            save_folder_path = vault_root(Path(save_folder).expanduser().resolve())
+        if template_root(save_folder_path).parent.exists():
             console.print(f"[green]✓ 保存先フォルダ: {save_folder_path}[/green]")
             break
         else:
@@ -136,21 +769,22 @@ def configure_interactive() -> VoiceNoteConfig:
     whisper_model = "small"
     if transcription_mode == "local":
         console.print("\n[bold]使用するWhisperモデルを選択してください:[/bold]")
-        console.print("  1. tiny     (最速・精度低)")
-        console.print("  2. base     (高速・精度中)")
-        console.print("  3. small    (標準)")
-        console.print("  4. medium   (精度高・時間かかる)")
-        console.print("  5. large-v3 (最高精度・最も時間かかる)")
+        for i, name in enumerate(WHISPER_MODELS, start=1):
+            description = _MODEL_DESCRIPTIONS.get(name, "英語専用・英語なら高精度")
+            console.print(f"  {i}. {name:<9} ({description})")
 
-        model_map = {"1": "tiny", "2": "base", "3": "small", "4": "medium", "5": "large-v3"}
+        model_map = {str(i): name for i, name in enumerate(WHISPER_MODELS, start=1)}
         while True:
-            choice = Prompt.ask("[bold]選択[/bold]", default="3")
+            choice = Prompt.ask("[bold]選択[/bold]", default=_default_model_choice(previous))
             if choice in model_map:
                 whisper_model = model_map[choice]
                 console.print(f"[green]✓ モデル '{whisper_model}' を選択しました[/green]")
+                warning = model_download_warning(whisper_model)
+                if warning:
+                    console.print(f"[yellow]⚠ {warning}[/yellow]")
                 break
             else:
-                console.print("[red]✗ 1-5の数字を入力してください。[/red]")
+                console.print(f"[red]✗ 1-{len(model_map)}の数字を入力してください。[/red]")
 
     # OpenAI APIキー設定（openaiモード選択時）
     openai_api_key = None
@@ -212,7 +846,15 @@ def configure_interactive() -> VoiceNoteConfig:
         else:
             console.print("[red]✗ 1・2・3のいずれかを入力してください。[/red]")
 
-    return VoiceNoteConfig(
+    # マイクテスト（既定の入力デバイスの取り違えやマイク権限の不足を、最初の録音の前に見つける）
+    console.print("\n[bold]マイクをテストしますか？[/bold]")
+    console.print("  3秒間試し録音し、入力レベルと音声が届いたかを表示します。何か話してください。")
+    if Prompt.ask("[bold]マイクテスト[/bold]", choices=["y", "n"], default="y") == "y":
+        _mic_test(previous or VoiceNoteConfig())
+
+    # 対話で尋ねない項目（input_device・presets など）は既存の設定を引き継ぐ
+    return replace(
+        previous or VoiceNoteConfig(),
         save_folder=str(save_folder_path),
         whisper_model=whisper_model,
         transcription_mode=transcription_mode,
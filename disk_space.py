@@ -0,0 +1,42 @@
+"""
+空き容量チェックモジュール
+録音やモデルのダウンロードの前に保存先ボリュームの空き容量を確認し、
+途中でディスクが一杯になって壊れたファイルができるのを防ぐ
+"""
+
+import shutil
+from pathlib import Path
+
+from errors import VoiceNoteError
+
+_MB = 1024 * 1024
+
+
+class InsufficientDiskSpaceError(VoiceNoteError, RuntimeError):
+    """保存先ボリュームの空き容量が必要量に満たない場合に送出する。"""
+
+
+def free_megabytes(path: Path | str) -> int:
+    """path（無ければ最も近い既存の親フォルダ）があるボリュームの空き容量を MB で返す。"""
+    path = Path(path).expanduser()
+    for candidate in (path, *path.parents):
+        if candidate.exists():
+            return shutil.disk_usage(candidate).free // _MB
+    raise FileNotFoundError(f"フォルダが見つかりません: {path}")
+
+
+def ensure_free_space(path: Path | str, required_mb: int, purpose: str):
+    """path のボリュームに required_mb 以上の空きがあるか確認する。0 以下なら確認しない。
+
+    Raises:
+        InsufficientDiskSpaceError: 空き容量が足りない場合
+    """
+    if required_mb <= 0:
+        return
+    free = free_megabytes(path)
+    if free < required_mb:
+        raise InsufficientDiskSpaceError(
+            f"空き容量が不足しているため{purpose}を中止しました: {path}"
+            f"（空き {free}MB / 必要 {required_mb}MB）。"
+            "不要なファイルを削除するか、設定の min_free_disk_mb を見直してください。"
+        )
@@ -0,0 +1,162 @@
+"""
+動作環境の診断モジュール（CLI --doctor）
+初回実行時につまずきやすい箇所（設定・保存先・マイク・モデルと言語・モデルの保存先・
+ダウンロード元）を確認する
+"""
+
+import os
+import urllib.error
+import urllib.request
+from collections.abc import Callable
+from dataclasses import dataclass
+from pathlib import Path
+
+from config import (
+    CONFIG_PATH,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    load_config,
+    model_language_warning,
+)
+from note_writer import SaveFolderUnavailableError, ensure_save_folder_available, template_root
+from recorder import ThreadedRecorder, resolve_device_id
+from transcriber import model_cache_dir
+
+# faster-whisper がモデルをダウンロードする Hugging Face Hub
+HF_HUB_URL = "https://huggingface.co"
+NETWORK_TIMEOUT_SECONDS = 5.0
+# 入力デバイスから無音でない音声が届くまで待つ秒数
+DEVICE_CHECK_SECONDS = 2.0
+
+
+@dataclass(frozen=True)
+class DoctorCheck:
+    """診断項目1件の結果。"""
+
+    name: str
+    ok: bool
+    detail: str
+
+
+def _writable_dir(path: Path) -> Path | None:
+    """path（無ければ作成されるはずの最も近い既存の親）が書き込めるなら、そのフォルダを返す。"""
+    for candidate in (path, *path.parents):
+        if candidate.exists():
+            return candidate if os.access(candidate, os.W_OK) else None
+    return None
+
+
+def check_config(config_path: Path = CONFIG_PATH) -> tuple[DoctorCheck, VoiceNoteConfig | None]:
+    """設定ファイルを読み込めるか確認し、読み込めた設定も返す。"""
+    name = "設定ファイル"
+    try:
+        config = load_config(config_path)
+    except InvalidConfigError as e:
+        return DoctorCheck(name, False, str(e)), None
+    if config is None:
+        return DoctorCheck(name, False, f"{config_path} がありません（--config で作成）"), None
+    return DoctorCheck(name, True, str(config_path)), config
+
+
+def check_save_folder(config: VoiceNoteConfig | None) -> DoctorCheck:
+    """保存先フォルダが存在し（または作成でき）、書き込めるか確認する。"""
+    name = "保存先フォルダ"
+    if config is None or not config.save_folder:
+        return DoctorCheck(name, False, "保存先フォルダが設定されていません")
+    try:
+        ensure_save_folder_available(config.save_folder)
+    except SaveFolderUnavailableError as e:
+        return DoctorCheck(name, False, str(e).splitlines()[0])
+    root = template_root(config.save_folder)
+    if _writable_dir(root) is None:
+        return DoctorCheck(name, False, f"書き込み権限がありません: {root}")
+    return DoctorCheck(name, True, str(root))
+
+
+def check_input_device(
+    config: VoiceNoteConfig | None,
+    recorder_factory: Callable[[VoiceNoteConfig], ThreadedRecorder] | None = None,
+    timeout: float = DEVICE_CHECK_SECONDS,
+) -> DoctorCheck:
+    """設定の入力デバイスを開き、無音でない音声が届くか確認する。
+
+    録音と同じく input_device・capture_sample_rate・buffer_frames・gain_db・stereo_capture
+    で開く。input_device が未設定ならデフォルト入力デバイスを使う。
+    """
+    name = "入力デバイス"
+    config = config or VoiceNoteConfig()
+    try:
+        recorder = (recorder_factory or _configured_recorder)(config)
+        recorder.start()
+    except Exception as e:
+        return DoctorCheck(name, False, f"入力デバイスを開けません: {e}")
+    try:
+        hint = recorder.input_problem_hint(timeout)
+    finally:
+        recorder.stop(drain=0)
+    if hint:
+        return DoctorCheck(name, False, hint)
+    return DoctorCheck(name, True, f"{recorder.sample_rate}Hz で音声を受信できました")
+
+
+def _configured_recorder(config: VoiceNoteConfig) -> ThreadedRecorder:
+    """録音時と同じ入力デバイス・サンプルレート・バッファ・ゲイン・チャンネル数の録音器を作る。"""
+    return ThreadedRecorder(
+        resolve_device_id(config.input_device),
+        config.capture_sample_rate,
+        config.buffer_frames,
+        config.gain_db,
+        2 if config.stereo_capture else 1,
+    )
+
+
+def check_model_language(config: VoiceNoteConfig | None) -> DoctorCheck:
+    """英語専用モデルに英語以外の言語の設定を組み合わせていないか確認する。"""
+    name = "モデルと言語"
+    config = config or VoiceNoteConfig()
+    warning = model_language_warning(config)
+    if warning:
+        return DoctorCheck(name, False, warning)
+    language = config.language or "自動判定"
+    return DoctorCheck(name, True, f"{config.whisper_model}（language: {language}）")
+
+
+def check_model_dir(path: Path | None = None) -> DoctorCheck:
+    """モデルのダウンロード先に書き込めるか確認する。"""
+    name = "モデルの保存先"
+    path = path or model_cache_dir()
+    if _writable_dir(path) is None:
+        return DoctorCheck(name, False, f"書き込み権限がありません: {path}")
+    return DoctorCheck(name, True, str(path))
+
+
+def check_model_download(
+    url: str = HF_HUB_URL, timeout: float = NETWORK_TIMEOUT_SECONDS
+) -> DoctorCheck:
+    """モデルのダウンロード元に接続できるか確認する。"""
+    name = "モデルのダウンロード元"
+    request = urllib.request.Request(url, method="HEAD")
+    try:
+        with urllib.request.urlopen(request, timeout=timeout):
+            pass
+    except urllib.error.HTTPError as e:
+        # 応答が返ってくれば到達はできている
+        return DoctorCheck(name, True, f"{url}（HTTP {e.code}）")
+    except (urllib.error.URLError, OSError) as e:
+        return DoctorCheck(
+            name, False, f"{url} に接続できません: {e}（ダウンロード済みのモデルは使えます）"
+        )
+    return DoctorCheck(name, True, url)
+
+
+def run_checks(config_path: Path = CONFIG_PATH) -> list[DoctorCheck]:
+    """すべての診断を実行する。"""
+    config_check, config = check_config(config_path)
+    return [
+        config_check,
+        check_save_folder(config),
+        check_input_device(config),
+        check_model_language(config),
+        check_model_dir(),
+        check_model_download(),
+    ]
@@ -0,0 +1,34 @@
+"""
+例外の階層
+voicenote が送出する例外の基底クラスと、録音・モデルのロード・文字起こしの失敗を表す例外。
+ライブラリ API の利用側（独自の GUI など）は、失敗の種類ごとに例外クラスで処理を分けられる。
+既存の呼び出し元が捕捉している ValueError / RuntimeError も継承して互換性を保つ。
+"""
+
+
+class VoiceNoteError(Exception):
+    """voicenote が送出する例外の基底クラス。"""
+
+
+class DeviceNotFoundError(VoiceNoteError, ValueError):
+    """指定した入力デバイスが見つからない・入力として使えない場合に送出する。"""
+
+
+class DeviceUnavailableError(VoiceNoteError, RuntimeError):
+    """録音を開始できる入力デバイスが無い場合に送出する。"""
+
+
+class ModelLoadError(VoiceNoteError, RuntimeError):
+    """ダウンロード済みの Whisper モデルをロードできない場合に送出する。"""
+
+
+class ModelDownloadError(ModelLoadError):
+    """Whisper モデルをダウンロードできない場合に送出する。"""
+
+
+class ModelDownloadTimeoutError(ModelDownloadError):
+    """モデルのダウンロードで接続・受信が model_download_timeout 秒止まった場合に送出する。"""
+
+
+class TranscriptionError(VoiceNoteError, RuntimeError):
+    """文字起こしに失敗した場合に送出する。"""
@@ -1,14 +1,19 @@
 """
 文字起こしテキスト整形モジュール
-ルールベース整形とLLM（GPT-4o-mini）による整形を提供する
+ルールベース整形とLLM（GPT-4o-mini）による整形、外部コマンドによる要約を提供する
 """
 
 import re
-from collections.abc import Callable
+import shlex
+import subprocess
+from collections.abc import Callable, Sequence
 
 from config import VoiceNoteConfig, resolve_api_key
 
-# 日本語フィラー語パターン（単独出現かつ文脈に依存しない語）
+# 要約・後処理コマンドの実行を打ち切るまでの秒数（ローカルLLMは長い文字起こしで時間がかかる）
+SUMMARY_TIMEOUT_SECONDS = 600
+POSTPROCESS_TIMEOUT_SECONDS = 600
+
 _FILLER_PATTERNS = [
     r"(?<![^\s。！？])えーと(?=[^\s。！？]|\s|$)",
     r"(?<![^\s。！？])えっと(?=[^\s。！？]|\s|$)",
@@ -19,20 +24,76 @@ _FILLER_PATTERNS = [
     r"(?<![^\s。！？])なんか(?=\s|$|、|。)",
 ]
 
+# フィラーの前後に来てよい区切り（空白・句読点）
+_FILLER_BOUNDARY = r"\s、。，．,.！!？?"
+_SENTENCE_END = "。．.！!？?"
+
+# _apply_rule_based_format: 行の途中の文末（後ろに続く文字がある句点など）
+_SENTENCE_BREAK_RE = re.compile(r"([。！？])[^\S\n]*(?=\S)")
+# clean_transcript: 文末とみなす末尾の文字（閉じ括弧の前の句点も含む）
+_SENTENCE_END_RE = re.compile(r"[。！？.!?][」』）)]*$")
+# clean_transcript: 空白なしでつなぐ日本語の文字（句読点・かな・漢字・全角文字）
+_CJK_RE = re.compile(r"[\u3000-\u30ff\u3400-\u9fff\uff00-\uffef]")
+
+
+def _filler_pattern(words: Sequence[str]) -> re.Pattern | None:
+    """words のいずれかが単独で現れる箇所（直後の句読点・空白を含む）に一致する正規表現を作る。"""
+    words = sorted({w.strip() for w in words} - {""}, key=len, reverse=True)
+    if not words:
+        return None
+    alternatives = "|".join(re.escape(w) for w in words)
+    return re.compile(
+        rf"(?<![^{_FILLER_BOUNDARY}])(?:{alternatives})[ーｰ〜~]*"
+        rf"(?P<tail>[、，,]\s*|[{_SENTENCE_END}]\s*|\s+|$)",
+        re.IGNORECASE,
+    )
+
+
+def _drop_filler(match: re.Match) -> str:
+    """フィラーを消す。文の途中のフィラーが文末の句点を伴う場合は句点を残す。"""
+    tail = match.group("tail").strip()
+    before = match.string[: match.start()].rstrip()
+    if tail and tail in _SENTENCE_END and before and before[-1] not in _SENTENCE_END:
+        return match.group("tail")
+    return ""
+
 
-def _apply_rule_based_format(text: str) -> str:
+def remove_filler_words(text: str, words: Sequence[str]) -> str:
+    """1行のテキストから、単独で現れるフィラー（filler_words。「えーと、」など）を取り除く。
+
+    前後が空白・句読点・行の端で区切られているものだけが対象で、
+    「あのーそれで」のように語と続いているものは残す。
+    フィラーの伸ばし（「えーー」）や英字の大文字小文字の違いは同じフィラーとして扱う。
+    """
+    pattern = _filler_pattern(words)
+    if pattern is None:
+        return text
+    text, removed = pattern.subn(_drop_filler, text)
+    if not removed:
+        return text
+    # フィラーの前の読点・空白が文末に残った「はい、。」を整える
+    return re.sub(rf"[、，,]?\s*(?=[{_SENTENCE_END}]|$)", "", text).strip()
+
+
+def _apply_rule_based_format(
+    text: str, filler_words: Sequence[str] = (), keep_line_breaks: bool = False
+) -> str:
     """
     ルールベースで文字起こしテキストを整形する。
 
     処理内容:
     1. 連続スペースを1つに正規化
-    2. 句点・感嘆符・疑問符の後に改行を挿入
-    3. フィラー語（えーと、あー等）を除去
+    2. 句点・感嘆符・疑問符の後に改行を挿入（keep_line_breaks なら既存の改行・空行と、
+       `- ` の箇条書きの行はそのまま。そうでなければ句点の後の空行も1つの改行にまとめる）
+    3. フィラー語（えーと、あー等）を除去（filler_words があれば先にそれを除く）
     4. 連続する同一フレーズを圧縮
     5. 連続改行を正規化
 
     Args:
         text: 整形対象のテキスト
+        filler_words: 追加で取り除くフィラー語（filler_filter が有効な場合の filler_words）
+        keep_line_breaks: セグメントの区切り（segment_separator の blank・single・bullet）を
+            崩さない
 
     Returns:
         整形後のテキスト
@@ -44,9 +105,18 @@ def _apply_rule_based_format(text: str) -> str:
     result = re.sub(r" {2,}", " ", text)
 
     # 2. 句点・感嘆符・疑問符の後に改行を挿入（既に改行がある場合はスキップ）
-    result = re.sub(r"([。！？])\s*(?!\n)", r"\1\n", result)
+    if keep_line_breaks:
+        # segment_separator の空行や箇条書き（1セグメント1項目）を崩さないよう、行ごとに処理する
+        result = "\n".join(
+            line if line.lstrip().startswith("- ") else _SENTENCE_BREAK_RE.sub(r"\1\n", line)
+            for line in result.split("\n")
+        )
+    else:
+        result = re.sub(r"([。！？])\s*(?!\n)", r"\1\n", result)
 
-    # 3. フィラー語を除去
+    # 3. フィラー語を除去（filler_words は行をつなげないよう行ごとに処理する）
+    if filler_words:
+        result = "\n".join(remove_filler_words(line, filler_words) for line in result.split("\n"))
     for pattern in _FILLER_PATTERNS:
         result = re.sub(pattern, "", result)
 
@@ -60,6 +130,45 @@ def _apply_rule_based_format(text: str) -> str:
     return result.strip()
 
 
+def _join_sentence(head: str, tail: str) -> str:
+    """文の途中で切れた2つの断片をつなぐ。日本語どうしは空白なし、それ以外は空白1つ。"""
+    if not head:
+        return tail
+    if _CJK_RE.match(head[-1]) and _CJK_RE.match(tail[0]):
+        return head + tail
+    return f"{head} {tail}"
+
+
+def clean_transcript(text: str) -> str:
+    """
+    文字起こしを連続した段落の読みやすい文章にする（dual_output の `_clean.md` 用）。
+
+    処理内容（入力だけで結果が決まる単純な規則のみ）:
+    1. 行頭の箇条書きの記号（`- `）と、行内の連続する空白を除く
+    2. 段落内の行（セグメント）を1つの段落につなげる
+    3. 文末（。！？.!?）で終わらない段落は、文の途中で切れたものとして次の段落につなげる
+
+    Args:
+        text: 文字起こしテキスト（空行で段落が区切られたもの）
+
+    Returns:
+        空行で段落を区切ったテキスト
+    """
+    paragraphs: list[str] = []
+    current = ""
+    for block in re.split(r"\n\s*\n", text):
+        for line in block.splitlines():
+            line = " ".join(re.sub(r"^\s*- ", "", line).split())
+            if line:
+                current = _join_sentence(current, line)
+        if current and _SENTENCE_END_RE.search(current):
+            paragraphs.append(current)
+            current = ""
+    if current:
+        paragraphs.append(current)
+    return "\n\n".join(paragraphs)
+
+
 def _apply_llm_format(
     text: str,
     api_key: str,
@@ -141,7 +250,8 @@ def format_transcription(
 
     Args:
         text: 整形対象のテキスト
-        config: 設定（format_mode を参照）
+        config: 設定（format_mode・segment_separator と、filler_filter が有効なら filler_words
+            を参照）
         progress_callback: 進捗メッセージを受け取るコールバック
 
     Returns:
@@ -153,13 +263,16 @@ def format_transcription(
             progress_callback(msg)
 
     format_mode = config.format_mode
+    filler_words = config.filler_words if config.filler_filter else ()
+    # pause（既定）は従来どおり句点の後の空行もまとめ、他の区切りはそのまま残す
+    keep_line_breaks = config.segment_separator != "pause"
 
     if format_mode == "none" or not text:
         return text
 
     if format_mode == "rule":
         notify("テキストを整形中...")
-        result = _apply_rule_based_format(text)
+        result = _apply_rule_based_format(text, filler_words, keep_line_breaks)
         notify("✓ テキスト整形完了（ルールベース）")
         return result
 
@@ -167,13 +280,125 @@ def format_transcription(
         api_key = resolve_api_key(config)
         if not api_key:
             notify("⚠ OPENAI_API_KEYが設定されていません。ルールベース整形を使用します。")
-            return _apply_rule_based_format(text)
+            return _apply_rule_based_format(text, filler_words, keep_line_breaks)
 
         notify("LLMでテキストを整形中...")
         # ルールベースで前処理してからLLMに渡す（トークン節約）
-        preprocessed = _apply_rule_based_format(text)
+        preprocessed = _apply_rule_based_format(text, filler_words, keep_line_breaks)
         result = _apply_llm_format(preprocessed, api_key, progress_callback)
         notify("✓ テキスト整形完了（LLM）")
         return result
 
     return text
+
+
+def summarize_transcription(
+    text: str,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+) -> str | None:
+    """
+    `summary_command` にテキストを標準入力で渡し、その標準出力を要約として返す。
+
+    Args:
+        text: 要約対象のテキスト
+        config: 設定（summary_command を参照）
+        progress_callback: 進捗メッセージを受け取るコールバック
+
+    Returns:
+        要約。未設定・出力が空の場合や、コマンドが失敗した場合（警告を通知する）は None
+    """
+
+    def notify(msg: str):
+        if progress_callback:
+            progress_callback(msg)
+
+    if not config.summary_command or not text:
+        return None
+
+    notify("要約を生成中...")
+    output = _run_text_command(
+        config.summary_command, text, "要約", "要約を省略します", SUMMARY_TIMEOUT_SECONDS, notify
+    )
+    if output is None:
+        return None
+
+    notify("✓ 要約完了")
+    return output or None
+
+
+def postprocess_transcription(
+    text: str,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+) -> str:
+    """
+    `postprocess_command` にテキストを標準入力で渡し、その標準出力を整えた本文として返す。
+
+    句読点の補正など、手元のツールで本文を整えるためのフック。
+
+    Args:
+        text: 文字起こしされたテキスト（整形前）
+        config: 設定（postprocess_command を参照）
+        progress_callback: 進捗メッセージを受け取るコールバック
+
+    Returns:
+        後処理した本文。未設定の場合や、コマンドが失敗した・出力が空の場合（警告を通知する）は
+        元のテキスト
+    """
+
+    def notify(msg: str):
+        if progress_callback:
+            progress_callback(msg)
+
+    if not config.postprocess_command or not text:
+        return text
+
+    notify("後処理コマンドを実行中...")
+    output = _run_text_command(
+        config.postprocess_command,
+        text,
+        "後処理",
+        "後処理せずに保存します",
+        POSTPROCESS_TIMEOUT_SECONDS,
+        notify,
+    )
+    if output is None:
+        return text
+    if not output:
+        notify("⚠ 後処理コマンドの出力が空でした。後処理せずに保存します")
+        return text
+    return output
+
+
+def _run_text_command(
+    command_line: str,
+    text: str,
+    name: str,
+    fallback: str,
+    timeout: float,
+    notify: Callable[[str], None],
+) -> str | None:
+    """command_line にテキストを標準入力で渡し、前後の空白を除いた標準出力を返す。
+
+    実行できない・失敗した場合は「⚠ {name}コマンドが…。{fallback}」と警告を通知して None を返す。
+    """
+    try:
+        command = shlex.split(command_line)
+        completed = subprocess.run(
+            command,
+            input=text,
+            capture_output=True,
+            encoding="utf-8",
+            timeout=timeout,
+            check=False,
+        )
+    except (ValueError, OSError, subprocess.TimeoutExpired) as e:
+        notify(f"⚠ {name}コマンドを実行できませんでした。{fallback}: {e}")
+        return None
+
+    if completed.returncode != 0:
+        detail = completed.stderr.strip() or f"終了コード {completed.returncode}"
+        notify(f"⚠ {name}コマンドが失敗しました。{fallback}: {detail}")
+        return None
+    return completed.stdout.strip()
@@ -7,7 +7,15 @@ from tkinter import filedialog, messagebox
 
 import customtkinter as ctk
 
-from config import CONFIG_PATH, InvalidConfigError, VoiceNoteConfig, load_config, save_config
+from config import (
+    CONFIG_PATH,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    default_recording_dir,
+    load_config,
+    save_config,
+)
+from logging_setup import add_log_file
 from recorder import list_devices
 
 from .constants import (
@@ -92,6 +100,11 @@ class App(ctk.CTk):
             self._config = config
             self._workflow.update_config(config)
             self._log("設定を読み込みました")
+            if config.log_file:
+                try:
+                    self._log(f"ログを追記するファイル: {add_log_file(config.log_file)}")
+                except OSError as e:
+                    self._log(f"⚠ ログファイルを開けません: {e}")
         else:
             self._log("設定が見つかりません。設定から保存フォルダを設定してください")
         self._log(f"ログファイル: {self._log_file}")
@@ -159,16 +172,18 @@ class App(ctk.CTk):
 
         device_label = self._device_var.get()
         device_id = parse_device_id(device_label)
-        error_message = self._workflow.start(device_id, device_label)
+        error_message = self._workflow.start(device_id, device_label, self._rec_dest())
         if error_message:
             messagebox.showerror("録音エラー", error_message)
 
     def _stop_recording(self):
         self._set_processing(True)
         self._exec_btn.configure(text="実行", fg_color=PRIMARY_BUTTON_COLOR)
-        rec_dest = Path(self._rec_dest_entry.get().strip() or str(Path.home() / "Desktop"))
         mode = self._mode_var.get()
-        self._workflow.stop_and_process(rec_dest, mode)
+        self._workflow.stop_and_process(self._rec_dest(), mode)
+
+    def _rec_dest(self) -> Path:
+        return Path(self._rec_dest_entry.get().strip() or default_recording_dir())
 
     def _run_transcribe_only(self):
         audio_path = self._file_entry.get().strip()
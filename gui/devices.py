@@ -9,8 +9,14 @@ def format_device_label(device_id: int, name: str) -> str:
 
 
 def build_device_labels(devices: list[dict]) -> list[str]:
-    """`recorder.list_devices()` の戻り値からラベル一覧を生成する。"""
-    names = [format_device_label(d["id"], d["name"]) for d in devices]
+    """`recorder.list_devices()` の戻り値からラベル一覧を生成する。
+
+    システム音声（ループバック）と推定されるデバイスには「(システム音声)」を付ける。
+    """
+    names = [
+        format_device_label(d["id"], d["name"]) + (" (システム音声)" if d.get("loopback") else "")
+        for d in devices
+    ]
     return names or ["デバイスなし"]
 
 
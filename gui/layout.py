@@ -1,9 +1,9 @@
 """App のウィジェット構築処理 (`_build_ui`) を切り出したモジュール。"""
 
-from pathlib import Path
-
 import customtkinter as ctk
 
+from config import default_recording_dir
+
 from .constants import MODE_RECORD_TRANSCRIBE, MODES
 
 
@@ -45,7 +45,7 @@ def build_ui(app) -> None:
     rec_dest_row = ctk.CTkFrame(app._rec_dest_section, fg_color="transparent")
     rec_dest_row.pack(fill="x", pady=2)
     app._rec_dest_entry = ctk.CTkEntry(rec_dest_row)
-    app._rec_dest_entry.insert(0, str(Path.home() / "Desktop"))
+    app._rec_dest_entry.insert(0, str(default_recording_dir()))
     app._rec_dest_entry.pack(side="left", fill="x", expand=True)
     ctk.CTkButton(rec_dest_row, text="...", width=36, command=app._browse_rec_dest).pack(
         side="left", padx=(4, 0)
@@ -6,7 +6,13 @@ from tkinter import filedialog, messagebox
 
 import customtkinter as ctk
 
-from config import VoiceNoteConfig
+from config import (
+    WHISPER_MODELS,
+    VoiceNoteConfig,
+    model_download_warning,
+    model_language_warning,
+)
+from note_writer import template_root
 
 
 class SettingsDialog(ctk.CTkToplevel):
@@ -64,7 +70,8 @@ class SettingsDialog(ctk.CTkToplevel):
         self._model_menu = ctk.CTkOptionMenu(
             self,
             variable=self._model_var,
-            values=["tiny", "base", "small", "medium", "large-v3"],
+            values=list(WHISPER_MODELS),
+            command=self._on_model_change,
         )
         self._model_menu.pack(fill="x", padx=20, pady=2)
 
@@ -94,6 +101,17 @@ class SettingsDialog(ctk.CTkToplevel):
         self._apikey_label.configure(text_color=("black", "white") if not is_local else "gray")
         self._apikey_entry.configure(state="normal" if not is_local else "disabled")
 
+    def _on_model_change(self, model_name: str):
+        warning = model_download_warning(model_name)
+        if warning:
+            messagebox.showwarning("ダウンロードサイズ", warning, parent=self)
+        # 設定ファイルの language などと英語専用モデルの組み合わせを、文字起こしの前に知らせる
+        language_warning = model_language_warning(
+            replace(self._config, whisper_model=model_name, transcription_mode="local")
+        )
+        if language_warning:
+            messagebox.showwarning("モデルと言語", language_warning, parent=self)
+
     def _browse_folder(self):
         folder = filedialog.askdirectory(title="保存フォルダを選択")
         if folder:
@@ -106,9 +124,10 @@ class SettingsDialog(ctk.CTkToplevel):
             messagebox.showerror("エラー", "保存フォルダを指定してください", parent=self)
             return
         folder_path = Path(save_folder).expanduser()
-        if not folder_path.parent.exists():
+        base_parent = template_root(folder_path).parent
+        if not base_parent.exists():
             messagebox.showerror(
-                "エラー", f"親ディレクトリが存在しません:\n{folder_path.parent}", parent=self
+                "エラー", f"親ディレクトリが存在しません:\n{base_parent}", parent=self
             )
             return
         api_key = self._apikey_entry.get().strip()
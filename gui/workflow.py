@@ -14,12 +14,14 @@ import time
 import traceback
 from collections.abc import Callable
 from dataclasses import dataclass
+from datetime import datetime
 from pathlib import Path
 
 import numpy as np
 
 from config import VoiceNoteConfig
-from pipeline import save_wav, transcribe_and_save
+from disk_space import ensure_free_space
+from pipeline import EmptyTranscriptionError, save_wav, transcribe_and_save, trim_recording
 from recorder import ThreadedRecorder
 
 from .constants import MODE_RECORD_ONLY, MODE_RECORD_TRANSCRIBE
@@ -63,12 +65,22 @@ class RecordingWorkflow:
         self,
         config: VoiceNoteConfig,
         callbacks: WorkflowCallbacks,
-        recorder_factory: Callable[[int | None], ThreadedRecorder] = ThreadedRecorder,
+        recorder_factory: Callable[[int | None], ThreadedRecorder] | None = None,
         log_file: Path | None = None,
     ):
         self._config = config
         self._callbacks = callbacks
-        self._recorder_factory = recorder_factory
+        # 既定では録音開始時点の設定の capture_sample_rate・buffer_frames・gain_db・
+        # stereo_capture で録音する
+        self._recorder_factory = recorder_factory or (
+            lambda device_id: ThreadedRecorder(
+                device_id,
+                self._config.capture_sample_rate,
+                self._config.buffer_frames,
+                self._config.gain_db,
+                2 if self._config.stereo_capture else 1,
+            )
+        )
         self._log_file = log_file
         self._recorder: ThreadedRecorder | None = None
         self._recording = False
@@ -81,10 +93,17 @@ class RecordingWorkflow:
     def update_config(self, config: VoiceNoteConfig) -> None:
         self._config = config
 
-    def start(self, device_id: int | None, device_label: str) -> str | None:
-        """録音を開始する。失敗時はエラーメッセージを返す。"""
-        recorder = self._recorder_factory(device_id)
+    def start(
+        self, device_id: int | None, device_label: str, rec_dest: Path | None = None
+    ) -> str | None:
+        """録音を開始する。失敗時はエラーメッセージを返す。
+
+        rec_dest（録音の保存先）を渡すと、空き容量が min_free_disk_mb 未満なら録音しない。
+        """
         try:
+            if rec_dest is not None:
+                ensure_free_space(rec_dest, self._config.min_free_disk_mb, "録音")
+            recorder = self._recorder_factory(device_id)
             recorder.start()
         except Exception as e:
             return str(e)
@@ -94,6 +113,8 @@ class RecordingWorkflow:
         self._elapsed = 0
         self._callbacks.on_recording_started()
         self._callbacks.on_log(f"録音開始 (デバイス: {device_label})")
+        for warning in recorder.warnings:
+            self._callbacks.on_log(f"⚠ {warning}")
         threading.Thread(target=self._timer_loop, daemon=True).start()
         return None
 
@@ -114,11 +135,14 @@ class RecordingWorkflow:
         except RuntimeError as e:
             self._callbacks.on_error(f"エラー: {e}")
             return
-        self._callbacks.on_log(f"録音データ取得完了 ({len(audio_data) / 16000:.1f}秒)")
+        sample_rate = recorder.sample_rate
+        self._callbacks.on_log(f"録音データ取得完了 ({len(audio_data) / sample_rate:.1f}秒)")
 
         self._callbacks.on_processing_started()
         threading.Thread(
-            target=self._process_audio, args=(audio_data, rec_dest, mode), daemon=True
+            target=self._process_audio,
+            args=(audio_data, sample_rate, rec_dest, mode, recorder.started_at),
+            daemon=True,
         ).start()
 
     def run_transcribe_only(self, audio_file: Path) -> None:
@@ -138,17 +162,48 @@ class RecordingWorkflow:
     # ──────────────── 内部: バックグラウンドスレッドで実行される処理 ────────────────
 
     def _timer_loop(self):
+        recorder = self._recorder
+        reported = 0
+        clipping_reported = False
         while self._recording:
+            for message in recorder.interruptions[reported:]:
+                self._callbacks.on_log(f"⚠ {message}")
+            reported = len(recorder.interruptions)
+            if recorder.clipped.is_set() and not clipping_reported:
+                clipping_reported = True
+                self._callbacks.on_log(f"⚠ {recorder.clipping_warning}")
             mins, secs = divmod(self._elapsed, 60)
-            self._callbacks.on_status(f"[REC]  {mins:02d}:{secs:02d}  録音中...")
+            if recorder.lost.is_set():
+                # 切断後の録音データは残っているので、停止操作で保存できる
+                self._callbacks.on_status(f"[REC]  {mins:02d}:{secs:02d}  入力が切断されました")
+            else:
+                self._callbacks.on_status(f"[REC]  {mins:02d}:{secs:02d}  録音中...")
             time.sleep(1)
             self._elapsed += 1
 
-    def _process_audio(self, audio_data: np.ndarray, rec_dest: Path, mode: str):
+    def _process_audio(
+        self,
+        audio_data: np.ndarray,
+        sample_rate: int,
+        rec_dest: Path,
+        mode: str,
+        recorded_at: datetime | None = None,
+    ):
         try:
+            trimmed = trim_recording(audio_data, sample_rate, self._config)
+            if len(trimmed) < len(audio_data):
+                removed = (len(audio_data) - len(trimmed)) / sample_rate
+                self._callbacks.on_log(f"前後の無音 {removed:.1f}秒 を取り除きました")
+                audio_data = trimmed
             self._callbacks.on_log(f"WAVファイルを書き込み中... → {rec_dest}")
             try:
-                audio_file = save_wav(audio_data, rec_dest)
+                audio_file = save_wav(
+                    audio_data,
+                    rec_dest,
+                    sample_rate,
+                    self._config.recording_format,
+                    progress_callback=self._callbacks.on_log,
+                )
                 self._callbacks.on_log(f"音声ファイルを保存: {audio_file.name}")
             except Exception as e:
                 self._callbacks.on_error(f"エラー: {e}")
@@ -159,13 +214,13 @@ class RecordingWorkflow:
                 self._callbacks.on_record_only_done(audio_file)
                 return
 
-            self._run_transcription(audio_file)
+            self._run_transcription(audio_file, recorded_at)
         except Exception:
             _logger.error("_process_audio で未捕捉の例外:\n%s", traceback.format_exc())
             log_name = self._log_file.name if self._log_file else "ログファイル"
             self._callbacks.on_error(f"予期せぬエラーが発生しました（ログを確認: {log_name}）")
 
-    def _run_transcription(self, audio_file: Path):
+    def _run_transcription(self, audio_file: Path, recorded_at: datetime | None = None):
         _logger.debug("_run_transcription 開始: %s", audio_file)
         start_time = time.time()
         self._callbacks.on_log("文字起こし開始...")
@@ -177,8 +232,12 @@ class RecordingWorkflow:
 
         try:
             saved_path = transcribe_and_save(
-                audio_file, self._config, progress_callback=on_progress
+                audio_file, self._config, progress_callback=on_progress, recorded_at=recorded_at
             )
+        except EmptyTranscriptionError as e:
+            _logger.info("空の文字起こし結果のため保存をスキップ: %s", audio_file)
+            self._callbacks.on_error(str(e))
+            return
         except Exception as e:
             _logger.error("transcribe_and_save エラー:\n%s", traceback.format_exc())
             self._callbacks.on_error(f"文字起こしエラー: {e}")
@@ -2,7 +2,8 @@
 ロギング初期化モジュール。GUI / CLI のエントリーポイントから呼び出す。
 
 `voicenote` ロガーは DEBUG 以上、ルートロガー (外部ライブラリ) は WARNING 以上を
-`logs/YYYY-MM-DD_HHMMSS.log` に出力する。
+`logs/YYYY-MM-DD_HHMMSS.log` に出力する。設定の `log_file` があれば、`voicenote` ロガーの
+INFO 以上をそのファイルにも追記する。
 """
 
 import logging
@@ -10,6 +11,7 @@ from datetime import datetime
 from pathlib import Path
 
 _LOG_DIR = Path(__file__).parent / "logs"
+_LOG_FORMAT = "%(asctime)s [%(levelname)s] %(message)s"
 
 
 def setup_logging() -> Path:
@@ -18,7 +20,7 @@ def setup_logging() -> Path:
     log_file = _LOG_DIR / f"{datetime.now().strftime('%Y-%m-%d_%H%M%S')}.log"
 
     file_handler = logging.FileHandler(log_file, encoding="utf-8")
-    file_handler.setFormatter(logging.Formatter("%(asctime)s [%(levelname)s] %(message)s"))
+    file_handler.setFormatter(logging.Formatter(_LOG_FORMAT))
 
     logging.getLogger().setLevel(logging.WARNING)
     logger = logging.getLogger("voicenote")
@@ -26,3 +28,20 @@ def setup_logging() -> Path:
     logger.addHandler(file_handler)
 
     return log_file
+
+
+def add_log_file(path: str | Path) -> Path:
+    """`voicenote` ロガーの INFO 以上を `path` に追記する。
+
+    FileHandler は1レコードごとに flush するため、クラッシュしてもそれまでのログが残る。
+
+    Raises:
+        OSError: ログファイルを開けない場合
+    """
+    path = Path(path).expanduser()
+    path.parent.mkdir(parents=True, exist_ok=True)
+    handler = logging.FileHandler(path, encoding="utf-8")
+    handler.setLevel(logging.INFO)
+    handler.setFormatter(logging.Formatter(_LOG_FORMAT))
+    logging.getLogger("voicenote").addHandler(handler)
+    return path
@@ -4,65 +4,568 @@
 """
 
 import argparse
+import dataclasses
+import logging
+import os
+import subprocess
 import sys
+import tempfile
+from collections.abc import Callable, Iterator
+from contextlib import contextmanager
+from datetime import datetime
 from pathlib import Path
 
+import numpy as np
+import rich
 from dotenv import load_dotenv
 from rich.console import Console
 from rich.panel import Panel
-from rich.progress import Progress, SpinnerColumn, TextColumn
+from rich.prompt import Confirm, Prompt
 from rich.table import Table
 
-from config import InvalidConfigError, VoiceNoteConfig
-from logging_setup import setup_logging
-from pipeline import load_or_configure, save_wav, transcribe_and_save
-from recorder import default_input_name, list_devices, record_audio
+from benchmark import generate_sample, parse_thread_counts, run_benchmark
+from clipboard import copy_to_clipboard
+from config import (
+    CONFIG_PATH,
+    WHISPER_MODELS,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    apply_preset,
+    default_recording_dir,
+    load_config,
+    parse_meta_items,
+    resolve_editor,
+    resolve_model_settings,
+    save_config,
+)
+from config import console as config_console
+from disk_space import ensure_free_space
+from doctor import run_checks
+from logging_setup import add_log_file, setup_logging
+from note_writer import OUTPUT_FORMATS, check_append_to, normalize_tags
+from pipeline import (
+    AUDIO_EXTENSIONS,
+    EmptyTranscriptionError,
+    SavedTranscript,
+    audio_recorded_at,
+    format_segments,
+    latest_recording,
+    load_or_configure,
+    print_only_config,
+    save_result,
+    save_wav,
+    transcribe_batch,
+    transcribe_to_note,
+    transcript_summary,
+    trim_recording,
+)
+from recorder import (
+    PCM_FORMATS,
+    SilenceStopDetector,
+    decode_pcm,
+    default_input_name,
+    list_devices,
+    loopback_hint,
+    record_audio,
+    resolve_device_id,
+)
+from transcriber import backend_info, preload_model, transcribe
 
 console = Console()
+_logger = logging.getLogger("voicenote")
+# --no-progress: スピナーの代わりに進捗メッセージを1行ずつ表示する
+_plain_progress = False
+
+
+def _all_consoles() -> tuple[Console, ...]:
+    """表示に使う Console（このモジュール・対話的設定・Prompt が使う既定のもの）。"""
+    return console, config_console, rich.get_console()
+
+
+def disable_color():
+    """--no-color: 色を使わずに表示する（環境変数 NO_COLOR を設定した場合と同じ）。"""
+    os.environ["NO_COLOR"] = "1"
+    for c in _all_consoles():
+        c.no_color = True
+
+
+def display_to_stderr():
+    """--stdout: 進捗・保存先などの表示を標準エラー出力に回し、標準出力をノートの内容だけにする。"""
+    for c in _all_consoles():
+        c.stderr = True
+
+
+def write_note_to_stdout(saved: SavedTranscript):
+    """--stdout: 保存したノートの内容（frontmatter を含む）を標準出力に書き出す。
+
+    split_gap_seconds で複数のノートに分けた場合は、保存した順にすべて書き出す。
+    """
+    for path in (saved.path, *saved.split_paths):
+        sys.stdout.write(path.read_text(encoding="utf-8"))
+    sys.stdout.flush()
+
+
+@contextmanager
+def _status(message: str) -> Iterator[Callable[[str], None]]:
+    """スピナーで進捗を表示し、表示を更新する関数を返す。
+
+    --no-progress ではスピナーを使わず、メッセージが変わるたびに1行ずつ表示する
+    （ログや CI の出力に制御文字が混ざらない）。警告（⚠）は呼び出し側で表示するため出さない。
+    """
+    if not _plain_progress:
+        with console.status(message) as status:
+            yield status.update
+        return
+    last: str | None = None
+
+    def update(msg: str):
+        nonlocal last
+        if msg != last and not msg.startswith("⚠"):
+            console.print(msg, markup=False, highlight=False)
+        last = msg
+
+    update(message)
+    yield update
+
+
+def _log_progress(msg: str):
+    """進捗メッセージをログにも残す（⚠ で始まるものは警告として）。"""
+    if msg.startswith("⚠"):
+        _logger.warning(msg)
+    else:
+        _logger.info(msg)
 
 
 def print_devices():
-    """利用可能なオーディオデバイス一覧を表示"""
+    """利用可能なオーディオデバイス一覧を表示（出力専用デバイスも参考として表示）"""
     table = Table(title="利用可能なオーディオデバイス")
     table.add_column("ID", style="cyan", justify="right")
     table.add_column("デバイス名", style="green")
     table.add_column("入力Ch", justify="right")
+    table.add_column("種別")
 
-    for d in list_devices():
-        table.add_row(str(d["id"]), d["name"], str(d["input_channels"]))
+    for d in list_devices(include_outputs=True):
+        if d["input_channels"] == 0:
+            table.add_row(
+                str(d["id"]), f"[dim]{d['name']}[/dim]", "0", "[dim]出力（録音不可）[/dim]"
+            )
+        else:
+            kind = "[magenta]システム音声[/magenta]" if d["loopback"] else "入力"
+            table.add_row(str(d["id"]), d["name"], str(d["input_channels"]), kind)
 
     console.print(table)
     console.print(f"\n[dim]デフォルト入力: {default_input_name()}[/dim]")
+    console.print(f"[dim]システム音声の録音: {loopback_hint()}[/dim]")
+
+
+def print_doctor(config_path: Path = CONFIG_PATH) -> bool:
+    """動作環境の診断結果をチェックリストとして表示し、すべて成功したかを返す。"""
+    with _status("動作環境を確認しています（マイクに向かって話してください）..."):
+        checks = run_checks(config_path)
+
+    table = Table(title="動作環境の診断")
+    table.add_column("", justify="center")
+    table.add_column("項目", style="bold")
+    table.add_column("詳細")
+    for check in checks:
+        mark = "[green]✓[/green]" if check.ok else "[red]✗[/red]"
+        table.add_row(mark, check.name, check.detail if check.ok else f"[red]{check.detail}[/red]")
+    console.print(table)
+
+    failed = sum(1 for check in checks if not check.ok)
+    if failed:
+        console.print(f"[red]{failed}件の項目で問題が見つかりました[/red]")
+    else:
+        console.print("[green]すべての項目に問題はありません[/green]")
+    return failed == 0
+
+
+def print_presets(config_path: Path = CONFIG_PATH) -> bool:
+    """設定ファイルの presets（--preset で選べるプリセット）を一覧表示する。"""
+    try:
+        config = load_config(config_path) or VoiceNoteConfig()
+    except InvalidConfigError as e:
+        console.print(f"[red]{e}[/red]")
+        return False
+    if not config.presets:
+        console.print("[dim]プリセットはありません（設定ファイルの presets で追加できます）[/dim]")
+        return True
+
+    table = Table(title="プリセット")
+    table.add_column("名前", style="cyan")
+    table.add_column("設定")
+    for name, preset in config.presets.items():
+        table.add_row(name, ", ".join(f"{k}={v}" for k, v in preset.items()))
+    console.print(table)
+    return True
+
+
+def print_benchmark(
+    config: VoiceNoteConfig, audio_file: Path | None, thread_counts: list[int]
+) -> bool:
+    """文字起こし速度を計測して表で表示する。audio_file が無ければ生成した合成音声で計測する。"""
+    if config.transcription_mode != "local":
+        console.print(
+            "[red]エラー: --benchmark はローカルモード（faster-whisper）でのみ使えます[/red]"
+        )
+        return False
+    try:
+        with (
+            tempfile.TemporaryDirectory() as tmp,
+            _status("ベンチマークを準備しています...") as update,
+        ):
+            sample = audio_file or generate_sample(Path(tmp) / "benchmark.wav")
+            results = run_benchmark(sample, config, thread_counts, update)
+    except Exception as e:
+        console.print(f"[red]エラー: {e}[/red]")
+        return False
+
+    fastest = min(results, key=lambda r: r.transcribe_seconds)
+    table = Table(
+        title=f"ベンチマーク（{config.whisper_model}、音声 {results[0].audio_seconds:.0f}秒）"
+    )
+    table.add_column("cpu_threads", style="cyan", justify="right")
+    table.add_column("ロード", justify="right")
+    table.add_column("文字起こし", justify="right")
+    table.add_column("実時間比", justify="right")
+    for r in results:
+        style = "bold green" if r is fastest and len(results) > 1 else None
+        table.add_row(
+            str(r.cpu_threads or "自動"),
+            f"{r.load_seconds:.1f}秒",
+            f"{r.transcribe_seconds:.1f}秒",
+            f"{r.realtime_factor:.2f}x",
+            style=style,
+        )
+    console.print(table)
+    console.print("[dim]実時間比は音声1秒あたりの文字起こし秒数（小さいほど速い）[/dim]")
+    return True
 
 
-def _run_transcription(audio_file: Path, config: VoiceNoteConfig) -> Path:
-    """Rich Progress を駆動しつつ pipeline.transcribe_and_save を実行する。"""
-    with Progress(
-        SpinnerColumn(),
-        TextColumn("[progress.description]{task.description}"),
-        console=console,
-    ) as progress:
-        task = progress.add_task("準備中...", total=None)
+def print_version():
+    """voicenote と文字起こしバックエンドのバージョン情報を表示（バグ報告用）"""
+    table = Table(title="バージョン情報", show_header=False)
+    table.add_column("項目", style="cyan")
+    table.add_column("値", style="green")
+    for key, value in backend_info().items():
+        table.add_row(key, value)
+    console.print(table)
+
+
+def _trim_silence(audio_data: np.ndarray, sample_rate: int, config: VoiceNoteConfig) -> np.ndarray:
+    """設定に応じて前後の無音を取り除き、取り除いた長さを表示する。"""
+    trimmed = trim_recording(audio_data, sample_rate, config)
+    removed = (len(audio_data) - len(trimmed)) / sample_rate
+    if removed > 0:
+        console.print(f"[dim]前後の無音 {removed:.1f}秒 を取り除きました[/dim]")
+    return trimmed
+
+
+def _run_transcription(
+    audio_file: Path,
+    config: VoiceNoteConfig,
+    recorded_at: datetime | None = None,
+    note_name: str | None = None,
+) -> SavedTranscript | None:
+    """Rich Progress を駆動しつつ pipeline.transcribe_to_note を実行する。
+
+    recorded_at（録音開始日時）を渡すと、ノートの created に使う。
+    note_name を渡すと、その名前のノートとして保存する（既にあれば上書きする）。
+
+    文字起こし結果が空の場合は、対話端末なら保存するか確認し、そうでなければ保存せず None を返す。
+    """
+    try:
+        with _status("準備中...") as update:
+
+            def on_progress(msg: str):
+                _log_progress(msg)
+                if msg.startswith("⚠"):
+                    # 警告はスピナーの説明文だと次のメッセージで上書きされ見落とされるため、
+                    # スクロールバックに残る形でも表示する
+                    console.print(f"[yellow]{msg}[/yellow]")
+                update(msg)
+
+            saved = transcribe_to_note(
+                audio_file,
+                config,
+                progress_callback=on_progress,
+                recorded_at=recorded_at,
+                note_name=note_name,
+            )
+    except EmptyTranscriptionError as e:
+        if not (
+            sys.stdin.isatty()
+            and Confirm.ask("[yellow]文字起こし結果が空です。それでも保存しますか？[/yellow]")
+        ):
+            console.print(f"[yellow]⚠ {e}[/yellow]")
+            return None
+        saved = save_result(
+            e.result, config, recorded_at=recorded_at, audio_file=audio_file, note_name=note_name
+        )
+
+    console.print("[green]✓ 文字起こし完了[/green]")
+    if saved.raw_path:
+        console.print(f"[green]✓ タイムスタンプ付きのノート: {saved.raw_path.absolute()}[/green]")
+    for path in saved.split_paths:
+        console.print(f"[green]✓ 分けたノート: {path.absolute()}[/green]")
+    for path in saved.extra_paths:
+        console.print(f"[green]✓ 追加の保存先: {path.absolute()}[/green]")
+    return saved
+
+
+def print_segments(audio_file: Path, model_dir: Path):
+    """ローカルモデルで文字起こしし、セグメントを標準出力に表示するだけにする（保存しない）。"""
+    try:
+        config = print_only_config(model_dir)
+        result = transcribe(audio_file, config)
+    except Exception as e:
+        console.print(f"[red]エラー: {e}[/red]")
+        sys.exit(1)
+    print(format_segments(result.segments))
+
+
+def copy_transcript(saved: SavedTranscript):
+    """保存した本文をクリップボードにコピーし、結果を表示する（失敗しても処理は続ける）。"""
+    try:
+        copy_to_clipboard(saved.text)
+    except RuntimeError as e:
+        _logger.warning(str(e))
+        console.print(f"[yellow]⚠ {e}[/yellow]")
+        return
+    console.print(
+        f"[green]✓ 文字起こし結果をクリップボードにコピーしました（{len(saved.text)}文字）[/green]"
+    )
+
+
+def _run_batch(folder: Path, config: VoiceNoteConfig) -> tuple[Path, int]:
+    """フォルダ内の音声ファイルを一括で文字起こしし、インデックスノートのパスと失敗件数を返す。"""
+    with _status("準備中...") as update:
 
         def on_progress(msg: str):
+            _log_progress(msg)
             if msg.startswith("⚠"):
-                # 警告はスピナーの説明文だと次のメッセージで上書きされ見落とされるため、
-                # スクロールバックに残る形でも表示する
-                progress.console.print(f"[yellow]{msg}[/yellow]")
-            progress.update(task, description=msg)
+                console.print(f"[yellow]{msg}[/yellow]")
+            update(msg)
 
-        saved_path = transcribe_and_save(audio_file, config, progress_callback=on_progress)
-        progress.update(task, completed=True)
+        entries, index_path = transcribe_batch(folder, config, progress_callback=on_progress)
 
-    console.print("[green]✓ 文字起こし完了[/green]")
-    return saved_path
+    table = Table(title="一括文字起こし結果")
+    table.add_column("音声ファイル", style="cyan")
+    table.add_column("ノート")
+    for entry in entries:
+        if entry.note is None:
+            table.add_row(entry.source.name, f"[red]失敗: {entry.error}[/red]")
+        else:
+            table.add_row(entry.source.name, f"[green]{entry.note.name}[/green]")
+    console.print(table)
+
+    failed = sum(1 for e in entries if e.note is None)
+    console.print(
+        Panel.fit(
+            f"[bold green]完了![/bold green]（成功 {len(entries) - failed}件 / 失敗 {failed}件）\n\n"
+            f"[bold]インデックス:[/bold]\n{index_path.absolute()}",
+            border_style="green" if failed == 0 else "yellow",
+        )
+    )
+    return index_path, failed
+
+
+def open_in_editor(note_path: Path, config: VoiceNoteConfig):
+    """保存したノートをエディタで開き、閉じられるまで待つ。エディタ未設定ならパスを表示する。"""
+    command = resolve_editor(config)
+    if command is None:
+        console.print(
+            "[yellow]エディタが設定されていません（環境変数 EDITOR または設定の editor）[/yellow]"
+        )
+        console.print(str(note_path.absolute()))
+        return
+    try:
+        subprocess.run([*command, str(note_path)], check=False)
+    except OSError as e:
+        console.print(f"[red]エディタを起動できません ({command[0]}): {e}[/red]")
+        console.print(str(note_path.absolute()))
+
+
+def _record_session(args: argparse.Namespace, config: VoiceNoteConfig, dest_dir: Path) -> bool:
+    """録音 → WAV保存 → (必要なら) 文字起こしを1回分実行する。失敗時は False を返す。"""
+    recorded_at: datetime | None = None
+    stop_seconds = config.stop_on_silence_seconds
+    stop_hint = f"（{stop_seconds:g}秒黙ると自動で終了）" if stop_seconds > 0 else ""
+    if args.sample_seconds:
+        stop_hint = f"（{args.sample_seconds:g}秒で自動で終了）"
+
+    def on_start(device_name: str):
+        nonlocal recorded_at
+        recorded_at = datetime.now()
+        console.print(
+            Panel.fit(
+                f"[bold green]録音を開始します[/bold green]\n"
+                f"[dim]デバイス: {device_name}[/dim]\n"
+                f"[yellow]Ctrl+C[/yellow] で録音を終了します{stop_hint}",
+                border_style="green",
+            )
+        )
+
+    def on_stop():
+        console.print("\n[yellow]録音を停止しています...[/yellow]")
+
+    def on_warning(hint: str):
+        _logger.warning(hint)
+        console.print(f"[yellow]⚠ {hint}[/yellow]")
+        console.print("[dim]デバイス一覧は --list-devices、指定は --device で行えます[/dim]")
+
+    while True:
+        try:
+            ensure_free_space(dest_dir, config.min_free_disk_mb, "録音")
+            audio_data, sample_rate = record_audio(
+                device=args.device or config.input_device,
+                on_start=on_start,
+                on_stop=on_stop,
+                on_warning=on_warning,
+                sample_rate=config.capture_sample_rate,
+                buffer_frames=config.buffer_frames,
+                gain_db=config.gain_db,
+                max_seconds=args.sample_seconds or 0.0,
+                channels=2 if config.stereo_capture else 1,
+                stop_detector=(
+                    SilenceStopDetector(stop_seconds, config.trim_threshold)
+                    if stop_seconds > 0
+                    else None
+                ),
+            )
+        except (ValueError, RuntimeError) as e:
+            _logger.error("録音に失敗: %s", e)
+            console.print(f"[red]エラー: {e}[/red]")
+            return False
+
+        audio_data = _trim_silence(audio_data, sample_rate, config)
+        console.print(f"[green]✓ 録音完了（{len(audio_data) / sample_rate:.1f}秒）[/green]")
+        if args.record_only or args.yes or not sys.stdin.isatty():
+            break
+        # 文字起こしに時間をかける前に、失敗テイクを録り直せるようにする
+        choice = Prompt.ask(
+            "[bold]文字起こしする (t) / 録り直す (r) / 中止する (a)[/bold]",
+            choices=["t", "r", "a"],
+            default="t",
+        )
+        if choice == "r":
+            console.print("[cyan]録り直します[/cyan]")
+            continue
+        if choice == "a":
+            console.print("[yellow]録音を破棄しました[/yellow]")
+            return True
+        break
+
+    console.print("\n[cyan]Desktopに音声データを保存中...[/cyan]")
+    audio_file = save_wav(
+        audio_data,
+        dest_dir,
+        sample_rate,
+        config.recording_format,
+        progress_callback=lambda msg: console.print(f"[yellow]{msg}[/yellow]"),
+    )
+    console.print(f"[green]✓ 保存完了: {audio_file.name}[/green]")
+
+    if args.record_only:
+        console.print(
+            Panel.fit(
+                f"[bold green]録音完了![/bold green]\n\n"
+                f"[bold]保存先:[/bold]\n{audio_file.absolute()}",
+                border_style="green",
+            )
+        )
+        return True
+
+    try:
+        saved = _run_transcription(audio_file, config, recorded_at)
+    except Exception as e:
+        console.print(f"[red]{e}[/red]")
+        return False
+    if saved is None:
+        return True
+
+    console.print(
+        Panel.fit(
+            f"[bold green]完了![/bold green]\n\n"
+            f"[bold]音声ファイル:[/bold]\n{audio_file.absolute()}\n\n"
+            f"[bold]文字起こし結果:[/bold]\n{saved.path.absolute()}\n\n"
+            f"[bold]概要:[/bold]\n{transcript_summary(saved.result)}",
+            border_style="green",
+        )
+    )
+    if args.stdout:
+        write_note_to_stdout(saved)
+    if args.clipboard:
+        copy_transcript(saved)
+    if args.edit:
+        open_in_editor(saved.path, config)
+    return True
+
+
+def _run_loop(args: argparse.Namespace, config: VoiceNoteConfig, dest_dir: Path):
+    """q が入力されるまで 録音 → 文字起こし → 保存 を繰り返す。モデルは最初に1回だけロードする。"""
+    if config.transcription_mode == "local" and not args.record_only:
+        settings = resolve_model_settings(config)
+        with _status(f"モデル '{settings.whisper_model}' をロード中..."):
+            try:
+                preload_model(config)
+            except Exception as e:
+                console.print(f"[red]モデルのロードに失敗しました: {e}[/red]")
+                sys.exit(1)
+
+    count = 0
+    while True:
+        if _record_session(args, config, dest_dir):
+            count += 1
+        answer = Prompt.ask(
+            "\n[bold]Enter で次の録音を開始 / q で終了[/bold]", default="", show_default=False
+        )
+        if answer.strip().lower() == "q":
+            break
+    console.print(f"[cyan]ループモードを終了しました（{count}件）[/cyan]")
 
 
 def main():
+    global _plain_progress
     load_dotenv()
     parser = argparse.ArgumentParser(description="録音・文字起こしツール")
     parser.add_argument("--config", action="store_true", help="設定を再入力する")
+    parser.add_argument(
+        "--config-path",
+        type=str,
+        metavar="FILE",
+        help=f"読み書きする設定ファイル（既定: {CONFIG_PATH}）",
+    )
     parser.add_argument("--file", type=str, help="既存の音声ファイルを文字起こしする")
+    parser.add_argument(
+        "audio_file",
+        nargs="?",
+        metavar="AUDIO",
+        help="文字起こしする音声ファイル（--file と同じ。ファイルのドラッグ&ドロップ用）",
+    )
+    parser.add_argument(
+        "--replace-existing",
+        action="store_true",
+        help="--file のノートを音声のファイル名で保存し、前回の同じ名前のノートを上書きする",
+    )
+    parser.add_argument(
+        "--batch", type=str, metavar="DIR", help="フォルダ内の音声ファイルをまとめて文字起こしする"
+    )
+    parser.add_argument(
+        "--stdin",
+        action="store_true",
+        help="録音の代わりに標準入力から生PCM（モノラル・リトルエンディアン）を読み込む",
+    )
+    parser.add_argument(
+        "--sample-rate", type=int, default=16000, help="--stdin のサンプルレート（既定: 16000）"
+    )
+    parser.add_argument(
+        "--format",
+        choices=list(PCM_FORMATS),
+        default="s16le",
+        dest="pcm_format",
+        help="--stdin のサンプル形式（s16le: 16bit整数 / f32le: 32bit浮動小数。既定: s16le）",
+    )
     parser.add_argument(
         "--record-only", action="store_true", help="録音のみ（文字起こしをスキップ）"
     )
@@ -70,94 +573,420 @@ def main():
         "--list-devices", action="store_true", help="利用可能なオーディオデバイス一覧を表示"
     )
     parser.add_argument("--device", type=str, help="録音に使用するデバイス（名前またはID）")
+    parser.add_argument(
+        "--save-device",
+        action="store_true",
+        help="--device のデバイスを設定に保存し、次回から --device を省略しても使う",
+    )
+    parser.add_argument(
+        "-y",
+        "--yes",
+        action="store_true",
+        help="録音後の確認（文字起こし / 録り直し / 中止）を省略してそのまま文字起こしする",
+    )
+    parser.add_argument(
+        "--edit", action="store_true", help="保存したノートをエディタ ($EDITOR) で開く"
+    )
+    parser.add_argument(
+        "--loop", action="store_true", help="q で終了するまで録音・文字起こしを繰り返す"
+    )
+    parser.add_argument(
+        "--continue",
+        dest="resume",
+        action="store_true",
+        help="録音せず、直前の録音（Desktop の最新の *_recording.wav）を文字起こしする",
+    )
+    parser.add_argument(
+        "--model",
+        type=str,
+        metavar="NAME",
+        help=f"この実行だけ使う Whisper モデル（{' / '.join(WHISPER_MODELS)} など。設定は変更しない）",
+    )
+    parser.add_argument(
+        "--preset",
+        type=str,
+        metavar="NAME",
+        help="この実行だけ設定の presets のプリセットを使う（モデル・言語などを切り替える）",
+    )
+    parser.add_argument(
+        "--list-presets", action="store_true", help="設定の presets にあるプリセットを一覧表示"
+    )
+    parser.add_argument(
+        "--output-format",
+        choices=OUTPUT_FORMATS,
+        help="保存形式（md: frontmatter付きMarkdown / txt: 本文のみ / json: セグメント付き）",
+    )
+    parser.add_argument(
+        "--clipboard",
+        action="store_true",
+        help="文字起こし結果（整形後の本文）をクリップボードにもコピーする",
+    )
+    parser.add_argument(
+        "--append-to",
+        type=str,
+        metavar="NOTE",
+        help="新しいノートを作らず、保存先フォルダからの相対パスで指定したノートに追記する",
+    )
+    parser.add_argument(
+        "--meta",
+        action="append",
+        default=[],
+        metavar="KEY=VALUE",
+        help="ノートの frontmatter に項目を追加する（複数指定可）",
+    )
+    parser.add_argument(
+        "--tag",
+        action="append",
+        default=[],
+        metavar="NAME",
+        help="ノートの frontmatter の tags にタグを追加する（複数指定可）",
+    )
+    parser.add_argument(
+        "--print-only",
+        type=str,
+        metavar="MODEL_DIR",
+        help="--file をローカルのモデルで文字起こしし、セグメントを表示するだけにする（保存しない）",
+    )
+    parser.add_argument(
+        "--doctor",
+        action="store_true",
+        help="設定・保存先・マイク・モデルのダウンロード環境を診断する",
+    )
+    parser.add_argument(
+        "--benchmark",
+        action="store_true",
+        help="設定のモデルで文字起こし速度を計測する（--file で計測に使う音声を指定できる）",
+    )
+    parser.add_argument(
+        "--benchmark-threads",
+        type=str,
+        metavar="N,N,...",
+        help="--benchmark で比べる cpu_threads（例: 1,2,4,8。0 は自動）",
+    )
+    parser.add_argument(
+        "--keep-temp",
+        action="store_true",
+        help="文字起こしに使った前処理後の一時WAVを削除せずに残し、パスを表示する",
+    )
+    parser.add_argument(
+        "--sample-seconds",
+        type=float,
+        metavar="N",
+        help="N秒だけ録音して自動で終了する（マイクや処理全体の動作確認用）",
+    )
+    parser.add_argument(
+        "--stdout",
+        action="store_true",
+        help="保存したノートの内容を標準出力に書き出す（進捗や保存先の表示は標準エラー出力へ）",
+    )
+    parser.add_argument(
+        "--no-color",
+        action="store_true",
+        help="色を使わずに表示する（環境変数 NO_COLOR を設定した場合と同じ）",
+    )
+    parser.add_argument(
+        "--no-progress",
+        action="store_true",
+        help="スピナーを使わず、進捗を1行ずつ表示する（ログや CI 向け）",
+    )
+    parser.add_argument(
+        "--version", action="store_true", help="バージョンと文字起こしバックエンドの情報を表示"
+    )
     args = parser.parse_args()
+    config_path = Path(args.config_path).expanduser() if args.config_path else CONFIG_PATH
+    if args.no_color:
+        disable_color()
+    if args.stdout:
+        display_to_stderr()
+    _plain_progress = args.no_progress
+
+    if args.version:
+        print_version()
+        return
 
     if args.list_devices:
         print_devices()
         return
 
+    if args.doctor:
+        if not print_doctor(config_path):
+            sys.exit(1)
+        return
+
+    if args.list_presets:
+        if not print_presets(config_path):
+            sys.exit(1)
+        return
+
+    if args.audio_file:
+        # ファイルマネージャーからドロップされたパスは --file として扱う
+        if args.file:
+            console.print(
+                "[red]エラー: 音声ファイルは --file か引数のどちらかで指定してください[/red]"
+            )
+            sys.exit(1)
+        if Path(args.audio_file).suffix.lower() not in AUDIO_EXTENSIONS:
+            console.print(
+                f"[red]エラー: 音声ファイルではありません: {args.audio_file}"
+                f"（{' / '.join(AUDIO_EXTENSIONS)}）[/red]"
+            )
+            sys.exit(1)
+        args.file = args.audio_file
+
+    if args.replace_existing and not args.file:
+        console.print("[red]エラー: --replace-existingは--fileと一緒に指定してください[/red]")
+        sys.exit(1)
+
     if args.file and args.record_only:
         console.print("[red]エラー: --fileと--record-onlyは同時に指定できません[/red]")
         sys.exit(1)
 
+    if args.file and args.loop:
+        console.print("[red]エラー: --fileと--loopは同時に指定できません[/red]")
+        sys.exit(1)
+
+    if args.batch and (args.file or args.record_only or args.loop):
+        console.print(
+            "[red]エラー: --batchは--file/--record-only/--loopと同時に指定できません[/red]"
+        )
+        sys.exit(1)
+
+    if args.clipboard and (args.batch or args.record_only):
+        console.print("[red]エラー: --clipboardは--batch/--record-onlyと同時に指定できません[/red]")
+        sys.exit(1)
+
+    if args.resume and (args.file or args.batch or args.loop or args.stdin or args.record_only):
+        console.print(
+            "[red]エラー: --continueは--file/--batch/--loop/--stdin/--record-onlyと"
+            "同時に指定できません[/red]"
+        )
+        sys.exit(1)
+
+    if args.benchmark and (
+        args.batch or args.loop or args.stdin or args.record_only or args.resume
+    ):
+        console.print(
+            "[red]エラー: --benchmarkは--batch/--loop/--stdin/--record-only/--continueと"
+            "同時に指定できません[/red]"
+        )
+        sys.exit(1)
+
+    if args.benchmark_threads and not args.benchmark:
+        console.print(
+            "[red]エラー: --benchmark-threads は --benchmark と一緒に指定してください[/red]"
+        )
+        sys.exit(1)
+
+    if args.stdout and (args.batch or args.loop or args.record_only or args.print_only):
+        console.print(
+            "[red]エラー: --stdoutは--batch/--loop/--record-only/--print-onlyと"
+            "同時に指定できません[/red]"
+        )
+        sys.exit(1)
+
+    if args.save_device and not args.device:
+        console.print("[red]エラー: --save-device は --device と一緒に指定してください[/red]")
+        sys.exit(1)
+
+    if args.sample_seconds is not None:
+        if args.sample_seconds <= 0:
+            console.print("[red]エラー: --sample-seconds には正の秒数を指定してください[/red]")
+            sys.exit(1)
+        if args.file or args.batch or args.stdin or args.resume or args.benchmark:
+            console.print(
+                "[red]エラー: --sample-secondsは--file/--batch/--stdin/--continue/--benchmarkと"
+                "同時に指定できません[/red]"
+            )
+            sys.exit(1)
+
+    if args.sample_rate <= 0:
+        console.print("[red]エラー: --sample-rate には正のサンプルレートを指定してください[/red]")
+        sys.exit(1)
+
+    if args.stdin and (args.file or args.batch or args.loop):
+        console.print("[red]エラー: --stdinは--file/--batch/--loopと同時に指定できません[/red]")
+        sys.exit(1)
+
+    if args.print_only:
+        if not args.file:
+            console.print(
+                "[red]エラー: --print-only には --file で音声ファイルを指定してください[/red]"
+            )
+            sys.exit(1)
+        print_segments(Path(args.file), Path(args.print_only))
+        return
+
+    if args.stdin and sys.stdin.isatty():
+        console.print("[red]エラー: --stdin にはパイプで音声データを渡してください[/red]")
+        sys.exit(1)
+
     setup_logging()
     try:
-        config = load_or_configure(force_config=args.config)
+        # 標準入力は音声データに使うため、--stdin では対話的設定にフォールバックしない
+        config = load_or_configure(
+            force_config=args.config, interactive_fallback=not args.stdin, config_path=config_path
+        )
     except (InvalidConfigError, RuntimeError) as e:
         console.print(f"[red]{e}[/red]")
         sys.exit(1)
-    desktop = Path.home() / "Desktop"
+    if config.log_file:
+        try:
+            add_log_file(config.log_file)
+        except OSError as e:
+            console.print(f"[yellow]⚠ ログファイルを開けません: {e}[/yellow]")
+    if args.save_device:
+        # プリセットなどの一時的な上書きを適用する前の設定に保存する
+        try:
+            resolve_device_id(args.device)
+            config = dataclasses.replace(config, input_device=args.device)
+            save_config(config_path, config)
+        except (ValueError, RuntimeError) as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        console.print(f"[green]入力デバイス '{args.device}' を設定に保存しました[/green]")
+    if args.preset:
+        try:
+            config = apply_preset(config, args.preset)
+        except ValueError as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+    if args.model:
+        if config.transcription_mode != "local":
+            console.print("[yellow]⚠ OpenAI APIモードのため --model は使われません[/yellow]")
+        config = dataclasses.replace(config, whisper_model=args.model)
+    if args.output_format:
+        config = dataclasses.replace(config, output_format=args.output_format)
+    if args.append_to:
+        # 文字起こしを終えてから追記先の誤りに気づかないよう、先に確かめる
+        try:
+            check_append_to(args.append_to)
+        except ValueError as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        config = dataclasses.replace(config, append_to=args.append_to)
+    if args.keep_temp:
+        config = dataclasses.replace(config, keep_temp=True)
+    if args.meta:
+        try:
+            meta = parse_meta_items(args.meta)
+        except ValueError as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        config = dataclasses.replace(config, frontmatter_extra={**config.frontmatter_extra, **meta})
+    if args.tag:
+        try:
+            tags = normalize_tags([*config.extra_tags, *args.tag])
+        except ValueError as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        config = dataclasses.replace(config, extra_tags=tags)
+    if args.benchmark:
+        try:
+            thread_counts = (
+                parse_thread_counts(args.benchmark_threads)
+                if args.benchmark_threads
+                else [resolve_model_settings(config).cpu_threads]
+            )
+        except ValueError as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        if not print_benchmark(config, Path(args.file) if args.file else None, thread_counts):
+            sys.exit(1)
+        return
+    desktop = default_recording_dir()
+    # ここから先はノートを保存する（--record-only は録音の保存だけなので保存先フォルダは要らない）
+    if not config.save_folder and not args.record_only:
+        console.print("[red]エラー: 設定がありません。先に --config で設定してください[/red]")
+        sys.exit(1)
+
+    if args.batch:
+        folder = Path(args.batch)
+        if not folder.is_dir():
+            console.print(f"[red]エラー: フォルダが見つかりません: {folder}[/red]")
+            sys.exit(1)
+        try:
+            index_path, failed = _run_batch(folder, config)
+        except Exception as e:
+            console.print(f"[red]{e}[/red]")
+            sys.exit(1)
+        if args.edit:
+            open_in_editor(index_path, config)
+        if failed:
+            sys.exit(1)
+        return
 
-    if args.file:
+    audio_file = None
+    recorded_at = None
+    note_name = None
+    if args.stdin:
+        try:
+            audio_data = decode_pcm(sys.stdin.buffer.read(), args.pcm_format)
+        except (ValueError, RuntimeError) as e:
+            console.print(f"[red]エラー: {e}[/red]")
+            sys.exit(1)
+        audio_data = _trim_silence(audio_data, args.sample_rate, config)
+        audio_file = save_wav(
+            audio_data,
+            desktop,
+            args.sample_rate,
+            config.recording_format,
+            progress_callback=lambda msg: console.print(f"[yellow]{msg}[/yellow]"),
+        )
+        console.print(
+            f"[green]✓ 標準入力から読み込み完了（{len(audio_data) / args.sample_rate:.1f}秒）: "
+            f"{audio_file.name}[/green]"
+        )
+        if args.record_only:
+            return
+    elif args.resume:
+        audio_file = latest_recording(desktop)
+        if audio_file is None:
+            console.print(f"[red]エラー: 文字起こしし直せる録音がありません: {desktop}[/red]")
+            sys.exit(1)
+        console.print(f"[cyan]直前の録音を文字起こしします: {audio_file.name}[/cyan]")
+    elif args.file:
         audio_file = Path(args.file)
         if not audio_file.is_file():
             console.print(f"[red]エラー: ファイルが見つかりません: {audio_file}[/red]")
             sys.exit(1)
         console.print(f"[cyan]音声ファイル: {audio_file.name}[/cyan]")
+        # 取り込んだ録音も、ノートの created・ファイル名を実際に録音した日時にする
+        recorded_at = audio_recorded_at(audio_file)
+        if args.replace_existing:
+            # 設定を変えて文字起こしし直すたびにノートが増えないよう、音声のファイル名で保存する
+            note_name = audio_file.stem
+
+    if audio_file is not None:
         try:
-            saved_path = _run_transcription(audio_file, config)
+            saved = _run_transcription(audio_file, config, recorded_at, note_name)
         except Exception as e:
             console.print(f"[red]{e}[/red]")
             sys.exit(1)
+        if saved is None:
+            return
         console.print(
             Panel.fit(
                 f"[bold green]完了![/bold green]\n\n"
-                f"[bold]文字起こし結果:[/bold]\n{saved_path.absolute()}",
+                f"[bold]文字起こし結果:[/bold]\n{saved.path.absolute()}\n\n"
+                f"[bold]概要:[/bold]\n{transcript_summary(saved.result)}",
                 border_style="green",
             )
         )
+        if args.stdout:
+            write_note_to_stdout(saved)
+        if args.clipboard:
+            copy_transcript(saved)
+        if args.edit:
+            open_in_editor(saved.path, config)
         return
 
-    def on_start(device_name: str):
-        console.print(
-            Panel.fit(
-                f"[bold green]録音を開始します[/bold green]\n"
-                f"[dim]デバイス: {device_name}[/dim]\n"
-                f"[yellow]Ctrl+C[/yellow] で録音を終了します",
-                border_style="green",
-            )
-        )
-
-    def on_stop():
-        console.print("\n[yellow]録音を停止しています...[/yellow]")
-
-    try:
-        audio_data = record_audio(device=args.device, on_start=on_start, on_stop=on_stop)
-    except (ValueError, RuntimeError) as e:
-        console.print(f"[red]エラー: {e}[/red]")
-        sys.exit(1)
-
-    console.print("[green]✓ 録音完了[/green]")
-
-    console.print("\n[cyan]Desktopに音声データを保存中...[/cyan]")
-    audio_file = save_wav(audio_data, desktop)
-    console.print(f"[green]✓ 保存完了: {audio_file.name}[/green]")
-
-    if args.record_only:
-        console.print(
-            Panel.fit(
-                f"[bold green]録音完了![/bold green]\n\n"
-                f"[bold]保存先:[/bold]\n{audio_file.absolute()}",
-                border_style="green",
-            )
-        )
+    if args.loop:
+        _run_loop(args, config, desktop)
         return
 
-    try:
-        saved_path = _run_transcription(audio_file, config)
-    except Exception as e:
-        console.print(f"[red]{e}[/red]")
+    if not _record_session(args, config, desktop):
         sys.exit(1)
 
-    console.print(
-        Panel.fit(
-            f"[bold green]完了![/bold green]\n\n"
-            f"[bold]音声ファイル:[/bold]\n{audio_file.absolute()}\n\n"
-            f"[bold]文字起こし結果:[/bold]\n{saved_path.absolute()}",
-            border_style="green",
-        )
-    )
-
 
 if __name__ == "__main__":
     main()
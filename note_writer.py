@@ -6,45 +6,612 @@ frontmatter は Obsidian の規約に準拠しているが、他の Markdown ベ
 そのまま利用できる。
 """
 
+import json
+import re
+from collections.abc import Callable
+from dataclasses import dataclass
 from datetime import datetime
 from pathlib import Path
 
+from errors import VoiceNoteError
 
-def save_transcript(save_folder: Path, transcription: str, format_mode: str = "none") -> Path:
-    """文字起こし結果を `save_folder/YYYY-MM-DD_HHMMSS_raw.md` として保存する。
+# 保存先フォルダに使える日付プレースホルダ（例: recordings/{year}/{month}）
+_PLACEHOLDER_RE = re.compile(r"\{(year|month|day|weekday)\}")
+_WEEKDAYS = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+# クォート無しで YAML に書いても型・意味が変わらない文字列
+_YAML_PLAIN_RE = re.compile(r"^[A-Za-z][A-Za-z0-9_.\-]*$")
+_YAML_RESERVED = {"true", "false", "yes", "no", "on", "off", "null"}
+# ノートのファイル名に使う日時の書式（timestamp_format の既定値）
+DEFAULT_TIMESTAMP_FORMAT = "%Y-%m-%d_%H%M%S"
+# ファイル名に使えない（いずれかの OS で問題になる）文字
+_UNSAFE_FILENAME_CHARS = set('/\\:*?"<>|')
+# 保存形式: md（frontmatter 付き Markdown）/ txt（本文のみ）/ json（セグメント付き）
+OUTPUT_FORMATS = ("md", "txt", "json")
+# ノートの frontmatter に必ず付けるタグ。この後にノートの種別（raw / clean）、extra_tags が続く
+DEFAULT_TAGS = ("recording",)
+# ノート側で書き込むため、metadata（frontmatter_extra を含む）では上書きできない項目
+_RESERVED_METADATA_KEYS = {"created", "saved", "type", "format_mode", "tags", "text", "segments"}
+
+# Markdown として解釈されると本文の構造を壊す行: 区切り線・setext 見出しの下線（frontmatter の
+# 区切り `---` を含む）と、以降をすべてコードブロックにしてしまうコードフェンス
+_MARKDOWN_BREAKING_LINE_RE = re.compile(
+    r"^([ \t]*)(-+[ \t]*$|=+[ \t]*$|\*{3,}[ \t]*$|_{3,}[ \t]*$|`{3,}|~{3,})", re.MULTILINE
+)
+
+# Markdown（Obsidian の拡張を含む）で記法として使われる文字と、行頭の箇条書きの記号
+_MARKDOWN_SPECIAL_CHAR_RE = re.compile(r"([\\`*_\[\]#<>|~=$%^])")
+_MARKDOWN_LIST_MARKER_RE = re.compile(r"^([ \t]*)(\d*)([-+.)])(?=[ \t])", re.MULTILINE)
+_ESCAPED_BULLET_RE = re.compile(r"^\\- ", re.MULTILINE)
+# Obsidian のコールアウトの種類（note / info / quote などの既定の種類のほか、独自の種類も使える）
+_CALLOUT_TYPE_RE = re.compile(r"^[A-Za-z0-9_-]+$")
+# セグメントの録音へのリンクの既定の書式（audio_link_template）。Obsidian は埋め込んだ音声を
+# メディアフラグメント `#t=秒` の位置から再生する
+DEFAULT_AUDIO_LINK_TEMPLATE = "![[{file}#t={seconds}]]"
+
+
+def escape_markdown_body(text: str) -> str:
+    """文字起こし本文を Markdown ノートに埋め込んでも構造が壊れないようにする。
+
+    `---` だけの行やコードフェンス（```）で始まる行の先頭の記号をバックスラッシュでエスケープする。
+    Obsidian では見た目はそのままの文字として表示される。
+    """
+    return _MARKDOWN_BREAKING_LINE_RE.sub(r"\1\\\2", text)
+
+
+def escape_markdown_text(text: str, keep_bullets: bool = False) -> str:
+    """文字起こし本文の Markdown 記法として解釈される文字をすべてエスケープする（escape_markdown）。
+
+    `#hashtag` がタグや見出しに、`[...]` がリンクに、`*` が強調になるのを防ぎ、
+    話した内容をそのままの文字として表示させる。escape_markdown_body の処理も含む。
+    keep_bullets なら、行頭の `- `（segment_separator の箇条書き）は残して続く本文だけを
+    エスケープする。
+    """
+    text = _MARKDOWN_SPECIAL_CHAR_RE.sub(r"\\\1", text)
+    text = _MARKDOWN_LIST_MARKER_RE.sub(r"\1\2\\\3", text)
+    if keep_bullets:
+        # 行頭の箇条書きの記号だけを元に戻す
+        text = _ESCAPED_BULLET_RE.sub("- ", text)
+    return escape_markdown_body(text)
+
+
+def check_callout_type(callout_type: str):
+    """コールアウトの種類（`> [!note]` の note の部分）として使える文字列か検証する。
+
+    Raises:
+        ValueError: 英数字・`-`・`_` 以外を含む、または空の場合
+    """
+    if not isinstance(callout_type, str) or not _CALLOUT_TYPE_RE.match(callout_type):
+        raise ValueError(
+            f"callout_type は英数字・'-'・'_' で指定してください（例: note）: {callout_type!r}"
+        )
+
+
+def wrap_callout(text: str, callout_type: str = "note", folded: bool = False) -> str:
+    """本文を Obsidian のコールアウト（`> [!note]`）で囲む（wrap_callout）。
+
+    すべての行の先頭に `> ` を付け、空行は `>` にして段落の区切りを保つ。
+    folded なら折りたたんだ状態（`> [!note]-`）で表示する。
+    """
+    title = f"> [!{callout_type}]{'-' if folded else ''}"
+    return "\n".join([title, *(f"> {line}" if line else ">" for line in text.split("\n"))])
+
+
+def format_audio_link(template: str, file_name: str, start: float) -> str:
+    """セグメントの開始位置への録音のリンクを audio_link_template の書式で組み立てる。
+
+    `{file}` は録音のファイル名、`{seconds}` は開始秒（整数）、`{time}` は mm:ss に置き換える。
+    """
+    return template.format(file=file_name, seconds=int(start), time=_format_duration(start))
+
+
+def check_audio_link_template(template: str):
+    """audio_link_template として使える書式か検証する。
+
+    Raises:
+        ValueError: `{file}` を含まない、または未知のプレースホルダ・閉じていない `{` を含む場合
+    """
+    if not isinstance(template, str) or "{file}" not in template:
+        raise ValueError(f"audio_link_template には {{file}} を含めてください: {template!r}")
+    try:
+        format_audio_link(template, "recording.wav", 0.0)
+    except (AttributeError, KeyError, IndexError, ValueError) as e:
+        raise ValueError(
+            "audio_link_template に使えるのは {file} / {seconds} / {time} です: "
+            f"{template!r}（{e}）"
+        ) from e
+
+
+def normalize_tags(tags: list[str]) -> list[str]:
+    """タグの先頭の `#` を外し、重複を除いて返す（extra_tags・--tag）。
+
+    Raises:
+        ValueError: 空のタグ、または空白を含むタグ（Obsidian のタグに使えない）がある場合
+    """
+    normalized = []
+    for tag in tags:
+        name = tag.strip().removeprefix("#") if isinstance(tag, str) else ""
+        if not name or any(c.isspace() for c in name):
+            raise ValueError(f"タグは空白を含まない文字列で指定してください: {tag!r}")
+        normalized.append(name)
+    return list(dict.fromkeys(normalized))
+
+
+def _yaml_value(value: str | int | float | bool) -> str:
+    """frontmatter に埋め込むスカラー値を YAML として安全な表記に変換する。"""
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, (int, float)):
+        return repr(value)
+    if _YAML_PLAIN_RE.match(value) and value.lower() not in _YAML_RESERVED:
+        return value
+    # JSON の文字列リテラルは YAML のダブルクォート文字列としても有効
+    return json.dumps(value, ensure_ascii=False)
+
+
+def check_timestamp_format(timestamp_format: str):
+    """ファイル名用の日時書式が、どの OS でも安全なファイル名を生成するか検証する。
+
+    Raises:
+        ValueError: 空の結果になる、またはファイル名に使えない文字を含む場合
+    """
+    sample = datetime(2026, 1, 16, 12, 34, 56).strftime(timestamp_format)
+    if not sample.strip():
+        raise ValueError(f"timestamp_format が空のファイル名になります: {timestamp_format!r}")
+    unsafe = sorted({c for c in sample if c in _UNSAFE_FILENAME_CHARS or ord(c) < 32})
+    if unsafe:
+        raise ValueError(
+            f"timestamp_format にファイル名に使えない文字が含まれます: {timestamp_format!r} "
+            f"→ {sample!r}（{' '.join(repr(c) for c in unsafe)}）"
+        )
+
+
+def check_index_name(index_name: str):
+    """インデックスノートの名前（batch_index_name）が保存先フォルダ内のファイル名か検証する。
+
+    Raises:
+        ValueError: 空、`..`、またはフォルダの区切りなどファイル名に使えない文字を含む場合
+    """
+    if not isinstance(index_name, str) or index_name.strip() in ("", ".", ".."):
+        raise ValueError(f"batch_index_name にはファイル名を指定してください: {index_name!r}")
+    unsafe = sorted({c for c in index_name if c in _UNSAFE_FILENAME_CHARS or ord(c) < 32})
+    if unsafe:
+        raise ValueError(
+            f"batch_index_name にフォルダの区切りやファイル名に使えない文字が含まれます: "
+            f"{index_name!r}（{' '.join(repr(c) for c in unsafe)}）"
+        )
+
+
+def check_append_to(note_path: Path | str):
+    """追記先（append_to）が保存先フォルダからの相対パスか検証する。
+
+    Raises:
+        ValueError: 空、絶対パス、または `..` で保存先フォルダの外を指す場合
+    """
+    if not isinstance(note_path, (str, Path)) or not str(note_path).strip():
+        raise ValueError(f"追記先はノートのパスで指定してください: {note_path!r}")
+    relative = Path(note_path)
+    if relative.is_absolute() or ".." in relative.parts:
+        raise ValueError(f"追記先は保存先フォルダからの相対パスで指定してください: {note_path}")
+
+
+def template_root(save_folder: Path | str) -> Path:
+    """保存先フォルダのうちプレースホルダを含まない先頭部分を返す。
+
+    プレースホルダが無ければ `save_folder` をそのまま返す。
+    """
+    parts = Path(save_folder).parts
+    for i, part in enumerate(parts):
+        if _PLACEHOLDER_RE.search(part):
+            return Path(*parts[:i]) if i > 0 else Path(".")
+    return Path(save_folder)
+
+
+class SaveFolderUnavailableError(VoiceNoteError, RuntimeError):
+    """保存先フォルダ（の親）が存在しない場合に送出する。外部ドライブの取り外しなど。"""
+
+
+class NoteWriteError(VoiceNoteError, RuntimeError):
+    """ノート（途中経過・一括文字起こしのインデックスを含む）を書き込めない場合に送出する。"""
+
+
+def ensure_save_folder_available(save_folder: Path | str):
+    """保存先フォルダが使える状態か確認する。
+
+    設定時と同じく、プレースホルダより前の部分の親フォルダが存在することを求める。
+    外部ドライブが外れている場合などに、mkdir が意図しない場所へフォルダを作ったり
+    分かりにくい OS エラーで失敗したりする前に検出する。
+
+    Raises:
+        SaveFolderUnavailableError: 親フォルダが存在しない場合
+    """
+    root = template_root(save_folder)
+    if not root.parent.exists():
+        raise SaveFolderUnavailableError(
+            f"保存先フォルダにアクセスできません: {root}\n"
+            "外部ドライブやネットワークドライブが接続されているか確認するか、"
+            "--config（GUIでは設定画面）で保存先を設定し直してください。"
+        )
+
+
+def _expand_placeholders(text: str, now: datetime) -> str:
+    """`{year}` `{month}` `{day}` `{weekday}` を now の値で置き換える。"""
+    values = {
+        "year": f"{now.year:04d}",
+        "month": f"{now.month:02d}",
+        "day": f"{now.day:02d}",
+        "weekday": _WEEKDAYS[now.weekday()],
+    }
+    return _PLACEHOLDER_RE.sub(lambda m: values[m.group(1)], text)
+
+
+def expand_note_template(
+    template: str, now: datetime, timestamp_format: str = DEFAULT_TIMESTAMP_FORMAT
+) -> str:
+    """ノートのヘッダー・フッターのテンプレートを展開する。
+
+    保存先フォルダと同じ日付プレースホルダに加え、`{timestamp}` をファイル名と同じ
+    日時表記に置き換える。それ以外の `{...}` はそのまま残す。
+    """
+    expanded = template.replace("{timestamp}", now.strftime(timestamp_format))
+    return _expand_placeholders(expanded, now)
+
+
+def expand_save_folder(save_folder: Path | str, now: datetime | None = None) -> Path:
+    """保存先フォルダ内の日付プレースホルダを展開する。
+
+    `{year}` `{month}` `{day}` `{weekday}` を現在日時で置き換える。
+    プレースホルダ以降の階層に `..` を含めて基準フォルダの外へ出ることは許可しない。
+
+    Raises:
+        ValueError: プレースホルダ以降に `..` が含まれる場合。
+    """
+    save_folder = Path(save_folder)
+    root = template_root(save_folder)
+    if root == save_folder:
+        return save_folder
+
+    now = now or datetime.now()
+    tail = [_expand_placeholders(part, now) for part in save_folder.parts[len(root.parts) :]]
+    if ".." in tail:
+        raise ValueError(f"保存先フォルダに '..' は使用できません: {save_folder}")
+    return root.joinpath(*tail)
+
+
+def _render_markdown(
+    created: datetime,
+    saved: datetime | None,
+    transcription: str,
+    format_mode: str,
+    metadata: dict[str, str | int | float],
+    tags: list[str],
+) -> str:
+    saved_line = f"saved: {saved.isoformat()}\n" if saved else ""
+    metadata_lines = "".join(f"{_yaml_value(k)}: {_yaml_value(v)}\n" for k, v in metadata.items())
+    tag_lines = "".join(f"  - {_yaml_value(tag)}\n" for tag in tags)
+    return f"""---
+created: {created.isoformat()}
+{saved_line}type: transcription
+format_mode: {format_mode}
+{metadata_lines}tags:
+{tag_lines}---
+{transcription}
+"""
+
+
+def _render_json(
+    created: datetime,
+    saved: datetime | None,
+    transcription: str,
+    format_mode: str,
+    metadata: dict[str, str | int | float],
+    segments: list[dict] | None,
+) -> str:
+    data = {
+        "created": created.isoformat(),
+        **({"saved": saved.isoformat()} if saved else {}),
+        "format_mode": format_mode,
+        **metadata,
+        "text": transcription,
+        "segments": segments or [],
+    }
+    return json.dumps(data, ensure_ascii=False, indent=2) + "\n"
+
+
+def save_transcript(
+    save_folder: Path,
+    transcription: str,
+    format_mode: str = "none",
+    metadata: dict[str, str | int | float] | None = None,
+    output_format: str = "md",
+    segments: list[dict] | None = None,
+    timestamp_format: str = DEFAULT_TIMESTAMP_FORMAT,
+    recorded_at: datetime | None = None,
+    header: str = "",
+    footer: str = "",
+    kind: str = "raw",
+    now: datetime | None = None,
+    tags: list[str] | None = None,
+    name: str | None = None,
+) -> Path:
+    """文字起こし結果を `save_folder/YYYY-MM-DD_HHMMSS_raw.{md,txt,json}` として保存する。
+
+    同じ名前のファイルが既にあれば（同じ秒に保存した場合など）上書きせず、
+    `_raw-1.md` のように連番を付ける。name を指定した場合は日時の代わりに
+    `{name}_raw.md` のように保存し、同じ名前のファイルがあれば上書きする。
 
     Args:
-        save_folder: 保存先フォルダの絶対パス
+        save_folder: 保存先フォルダの絶対パス。日付プレースホルダ
+            (`{year}` `{month}` `{day}` `{weekday}`) を含めると created の日付で展開する。
         transcription: 文字起こしされたテキスト
         format_mode: 使用した整形モード（"none"/"rule"/"llm"）
+        metadata: frontmatter に追加する項目（duration, model, language, sample_rate など）。
+            キー・値とも YAML として安全な表記にする。created / type / tags などの既定項目は無視する
+        output_format: 保存形式。"md" は frontmatter 付き Markdown、"txt" は本文のみ、
+            "json" はメタデータとタイムスタンプ付きセグメントを含む JSON
+        segments: "json" で出力するセグメント（start, end, text を持つ dict）
+        timestamp_format: ファイル名の日時部分の strftime 書式
+        recorded_at: 録音の開始日時。指定すると `created` とファイル名の日時をこの日時にし、
+            保存日時を別項目 `saved` に記録する（省略時は `created` が保存日時）
+        header: "md" で frontmatter の直後、本文の前に入れるテンプレート（expand_note_template）
+        footer: "md" で本文の後に入れるテンプレート
+        kind: ファイル名の日時の後に付ける種別（dual_output の整形版は "clean"）
+        now: 保存日時（省略時は現在時刻）。dual_output で2つのファイル名の日時をそろえるのに使う
+        tags: "md" で frontmatter の tags に既定のタグ（DEFAULT_TAGS と kind）に続けて加えるタグ。
+            既定のタグと重複するものは1つにまとめる
+        name: ファイル名の日時の代わりに使う名前（同じ音声を文字起こしし直したノートを置き換える）
 
     Returns:
         保存されたファイルのパス
 
     Raises:
-        RuntimeError: 保存失敗時
+        ValueError: 保存先フォルダのプレースホルダ指定・保存形式・タグ・name が不正な場合
+        SaveFolderUnavailableError: 保存先フォルダにアクセスできない場合
+        NoteWriteError: 保存失敗時
     """
-    save_folder = Path(save_folder)
-    save_folder.mkdir(parents=True, exist_ok=True)
+    if output_format not in OUTPUT_FORMATS:
+        raise ValueError(
+            f"不明な保存形式です: {output_format}（{' / '.join(OUTPUT_FORMATS)} のいずれか）"
+        )
+    if name is not None and (not name.strip() or Path(name).name != name):
+        raise ValueError(f"ノートの名前にはフォルダの区切りを含められません: {name!r}")
+
+    ensure_save_folder_available(save_folder)
+    now = now or datetime.now()
+    # ファイル名の日時・日付フォルダは created（録音の開始日時、無ければ保存日時）に合わせる
+    named_at = recorded_at or now
+    save_folder = expand_save_folder(save_folder, named_at)
+
+    timestamp = named_at.strftime(timestamp_format)
+    metadata = {k: v for k, v in (metadata or {}).items() if k not in _RESERVED_METADATA_KEYS}
+    created, saved = (recorded_at, now) if recorded_at else (now, None)
+
+    if output_format == "txt":
+        content = f"{transcription}\n"
+    elif output_format == "json":
+        content = _render_json(created, saved, transcription, format_mode, metadata, segments)
+    else:
+        header = expand_note_template(header, now, timestamp_format).strip()
+        footer = expand_note_template(footer, now, timestamp_format).strip()
+        body = "\n\n".join(part for part in (header, transcription, footer) if part)
+        tags = list(dict.fromkeys([*DEFAULT_TAGS, kind, *normalize_tags(tags or [])]))
+        content = _render_markdown(created, saved, body, format_mode, metadata, tags)
+
+    try:
+        save_folder.mkdir(parents=True, exist_ok=True)
+        if name:
+            filepath = save_folder / f"{name}_{kind}.{output_format}"
+            filepath.write_text(content, encoding="utf-8")
+            return filepath
+        return _write_new_file(save_folder, f"{timestamp}_{kind}", f".{output_format}", content)
+    except Exception as e:
+        raise NoteWriteError(f"保存エラー: {e}") from e
+
+
+def _write_new_file(folder: Path, stem: str, suffix: str, content: str) -> Path:
+    """folder/stem+suffix に新規作成で書き込む。既にあれば stem-1, stem-2, ... を試す。"""
+    counter = 0
+    while True:
+        filepath = folder / f"{stem}{f'-{counter}' if counter else ''}{suffix}"
+        try:
+            # 排他的に作成し、確認と作成の間に別のプロセスが同じ名前で保存しても上書きしない
+            with open(filepath, "x", encoding="utf-8") as f:
+                f.write(content)
+            return filepath
+        except FileExistsError:
+            counter += 1
+
+
+def append_transcript(
+    save_folder: Path,
+    note_path: Path | str,
+    transcription: str,
+    now: datetime | None = None,
+) -> Path:
+    """文字起こし結果を既存ノートの末尾に日時見出し付きで追記する（無ければ作成する）。
+
+    Args:
+        save_folder: 保存先フォルダ。日付プレースホルダを含む場合はその手前の部分を基準にする
+        note_path: 追記先ノートの `save_folder` からの相対パス
+        transcription: 文字起こしされたテキスト
+        now: 見出しに使う日時（省略時は現在日時）
+
+    Returns:
+        追記したファイルのパス
+
+    Raises:
+        ValueError: 追記先が絶対パス、または `..` で保存先フォルダの外を指す場合
+        SaveFolderUnavailableError: 保存先フォルダにアクセスできない場合
+        NoteWriteError: 書き込み失敗時
+    """
+    check_append_to(note_path)
+    relative = Path(note_path)
+
+    ensure_save_folder_available(save_folder)
+    now = now or datetime.now()
+    filepath = template_root(save_folder) / relative
+    section = f"## {now.strftime('%Y-%m-%d %H:%M')}\n\n{transcription}\n"
+
+    try:
+        filepath.parent.mkdir(parents=True, exist_ok=True)
+        existing = filepath.read_text(encoding="utf-8") if filepath.exists() else ""
+        if existing:
+            # 既存の本文と見出しの間に空行を1つ入れる
+            section = ("\n" if existing.endswith("\n") else "\n\n") + section
+        with open(filepath, "a", encoding="utf-8") as f:
+            f.write(section)
+        return filepath
+    except OSError as e:
+        raise NoteWriteError(f"追記エラー: {e}") from e
 
-    timestamp = datetime.now().strftime("%Y-%m-%d_%H%M%S")
-    filepath = save_folder / f"{timestamp}_raw.md"
 
-    now = datetime.now().isoformat()
+class PartialNote:
+    """文字起こし途中のセグメントを `YYYY-MM-DD_HHMMSS_raw.partial.md` に追記していくノート。
+
+    長い録音の文字起こしが途中で失敗しても、それまでの結果が残るようにするためのもの。
+    ファイルは最初の `append` で作成する。
+    """
+
+    def __init__(
+        self,
+        save_folder: Path,
+        now: datetime | None = None,
+        timestamp_format: str = DEFAULT_TIMESTAMP_FORMAT,
+    ):
+        self._now = now or datetime.now()
+        self._folder = expand_save_folder(save_folder, self._now)
+        timestamp = self._now.strftime(timestamp_format)
+        self.path = self._folder / f"{timestamp}_raw.partial.md"
+
+    def append(self, start: float, text: str):
+        """セグメントを1行追記する。
+
+        Raises:
+            NoteWriteError: 書き込み失敗時
+        """
+        try:
+            if not self.path.exists():
+                self._folder.mkdir(parents=True, exist_ok=True)
+                header = f"""---
+created: {self._now.isoformat()}
+type: transcription-partial
+tags:
+  - recording
+  - partial
+---
+"""
+                self.path.write_text(header, encoding="utf-8")
+            with open(self.path, "a", encoding="utf-8") as f:
+                f.write(f"[{_format_duration(start)}] {text}\n")
+        except OSError as e:
+            raise NoteWriteError(f"途中経過の保存エラー: {e}") from e
+
+    def discard(self):
+        """文字起こしが完了し不要になった途中経過ファイルを削除する。"""
+        self.path.unlink(missing_ok=True)
+
+
+@dataclass(frozen=True)
+class BatchIndexEntry:
+    """一括文字起こしのインデックスノートの1行分。"""
+
+    source: Path
+    note: Path | None
+    duration: float | None = None
+    segment_count: int | None = None
+    error: str | None = None
+
+
+def _format_duration(seconds: float | None) -> str:
+    if seconds is None:
+        return "-"
+    mins, secs = divmod(int(round(seconds)), 60)
+    return f"{mins:02d}:{secs:02d}"
+
+
+def render_timestamped_segments(
+    segments: list[tuple[float, str]], link: Callable[[float], str] | None = None
+) -> str:
+    """セグメントを1行ずつ `[mm:ss] テキスト` にした本文を組み立てる（dual_output の `_raw`）。
+
+    link を渡すと、`[mm:ss]` の代わりに開始秒から作ったリンク（audio_links）を行頭に置く。
+    """
+    if link is not None:
+        return "\n".join(f"{link(start)} {text}" for start, text in segments)
+    return "\n".join(f"[{_format_duration(start)}] {text}" for start, text in segments)
+
+
+def render_chapters(chapters: list[tuple[float, str]]) -> str:
+    """章ごとに `## [mm:ss]` 見出しを付けた Markdown 本文を組み立てる。"""
+    return "\n\n".join(f"## [{_format_duration(start)}]\n\n{text}" for start, text in chapters)
+
+
+def _table_cell(text: str) -> str:
+    """Markdown テーブルのセルとして安全な文字列にする。"""
+    return text.replace("|", "\\|").replace("\n", " ")
+
+
+def save_batch_index(
+    save_folder: Path,
+    entries: list[BatchIndexEntry],
+    index_name: str = "{timestamp}_index",
+    timestamp_format: str = DEFAULT_TIMESTAMP_FORMAT,
+) -> Path:
+    """一括文字起こしの結果一覧を、各ノートへのウィキリンク付きインデックスノートとして保存する。
+
+    Args:
+        save_folder: 保存先フォルダ（日付プレースホルダ可）
+        entries: 処理した音声ファイルごとの結果
+        index_name: 拡張子を除いたファイル名。`{timestamp}` は保存日時に置き換える
+        timestamp_format: `{timestamp}` の strftime 書式
+
+    Returns:
+        保存されたインデックスノートのパス
+
+    Raises:
+        ValueError: 保存先フォルダのプレースホルダ指定・index_name が不正な場合
+        SaveFolderUnavailableError: 保存先フォルダにアクセスできない場合
+        NoteWriteError: 保存失敗時
+    """
+    check_index_name(index_name)
+    ensure_save_folder_available(save_folder)
+    now = datetime.now()
+    save_folder = expand_save_folder(save_folder, now)
+    filename = index_name.replace("{timestamp}", now.strftime(timestamp_format))
+    filepath = save_folder / f"{filename}.md"
+
+    rows = []
+    for entry in entries:
+        if entry.note is not None:
+            # Obsidian のウィキリンクは .md 以外だと拡張子まで書く必要がある
+            link = entry.note.stem if entry.note.suffix == ".md" else entry.note.name
+            note_cell = f"[[{link}]]"
+        else:
+            note_cell = _table_cell(f"⚠ 失敗: {entry.error or '不明なエラー'}")
+        segment_cell = "-" if entry.segment_count is None else str(entry.segment_count)
+        rows.append(
+            f"| {_table_cell(entry.source.name)} | {note_cell} "
+            f"| {_format_duration(entry.duration)} | {segment_cell} |"
+        )
+
+    table = "\n".join(rows)
     content = f"""---
-created: {now}
-type: transcription
-format_mode: {format_mode}
+created: {now.isoformat()}
+type: transcription-index
 tags:
   - recording
-  - raw
+  - index
 ---
-{transcription}
+# 一括文字起こし {now.strftime("%Y-%m-%d %H:%M")}
+
+| 音声ファイル | ノート | 長さ | セグメント数 |
+|---|---|---|---|
+{table}
 """
 
     try:
+        save_folder.mkdir(parents=True, exist_ok=True)
         with open(filepath, "w", encoding="utf-8") as f:
             f.write(content)
         return filepath
     except Exception as e:
-        raise RuntimeError(f"保存エラー: {e}") from e
+        raise NoteWriteError(f"インデックスの保存エラー: {e}") from e
@@ -1,26 +1,129 @@
 """
 GUI/CLI 共通の業務ロジックモジュール。
 
-`save_wav`、`load_or_configure`、`transcribe_and_save` を提供し、
-エントリーポイント (`main.py` / `main_cli.py`) からは UI に集中できるようにする。
+`save_wav`、`trim_recording`、`load_or_configure`、`transcribe_and_save`、`transcribe_batch`
+を提供し、エントリーポイント (`main.py` / `main_cli.py`) からは UI に集中できるようにする。
 """
 
+import contextlib
+import logging
+import math
+import os
+import shlex
+import subprocess
 from collections.abc import Callable
-from datetime import datetime
+from dataclasses import dataclass, replace
+from datetime import datetime, timedelta
 from pathlib import Path
 
 import numpy as np
 from scipy.io import wavfile
 
-from config import CONFIG_PATH, VoiceNoteConfig, configure_interactive, load_config, save_config
-from formatter import format_transcription
-from note_writer import save_transcript
-from recorder import SAMPLE_RATE
-from transcriber import transcribe
+from config import (
+    CONFIG_PATH,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    configure_interactive,
+    load_config,
+    save_config,
+)
+from errors import TranscriptionError, VoiceNoteError
+from formatter import (
+    clean_transcript,
+    format_transcription,
+    postprocess_transcription,
+    summarize_transcription,
+)
+from note_writer import (
+    BatchIndexEntry,
+    PartialNote,
+    append_transcript,
+    ensure_save_folder_available,
+    escape_markdown_body,
+    escape_markdown_text,
+    format_audio_link,
+    render_chapters,
+    render_timestamped_segments,
+    save_batch_index,
+    save_transcript,
+    wrap_callout,
+)
+from recorder import SAMPLE_RATE, trim_silence
+from transcriber import (
+    PartialTranscriptionError,
+    Segment,
+    TranscriptionResult,
+    split_chapters,
+    split_result,
+    transcribe,
+)
+
+_logger = logging.getLogger("voicenote")
+
+AUDIO_EXTENSIONS = (".wav", ".mp3", ".m4a", ".ogg", ".flac")
+# 録音の保存形式（recording_format）ごとの拡張子と PyAV のエンコーダ名（wav は scipy で書き出す）
+_RECORDING_CODECS = {"wav": (".wav", None), "flac": (".flac", "flac"), "opus": (".ogg", "libopus")}
+# libopus が受け付けるサンプルレート。それ以外（44.1kHz など）は OPUS_SAMPLE_RATE にして圧縮する
+_OPUS_SAMPLE_RATES = (8000, 12000, 16000, 24000, 48000)
+OPUS_SAMPLE_RATE = 48000
+# post_save_command の実行を打ち切るまでの秒数
+POST_SAVE_TIMEOUT_SECONDS = 300
+
+
+@dataclass(frozen=True)
+class SavedTranscript:
+    """保存したノートのパスと、その元になった文字起こし結果・保存した本文（整形後）。
+
+    `extra_paths` は `extra_destinations` に保存できたノートのパス。
+    `raw_path` は `dual_output` で `path`（`_clean.md`）と別に保存した `_raw.md` のパス。
+    `split_paths` は `split_gap_seconds` で分けた2つ目以降のノートのパス（`path`・`result`・
+    `text` は最初の部分のもの）。
+    """
+
+    path: Path
+    result: TranscriptionResult
+    text: str = ""
+    extra_paths: tuple[Path, ...] = ()
+    raw_path: Path | None = None
+    split_paths: tuple[Path, ...] = ()
+
+
+class EmptyTranscriptionError(VoiceNoteError, RuntimeError):
+    """文字起こし結果が空（または短すぎる）ため、ノートを保存しなかった場合に送出する。
+
+    呼び出し側がユーザーに確認した上で保存したい場合は `save_result(e.result, config)` を使う。
+    """
+
+    def __init__(self, result: TranscriptionResult):
+        super().__init__("文字起こし結果が空のため、ノートを保存しませんでした")
+        self.result = result
+
+
+def is_empty_transcription(text: str, min_chars: int) -> bool:
+    """空白を除いた文字数が `min_chars` 未満なら空とみなす。"""
+    return len("".join(text.split())) < max(min_chars, 1)
+
+
+def transcript_summary(result: TranscriptionResult) -> str:
+    """完了時に表示する概要（録音の長さ・セグメント数・文字数・単語数）を返す。
+
+    日本語は空白で単語を区切らないため、空白を除いた文字数も併せて示す。
+    """
+    parts = []
+    if result.duration is not None:
+        minutes, seconds = divmod(round(result.duration), 60)
+        parts.append(f"長さ {minutes}分{seconds:02d}秒")
+    if result.segments:
+        parts.append(f"{len(result.segments):,}セグメント")
+    parts.append(f"{len(''.join(result.text.split())):,}文字")
+    parts.append(f"{len(result.text.split()):,}語")
+    return " / ".join(parts)
 
 
 def load_or_configure(
-    force_config: bool = False, interactive_fallback: bool = True
+    force_config: bool = False,
+    interactive_fallback: bool = True,
+    config_path: Path | None = None,
 ) -> VoiceNoteConfig:
     """設定ファイルを読み込み、必要なら対話的設定を実行する。
 
@@ -28,66 +131,633 @@ def load_or_configure(
         force_config: True なら既存設定を無視し対話的設定を実行する (CLI --config)。
         interactive_fallback: 設定が無いときに対話的設定にフォールバックするか。
             GUI 側は False を指定し、空設定を受け取って設定ダイアログで補完する。
+        config_path: 読み書きする設定ファイル (CLI --config-path)。None なら CONFIG_PATH。
 
     Returns:
         設定。GUI で interactive_fallback=False かつ設定無しなら `VoiceNoteConfig()`。
 
     Raises:
         InvalidConfigError: 設定ファイルが破損している場合。
-        RuntimeError: 対話的設定後の保存に失敗した場合。
+        ConfigSaveError: 対話的設定後の保存に失敗した場合。
     """
-    config = None if force_config else load_config(CONFIG_PATH)
+    config_path = config_path or CONFIG_PATH
+    previous = None
+    if force_config:
+        # 設定し直す場合は既存の設定を対話的設定の既定値に使う（壊れていれば使わない）
+        with contextlib.suppress(InvalidConfigError):
+            previous = load_config(config_path)
+    config = None if force_config else load_config(config_path)
 
     if config is None and interactive_fallback:
-        config = configure_interactive()
-        save_config(CONFIG_PATH, config)
+        config = configure_interactive(previous)
+        save_config(config_path, config)
     elif config is None:
         config = VoiceNoteConfig()
 
     return config
 
 
-def save_wav(audio_data: np.ndarray, dest_dir: Path) -> Path:
+def save_wav(
+    audio_data: np.ndarray,
+    dest_dir: Path,
+    sample_rate: int = SAMPLE_RATE,
+    audio_format: str = "wav",
+    progress_callback: Callable[[str], None] | None = None,
+) -> Path:
     """録音データを `dest_dir/YYYY-MM-DD_HHMMSS_recording.wav` として保存する。
 
-    float32 (-1.0〜1.0) を int16 に変換して書き出す。
+    float32 (-1.0〜1.0) を int16 に変換し、録音時のサンプルレートで書き出す。
+    16kHz 以外は文字起こし時にリサンプリングされる。
+    ステレオ（stereo_capture）の録音はフレーム数 × 2 の配列で渡し、2チャンネルのまま保存する
+    （文字起こし時にモノラルへダウンミックスされる）。
+    audio_format（recording_format）が "flac" / "opus" なら、WAV の代わりに
+    `.flac` / `.ogg` に圧縮して保存する（faster-whisper 同梱の PyAV でエンコードする）。
+    Opus は 8/12/16/24/48kHz しか扱えないため、それ以外のレートの録音は 48kHz にしてから圧縮する。
+    圧縮に失敗した場合は録音を失わないよう WAV で保存し、警告を progress_callback に通知する。
+
+    Raises:
+        ValueError: audio_format が不明な場合
     """
+    if audio_format not in _RECORDING_CODECS:
+        raise ValueError(
+            f"不明な録音の保存形式です: {audio_format}（{' / '.join(_RECORDING_CODECS)} のいずれか）"
+        )
+    extension, codec = _RECORDING_CODECS[audio_format]
     dest_dir = Path(dest_dir)
     dest_dir.mkdir(parents=True, exist_ok=True)
     timestamp = datetime.now().strftime("%Y-%m-%d_%H%M%S")
-    audio_file = dest_dir / f"{timestamp}_recording.wav"
+    audio_file = dest_dir / f"{timestamp}_recording{extension}"
     audio_int16 = (audio_data * 32767).astype(np.int16)
-    wavfile.write(audio_file, SAMPLE_RATE, audio_int16)
+    if codec is None:
+        wavfile.write(audio_file, sample_rate, audio_int16)
+        return audio_file
+    try:
+        if codec == "libopus" and sample_rate not in _OPUS_SAMPLE_RATES:
+            from scipy.signal import resample_poly
+
+            step = math.gcd(sample_rate, OPUS_SAMPLE_RATE)
+            resampled = resample_poly(
+                audio_data, OPUS_SAMPLE_RATE // step, sample_rate // step, axis=0
+            )
+            encoded = (np.clip(resampled, -1.0, 1.0) * 32767).astype(np.int16)
+            _encode_audio(encoded, audio_file, OPUS_SAMPLE_RATE, codec)
+        else:
+            _encode_audio(audio_int16, audio_file, sample_rate, codec)
+    except Exception as e:
+        # PyAV はコーデックごとに様々な例外を送出する。録音を失わないよう WAV で保存し直す
+        audio_file.unlink(missing_ok=True)
+        audio_file = audio_file.with_suffix(".wav")
+        wavfile.write(audio_file, sample_rate, audio_int16)
+        _logger.warning("%s に圧縮できないため WAV で保存: %s", audio_format, e)
+        if progress_callback:
+            progress_callback(f"⚠ {audio_format} に圧縮できなかったため WAV で保存しました: {e}")
     return audio_file
 
 
-def transcribe_and_save(
+def _encode_audio(audio_int16: np.ndarray, path: Path, sample_rate: int, codec: str):
+    """int16 の音声（モノラル、またはフレーム数 × 2 のステレオ）を PyAV の codec で書き出す。"""
+    import av
+
+    layout = "mono" if audio_int16.ndim == 1 else "stereo"
+    with av.open(str(path), "w") as container:
+        stream = container.add_stream(codec, rate=sample_rate)
+        stream.layout = layout
+        # s16 はチャンネルを交互に並べた1行の配列で渡す
+        frame = av.AudioFrame.from_ndarray(audio_int16.reshape(1, -1), format="s16", layout=layout)
+        frame.sample_rate = sample_rate
+        # None で残りのフレームを書き出す（フレーム長の決まった Opus の分割は PyAV が行う）
+        for data in (frame, None):
+            for packet in stream.encode(data):
+                container.mux(packet)
+
+
+def latest_recording(dest_dir: Path) -> Path | None:
+    """save_wav で保存した録音のうち最新のもの（CLI --continue 用）。無ければ None。"""
+    if not Path(dest_dir).is_dir():
+        return None
+    extensions = {extension for extension, _ in _RECORDING_CODECS.values()}
+    recordings = sorted(
+        p for p in Path(dest_dir).glob("*_recording.*") if p.suffix.lower() in extensions
+    )
+    return recordings[-1] if recordings else None
+
+
+def audio_recorded_at(audio_file: Path) -> datetime | None:
+    """既存の音声ファイルを録音した日時を推定する（--file）。
+
+    コンテナのメタデータの creation_time（スマートフォンのボイスメモの m4a など。UTC）を
+    ローカル時刻にして優先し、無ければファイルの更新日時を使う。どちらも読めなければ None。
+    """
+    try:
+        import av
+
+        with av.open(str(audio_file)) as container:
+            creation_time = container.metadata.get("creation_time")
+    except Exception:
+        # PyAV はコンテナごとに様々な例外を送出するため、まとめて「メタデータ無し」扱いにする
+        creation_time = None
+    if creation_time:
+        try:
+            recorded = datetime.fromisoformat(creation_time.replace("Z", "+00:00"))
+        except ValueError:
+            _logger.debug("creation_time を解釈できません: %s", creation_time)
+        else:
+            if recorded.tzinfo is not None:
+                recorded = recorded.astimezone().replace(tzinfo=None)
+            return recorded
+    try:
+        return datetime.fromtimestamp(Path(audio_file).stat().st_mtime)
+    except OSError:
+        return None
+
+
+def trim_recording(audio_data: np.ndarray, sample_rate: int, config: VoiceNoteConfig) -> np.ndarray:
+    """`trim_silence` が有効なら、録音の前後の無音（`trim_threshold` 未満の振幅）を取り除く。"""
+    if not config.trim_silence:
+        return audio_data
+    return trim_silence(audio_data, sample_rate, config.trim_threshold)
+
+
+def _transcribe_keeping_partial(
     audio_file: Path,
     config: VoiceNoteConfig,
     progress_callback: Callable[[str], None] | None = None,
-) -> Path:
-    """音声ファイルを文字起こし → 整形 → ノート保存し、保存先パスを返す。
+    segment_callback: Callable[[Segment], None] | None = None,
+) -> TranscriptionResult:
+    """文字起こしを実行し、途中で失敗したらそれまでの結果を `.partial.md` に残す。
+
+    `incremental_save` が有効ならセグメントが確定するたびに追記するため、
+    プロセスごと落ちた場合でも途中経過が残る。segment_callback にもセグメントを順に渡す。
+    """
+    partial = PartialNote(Path(config.save_folder), timestamp_format=config.timestamp_format)
+    incremental = config.incremental_save
+
+    def on_segment(segment: Segment):
+        nonlocal incremental
+        if segment_callback:
+            segment_callback(segment)
+        if not incremental:
+            return
+        try:
+            partial.append(segment.start, segment.text)
+        except RuntimeError as e:
+            # 途中経過の保存に失敗しても文字起こし自体は続ける
+            incremental = False
+            if progress_callback:
+                progress_callback(f"⚠ {e}")
+
+    try:
+        result = transcribe(
+            audio_file, config, progress_callback=progress_callback, on_segment=on_segment
+        )
+    except PartialTranscriptionError as e:
+        if not incremental:
+            try:
+                for segment in e.segments:
+                    partial.append(segment.start, segment.text)
+            except RuntimeError as save_error:
+                _logger.error("途中経過を保存できませんでした: %s", save_error)
+        if partial.path.exists():
+            raise TranscriptionError(f"{e}\n途中までの結果を保存しました: {partial.path}") from e
+        raise
+
+    partial.discard()
+    return result
+
+
+def transcribe_to_note(
+    audio_file: Path,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+    recorded_at: datetime | None = None,
+    on_segment: Callable[[Segment], None] | None = None,
+    note_name: str | None = None,
+) -> SavedTranscript:
+    """音声ファイルを文字起こし → 整形 → ノート保存し、保存結果を返す。
+
+    ノートの frontmatter には録音の長さ・モデル・言語・サンプルレートも記録する。
 
     Args:
         audio_file: 文字起こし対象の音声ファイル。
         config: 設定。`save_folder` `transcription_mode` `whisper_model`
-            `vad_filter` `format_mode` を参照する。
+            `vad_filter` `format_mode` `output_format` `append_to` `incremental_save` `skip_empty`
+            を参照する。
         progress_callback: 進捗メッセージを受け取るコールバック。
             GUI なら UI キュー経由、CLI なら Rich Progress 経由で消費する。
+        recorded_at: 録音の開始日時。ノートの `created` に使う（保存日時は `saved` に記録する）。
+        on_segment: 文字起こしのセグメントが確定するたびに呼ばれるコールバック。
+            ノートの保存を待たずに結果を逐次表示するフロントエンド向け（transcriber.transcribe）。
+        note_name: ノートのファイル名の日時の代わりに使う名前。同じ名前のノートは上書きする
+            （`save_result` を参照）。
 
     Returns:
-        保存された Markdown ファイルパス。
+        保存されたノートファイルのパスと文字起こし結果。
+
+    Raises:
+        SaveFolderUnavailableError: 保存先フォルダにアクセスできない場合（文字起こし前に確認する）。
+        EmptyTranscriptionError: `skip_empty` が有効で、文字起こし結果が空の場合。
+        TranscriptionError: 文字起こしが失敗した場合。途中で失敗した場合は、それまでの結果を
+            `.partial.md` に保存した上で送出する。
+        NoteWriteError: ノートを保存できない場合。
+    """
+
+    # 文字起こしに時間をかける前に、保存先が使えるか確認しておく
+    ensure_save_folder_available(config.save_folder)
+    _logger.info("文字起こし開始: %s", audio_file)
+    try:
+        result = _transcribe_keeping_partial(
+            audio_file, config, progress_callback=progress_callback, segment_callback=on_segment
+        )
+    except Exception as e:
+        _logger.error("文字起こしに失敗: %s: %s", audio_file, e)
+        raise
+    _logger.info(
+        "文字起こし完了: %s (model=%s, duration=%s秒, %d文字)",
+        audio_file,
+        result.model,
+        "-" if result.duration is None else f"{result.duration:.1f}",
+        len(result.text),
+    )
+    if config.skip_empty and is_empty_transcription(result.text, config.min_transcript_chars):
+        raise EmptyTranscriptionError(result)
+    return save_result(
+        result,
+        config,
+        progress_callback=progress_callback,
+        recorded_at=recorded_at,
+        audio_file=audio_file,
+        note_name=note_name,
+    )
+
+
+def save_result(
+    result: TranscriptionResult,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+    recorded_at: datetime | None = None,
+    audio_file: Path | None = None,
+    note_name: str | None = None,
+) -> SavedTranscript:
+    """文字起こし結果を（必要なら整形して）ノートとして保存する。
+
+    `append_to` が設定されていれば、新しいノートを作らずそのノートに追記する。
+    Markdown で `chapter_gap_seconds` が設定されていれば、その秒数以上の無音の箇所に
+    `## [mm:ss]` の章見出しを入れる（章ごとに整形する）。
+    `postprocess_command` が設定されていれば、整形の前に本文をそのコマンドに通す。
+    Markdown で `summary_command` が設定されていれば、その出力を末尾の `## 要約` に入れる。
+    Markdown で `note_header` / `note_footer` が設定されていれば、本文（要約を含む）の前後に入れる。
+    Markdown で `wrap_callout` が有効なら、本文を `callout_type` のコールアウトで囲む
+    （frontmatter・要約・ヘッダー・フッターは囲まない）。
+    Markdown で `dual_output` が有効なら、タイムスタンプ付きの `_raw.md` と、段落をつなげた
+    `_clean.md`（整形・要約はこちらに行う）の2つのノートを保存する。
+    Markdown で `audio_links` が有効で audio_file（文字起こしした録音）が分かれば、
+    セグメントごとの行の先頭に、録音のその位置へのリンク（`audio_link_template`）を置く
+    （`dual_output` なら `_raw.md`、そうでなければ整形せずにノートの本文とする）。
+    `extra_destinations` があれば、それぞれの保存先・保存形式でも新しいノートとして保存する
+    （後処理・整形・要約は保存先の数によらず1回だけ行う）。追加の保存先に保存できなかった
+    場合は警告を通知して続ける。
+    `post_save_command` が設定されていれば、保存後にノートのパスを渡して実行する。
+    `split_gap_seconds` が設定されていれば、その秒数以上の無音の箇所で結果を分け、部分ごとに
+    別のノートとして保存する（`created` は録音の開始日時にその部分の開始位置を足した日時）。
+    note_name を指定すると、ノートを日時の代わりに `{note_name}_raw.md` のような名前で保存し、
+    同じ名前のノートがあれば上書きする（CLI `--replace-existing` で同じ音声を文字起こしし直す場合。
+    分けたノートは2つ目から `{note_name}-2` のように番号を付ける）。
+
+    Raises:
+        RuntimeError: `post_save_required` が有効で、保存後コマンドが失敗した場合（ノートは保存済み）
+    """
+
+    def notify(msg: str):
+        if progress_callback:
+            progress_callback(msg)
+
+    parts = [result]
+    if config.split_gap_seconds > 0 and not config.append_to:
+        parts = split_result(result, config.split_gap_seconds, config.segment_separator)
+    if len(parts) > 1:
+        notify(f"無音の箇所で {len(parts)} 個のノートに分けて保存します")
+        single = replace(config, split_gap_seconds=0.0)
+        saved = [
+            save_result(
+                part,
+                single,
+                progress_callback=progress_callback,
+                recorded_at=(
+                    recorded_at + timedelta(seconds=part.segments[0].start) if recorded_at else None
+                ),
+                audio_file=audio_file,
+                note_name=f"{note_name}-{i}" if note_name and i > 1 else note_name,
+            )
+            for i, part in enumerate(parts, start=1)
+        ]
+        return replace(saved[0], split_paths=tuple(s.path for s in saved[1:]))
+
+    formatted: dict[str, str] = {}
+    summaries: dict[str, str | None] = {}
+
+    def format_once(text: str) -> str:
+        if text not in formatted:
+            cleaned = postprocess_transcription(text, config, progress_callback)
+            formatted[text] = _format(cleaned, config, progress_callback)
+        return formatted[text]
+
+    def summarize_once(text: str) -> str | None:
+        if text not in summaries:
+            summaries[text] = summarize_transcription(text, config, progress_callback)
+        return summaries[text]
+
+    path, transcription, raw_path = _save_note(
+        result, config, format_once, summarize_once, recorded_at, audio_file, note_name
+    )
+
+    extra_paths = []
+    for destination in config.extra_destinations:
+        destination_config = replace(config, append_to=None, **destination)
+        try:
+            extra_path, _, _ = _save_note(
+                result,
+                destination_config,
+                format_once,
+                summarize_once,
+                recorded_at,
+                audio_file,
+                note_name,
+            )
+        except (ValueError, RuntimeError) as e:
+            _logger.warning("追加の保存先に保存できません: %s: %s", destination["save_folder"], e)
+            notify(f"⚠ 追加の保存先に保存できませんでした（{destination['save_folder']}）: {e}")
+            continue
+        notify(f"追加の保存先に保存: {extra_path}")
+        extra_paths.append(extra_path)
+
+    run_post_save_command(path, config, progress_callback)
+    return SavedTranscript(
+        path=path,
+        result=result,
+        text=transcription,
+        extra_paths=tuple(extra_paths),
+        raw_path=raw_path,
+    )
+
+
+def run_post_save_command(
+    path: Path, config: VoiceNoteConfig, progress_callback: Callable[[str], None] | None = None
+):
+    """`post_save_command` を、保存したノートのパスを最後の引数に付けて実行する。
+
+    パスは環境変数 VOICENOTE_NOTE_PATH でも渡す。終了コードはログに記録し、失敗した場合は
+    警告を通知して続ける（同期スクリプトの失敗で文字起こし全体を失敗扱いにしないため）。
 
     Raises:
-        RuntimeError: 文字起こし・整形・保存のいずれかが失敗した場合。
+        RuntimeError: `post_save_required` が有効で、コマンドを実行できない・失敗した場合
+    """
+    if not config.post_save_command:
+        return
+    if progress_callback:
+        progress_callback("保存後コマンドを実行中...")
+    note = str(Path(path).absolute())
+    try:
+        completed = subprocess.run(
+            [*shlex.split(config.post_save_command), note],
+            env={**os.environ, "VOICENOTE_NOTE_PATH": note},
+            capture_output=True,
+            encoding="utf-8",
+            timeout=POST_SAVE_TIMEOUT_SECONDS,
+            check=False,
+        )
+    except (ValueError, OSError, subprocess.TimeoutExpired) as e:
+        detail = f"実行できません: {e}"
+    else:
+        _logger.info("保存後コマンドの終了コード: %d (%s)", completed.returncode, note)
+        if completed.returncode == 0:
+            return
+        detail = completed.stderr.strip() or f"終了コード {completed.returncode}"
+    _logger.warning("保存後コマンドが失敗: %s", detail)
+    if config.post_save_required:
+        raise RuntimeError(f"ノートは保存しましたが、保存後コマンドが失敗しました: {detail}")
+    if progress_callback:
+        progress_callback(f"⚠ 保存後コマンドが失敗しました: {detail}")
+
+
+def _save_note(
+    result: TranscriptionResult,
+    config: VoiceNoteConfig,
+    format_text: Callable[[str], str],
+    summarize: Callable[[str], str | None],
+    recorded_at: datetime | None,
+    audio_file: Path | None = None,
+    note_name: str | None = None,
+) -> tuple[Path, str, Path | None]:
+    """config の保存先・保存形式で1つのノートを保存し、そのパスと本文を返す。
+
+    Markdown で `dual_output` が有効なら、タイムスタンプ付きのセグメントを `_raw.md` に、
+    段落をつなげた本文を `_clean.md` に保存し、`_clean.md` のパスに加えて `_raw.md` の
+    パスも返す（それ以外は None）。
     """
+    dual = config.dual_output and config.output_format == "md" and not config.append_to
+    # 整形版は segment_separator によらず段落をつなげる
+    separator = "pause" if dual else config.segment_separator
+    chapters = []
+    if config.chapter_gap_seconds > 0 and config.output_format == "md":
+        chapters = split_chapters(result.segments, config.chapter_gap_seconds, separator)
+
+    # Markdown に埋め込む本文は、区切り線やコードフェンスとして解釈されないようにする
+    markdown = config.output_format == "md" or bool(config.append_to)
+    bullets = separator == "bullet"
+
+    def escape(text: str) -> str:
+        if not markdown:
+            return text
+        if config.escape_markdown:
+            # 箇条書きにした行頭の `- ` はエスケープしない
+            return escape_markdown_text(text, keep_bullets=bullets)
+        return escape_markdown_body(text)
+
+    def body(text: str) -> str:
+        return escape(format_text(clean_transcript(text) if dual else text))
 
-    transcription = transcribe(audio_file, config, progress_callback=progress_callback)
+    def callout(text: str) -> str:
+        if not (markdown and config.wrap_callout):
+            return text
+        return wrap_callout(text, config.callout_type, config.callout_folded)
 
-    if config.format_mode != "none":
-        transcription = format_transcription(
-            transcription, config, progress_callback=progress_callback
+    # 録音のファイル名で張るリンクは、保存したノートと同じ Vault に録音がある前提
+    linked = (
+        config.audio_links
+        and config.output_format == "md"
+        and not config.append_to
+        and audio_file is not None
+        and bool(result.segments)
+    )
+
+    def linked_segments() -> str:
+        return render_timestamped_segments(
+            [(s.start, escape(s.text)) for s in result.segments],
+            lambda start: format_audio_link(config.audio_link_template, audio_file.name, start),
         )
 
+    if linked and not dual:
+        transcription = linked_segments()
+    elif len(chapters) > 1:
+        transcription = render_chapters([(start, body(text)) for start, text in chapters])
+    else:
+        transcription = body(result.text)
+
+    # 要約はコールアウトの外に置く（要約コマンドにはコールアウトで囲む前の本文を渡す）
+    summary = summarize(transcription) if config.output_format == "md" else None
+    transcription = callout(transcription)
+    if summary:
+        transcription = f"{transcription}\n\n## 要約\n\n{summary}"
+
     save_folder = Path(config.save_folder)
-    return save_transcript(save_folder, transcription, config.format_mode)
+    if config.append_to:
+        path = append_transcript(save_folder, config.append_to, transcription, now=recorded_at)
+        _logger.info("ノートに追記: %s", path)
+        return path, transcription, None
+
+    # dual_output の2つのファイル名の日時をそろえる
+    saved_at = datetime.now()
+
+    def save(text: str, format_mode: str, kind: str) -> Path:
+        return save_transcript(
+            save_folder,
+            text,
+            format_mode,
+            metadata={**config.frontmatter_extra, **result.metadata()},
+            output_format=config.output_format,
+            segments=[segment.to_dict() for segment in result.segments],
+            timestamp_format=config.timestamp_format,
+            recorded_at=recorded_at,
+            header=config.note_header,
+            footer=config.note_footer,
+            kind=kind,
+            now=saved_at,
+            tags=config.extra_tags,
+            name=note_name,
+        )
+
+    raw_path = None
+    if dual:
+        raw_text = escape(result.text)
+        if linked:
+            raw_text = linked_segments()
+        elif result.segments:
+            raw_text = escape(
+                render_timestamped_segments([(s.start, s.text) for s in result.segments])
+            )
+        raw_path = save(callout(raw_text), "none", "raw")
+        _logger.info("タイムスタンプ付きのノートを保存: %s", raw_path)
+    # リンク付きのセグメントの行は整形しない（行をつなげるとリンクが崩れる）
+    format_mode = "none" if linked and not dual else config.format_mode
+    path = save(transcription, format_mode, "clean" if dual else "raw")
+    _logger.info("ノートを保存: %s", path)
+    return path, transcription, raw_path
+
+
+def _format(
+    text: str, config: VoiceNoteConfig, progress_callback: Callable[[str], None] | None
+) -> str:
+    """`format_mode` が none 以外ならテキストを整形する。"""
+    if config.format_mode == "none":
+        return text
+    return format_transcription(text, config, progress_callback=progress_callback)
+
+
+def transcribe_and_save(
+    audio_file: Path,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+    recorded_at: datetime | None = None,
+) -> Path:
+    """`transcribe_to_note` を実行し、保存されたノートファイルのパスだけを返す。"""
+    return transcribe_to_note(
+        audio_file, config, progress_callback=progress_callback, recorded_at=recorded_at
+    ).path
+
+
+def print_only_config(model_dir: Path) -> VoiceNoteConfig:
+    """`--print-only` 用の設定を返す。
+
+    ユーザー設定は読まず、ダウンロード済みのローカルモデルで temperature 0 に固定して
+    文字起こしする（ネットワーク・保存先フォルダに依存せず、結果が決定的になる）。
+
+    Raises:
+        ValueError: model_dir がモデルのフォルダでない場合
+    """
+    if not (Path(model_dir) / "model.bin").is_file():
+        raise ValueError(f"モデルのフォルダ（model.bin を含む）ではありません: {model_dir}")
+    return VoiceNoteConfig(
+        whisper_model=str(model_dir), transcription_mode="local", temperature=0.0
+    )
+
+
+def format_segments(segments: tuple[Segment, ...]) -> str:
+    """セグメントを `[開始秒 --> 終了秒] テキスト` の行にする（`--print-only` の出力）。"""
+    return "\n".join(f"[{s.start:.2f} --> {s.end:.2f}] {s.text}" for s in segments)
+
+
+def find_audio_files(folder: Path) -> list[Path]:
+    """フォルダ直下の音声ファイル（AUDIO_EXTENSIONS）を名前順で返す。"""
+    return sorted(
+        p for p in Path(folder).iterdir() if p.is_file() and p.suffix.lower() in AUDIO_EXTENSIONS
+    )
+
+
+def transcribe_batch(
+    folder: Path,
+    config: VoiceNoteConfig,
+    progress_callback: Callable[[str], None] | None = None,
+) -> tuple[list[BatchIndexEntry], Path]:
+    """フォルダ内の音声ファイルをまとめて文字起こしし、インデックスノートを保存する。
+
+    個々のファイルの失敗は処理を止めず、インデックスに失敗として記録する。
+
+    Args:
+        folder: 音声ファイルを含むフォルダ。
+        config: 設定。`transcribe_to_note` が参照する項目に加え `batch_index_name` を参照する。
+        progress_callback: 進捗メッセージを受け取るコールバック（先頭に [n/総数] を付ける）。
+
+    Returns:
+        ファイルごとの結果と、保存したインデックスノートのパス。
+
+    Raises:
+        ValueError: フォルダに音声ファイルが無い場合。
+        SaveFolderUnavailableError: 保存先フォルダにアクセスできない場合。
+        RuntimeError: インデックスの保存に失敗した場合。
+    """
+    audio_files = find_audio_files(folder)
+    if not audio_files:
+        raise ValueError(f"音声ファイルが見つかりません: {folder}")
+    ensure_save_folder_available(config.save_folder)
+
+    entries: list[BatchIndexEntry] = []
+    for i, audio_file in enumerate(audio_files, start=1):
+        prefix = f"[{i}/{len(audio_files)}] {audio_file.name}"
+
+        def on_progress(msg: str, prefix: str = prefix):
+            if progress_callback:
+                progress_callback(f"{msg} {prefix}" if msg.startswith("⚠") else f"{prefix}: {msg}")
+
+        try:
+            saved = transcribe_to_note(audio_file, config, progress_callback=on_progress)
+        except Exception as e:
+            _logger.error("一括文字起こしに失敗: %s: %s", audio_file, e)
+            on_progress(f"⚠ 失敗しました: {e}")
+            entries.append(BatchIndexEntry(source=audio_file, note=None, error=str(e)))
+            continue
+        entries.append(
+            BatchIndexEntry(
+                source=audio_file,
+                note=saved.path,
+                duration=saved.result.duration,
+                segment_count=len(saved.result.segments),
+            )
+        )
+
+    index_path = save_batch_index(
+        Path(config.save_folder),
+        entries,
+        config.batch_index_name,
+        timestamp_format=config.timestamp_format,
+    )
+    return entries, index_path
@@ -3,23 +3,96 @@
 sounddeviceを使用したリアルタイム録音
 """
 
+import contextlib
+import logging
 import signal
+import sys
 import threading
+import time
 from collections.abc import Callable
+from dataclasses import dataclass
+from datetime import datetime
+from typing import Protocol
 
 import numpy as np
 import sounddevice as sd
 
+from errors import DeviceNotFoundError, DeviceUnavailableError
+
+_logger = logging.getLogger("voicenote")
+
 SAMPLE_RATE = 16000
+# 入力ストリームに要求するサンプル形式（優先順）。float32 以外は録音時に float32 に変換する
+SAMPLE_FORMATS = ("float32", "int32", "int16", "int8", "uint8")
+SIGNAL_CHECK_SECONDS = 1.0
+# 停止要求からストリーム停止までの猶予。入力レイテンシ分の末尾の音声を取りこぼさないようにする
+STOP_DRAIN_SECONDS = 0.2
+# この秒数ブロックが届かなければ入力デバイスが切断されたとみなし、再接続を試みる
+DEVICE_STALL_SECONDS = 2.0
+RECONNECT_ATTEMPTS = 5
+RECONNECT_INTERVAL_SECONDS = 1.0
+# 設定時のマイクテストで録音する秒数と、レベルメーターを更新する間隔
+MIC_TEST_SECONDS = 3.0
+LEVEL_METER_INTERVAL_SECONDS = 0.1
+# レベル表示の下限と、マイクテストで入力が小さすぎるとみなすピーク（dBFS）
+LEVEL_FLOOR_DBFS = -60.0
+LOW_INPUT_PEAK_DBFS = -40.0
+
+
+# システム音声（ループバック / モニター）を入力として提供する仮想デバイスによくある名前
+LOOPBACK_NAME_HINTS = (
+    "monitor",
+    "loopback",
+    "blackhole",
+    "soundflower",
+    "stereo mix",
+    "ステレオ ミキサー",
+    "cable output",
+)
+
 
+def is_loopback_name(name: str) -> bool:
+    """デバイス名からシステム音声を録音できる入力（ループバック / モニター）かを推定する。"""
+    lowered = name.lower()
+    return any(hint in lowered for hint in LOOPBACK_NAME_HINTS)
+
+
+def loopback_hint(platform: str = sys.platform) -> str:
+    """出力デバイスを直接録音できない場合に、システム音声を録音する方法を返す。"""
+    if platform == "darwin":
+        return (
+            "macOS では出力デバイスを直接録音できません。BlackHole などの仮想オーディオデバイスを"
+            "インストールし、音声の出力先をそれ（または複数出力装置）にした上で、"
+            "そのデバイスを入力として指定してください。"
+        )
+    if platform == "win32":
+        return (
+            "Windows ではサウンド設定の「ステレオ ミキサー」を有効にするか、"
+            "VB-CABLE などの仮想オーディオデバイスを入力として指定してください。"
+        )
+    return (
+        "PulseAudio / PipeWire の「Monitor of ...」ソースを入力として指定してください"
+        "（一覧に無い場合は pavucontrol の録音タブで入力を Monitor に切り替えます）。"
+    )
 
-def list_devices() -> list[dict]:
-    """利用可能な入力デバイス一覧を返す"""
+
+def list_devices(include_outputs: bool = False) -> list[dict]:
+    """利用可能な入力デバイス一覧を返す。
+
+    `loopback` はシステム音声を録音できそうな入力かどうか（名前からの推定）。
+    include_outputs が True なら、入力を持たない出力専用デバイスも含める（録音には使えない）。
+    """
     devices = sd.query_devices()
     return [
-        {"id": i, "name": d["name"], "input_channels": d["max_input_channels"]}
+        {
+            "id": i,
+            "name": d["name"],
+            "input_channels": d["max_input_channels"],
+            "output_channels": d["max_output_channels"],
+            "loopback": d["max_input_channels"] > 0 and is_loopback_name(d["name"]),
+        }
         for i, d in enumerate(devices)
-        if d["max_input_channels"] > 0
+        if d["max_input_channels"] > 0 or include_outputs
     ]
 
 
@@ -29,100 +102,745 @@ def default_input_name() -> str:
 
 
 def resolve_device_id(device: str | None) -> int | None:
-    """デバイス名またはIDを数値IDに解決する。見つからない場合はValueErrorを送出。"""
+    """デバイス名またはIDを数値IDに解決する。
+
+    出力専用デバイスが指定された場合は、システム音声を録音する方法をエラーメッセージに添える。
+
+    Raises:
+        DeviceNotFoundError: デバイスが見つからない・入力として使えない場合
+    """
     if device is None:
         return None
-    if device.isdigit():
-        return int(device)
     devices = sd.query_devices()
+    if device.isdigit():
+        device_id = int(device)
+        if device_id < len(devices) and devices[device_id]["max_input_channels"] == 0:
+            raise DeviceNotFoundError(
+                f"デバイス '{devices[device_id]['name']}' は出力専用のため録音できません。"
+                f"{loopback_hint()}"
+            )
+        return device_id
     for i, d in enumerate(devices):
         if device.lower() in d["name"].lower() and d["max_input_channels"] > 0:
             return i
-    raise ValueError(f"デバイス '{device}' が見つかりません")
+    for d in devices:
+        if device.lower() in d["name"].lower():
+            raise DeviceNotFoundError(
+                f"デバイス '{d['name']}' は出力専用のため録音できません。{loopback_hint()}"
+            )
+    raise DeviceNotFoundError(f"デバイス '{device}' が見つかりません")
+
+
+def _reinitialize_portaudio():
+    """PortAudio を初期化し直してデバイス一覧を取り直す。
+
+    PortAudio は初期化時のデバイス一覧を使い続けるため、抜けて戻った Bluetooth マイクなどは
+    初期化し直さないと開けず、番号も付け直される。開いているストリームを閉じてから呼ぶ。
+    """
+    sd._terminate()
+    sd._initialize()
+
+
+def _input_device_id_by_name(name: str) -> int:
+    """名前が name と一致する入力デバイスの番号を返す（再接続でデバイスを探し直すため）。
+
+    Raises:
+        DeviceUnavailableError: 見つからない場合（デバイスがまだ戻っていない場合など）
+    """
+    for i, d in enumerate(sd.query_devices()):
+        if d["name"] == name and d["max_input_channels"] > 0:
+            return i
+    raise DeviceUnavailableError(f"入力デバイス '{name}' が見つかりません")
+
+
+def supported_sample_formats(device_id: int | None) -> list[str]:
+    """デバイスが（既定のサンプルレートで）受け付ける SAMPLE_FORMATS の形式を返す。"""
+    supported = []
+    for dtype in SAMPLE_FORMATS:
+        try:
+            sd.check_input_settings(device=device_id, channels=1, dtype=dtype)
+        except (sd.PortAudioError, ValueError):
+            continue
+        supported.append(dtype)
+    return supported
+
+
+def negotiate_sample_format(device_id: int | None) -> tuple[str, str | None]:
+    """録音に使うサンプル形式を決める。対応形式はログに記録する。
+
+    float32 に対応していないドライバ（一部の Windows の排他モードなど）では、対応している
+    別の形式で録音して float32 に変換する。どの形式も確認できない場合は float32 のまま開き、
+    ストリームを開くときのエラーで理由を示す。
+
+    Returns:
+        使うサンプル形式と、float32 以外にした場合の警告メッセージ
+    """
+    supported = supported_sample_formats(device_id)
+    _logger.info(
+        "入力デバイス %s の対応サンプル形式: %s",
+        "デフォルト" if device_id is None else device_id,
+        ", ".join(supported) or "不明",
+    )
+    if not supported or SAMPLE_FORMATS[0] in supported:
+        return SAMPLE_FORMATS[0], None
+    return supported[0], (
+        f"入力デバイスが float32 形式に対応していないため、{supported[0]} 形式で録音します"
+        f"（対応形式: {', '.join(supported)}）"
+    )
+
+
+def to_float32(samples: np.ndarray) -> np.ndarray:
+    """整数形式のサンプルを -1.0〜1.0 の float32 に変換する（float はそのままコピー）。"""
+    if samples.dtype.kind == "f":
+        return samples.astype(np.float32)
+    info = np.iinfo(samples.dtype)
+    # 符号なし形式は中央値が無音
+    offset = (int(info.max) + 1) // 2 if samples.dtype.kind == "u" else 0
+    scale = float(int(info.max) + 1 - offset)
+    return ((samples.astype(np.float32) - offset) / scale).astype(np.float32)
+
+
+def apply_gain(samples: np.ndarray, gain_db: float) -> tuple[np.ndarray, bool]:
+    """float32 のサンプルを gain_db 増幅し、-1.0〜1.0 に収める。はみ出して割れた場合は True も返す。"""
+    amplified = samples * np.float32(10 ** (gain_db / 20))
+    clipped = bool(np.any(np.abs(amplified) > 1.0))
+    return np.clip(amplified, -1.0, 1.0).astype(np.float32), clipped
+
+
+def negotiate_channels(device_id: int | None, requested: int) -> tuple[int, str | None]:
+    """requested チャンネル（1 か 2）で録音できるか確認し、できなければモノラルに落とす。
+
+    モノラルはどの入力デバイスでも開ける前提で確認しない（PortAudio がダウンミックスする）。
+
+    Returns:
+        実際に使うチャンネル数と、フォールバックした場合の警告メッセージ
+    """
+    if requested <= 1:
+        return 1, None
+    try:
+        sd.check_input_settings(device=device_id, channels=requested)
+        return requested, None
+    except (sd.PortAudioError, ValueError) as e:
+        return 1, f"入力デバイスがステレオ録音に対応していないため、モノラルで録音します（{e}）"
+
+
+def negotiate_sample_rate(
+    device_id: int | None, requested: int, dtype: str = SAMPLE_FORMATS[0]
+) -> tuple[int, str | None]:
+    """デバイスが requested Hz で録音できるか確認し、できなければデバイス既定のレートに落とす。
+
+    Returns:
+        実際に使うサンプルレートと、フォールバックした場合の警告メッセージ
+    """
+    try:
+        sd.check_input_settings(device=device_id, channels=1, dtype=dtype, samplerate=requested)
+        return requested, None
+    except (sd.PortAudioError, ValueError) as e:
+        fallback = int(sd.query_devices(device_id, kind="input")["default_samplerate"])
+        return fallback, (
+            f"入力デバイスが {requested}Hz での録音に対応していないため、"
+            f"デバイス既定の {fallback}Hz で録音します（{e}）"
+        )
+
+
+# 前後の無音を取り除くとき、最初・最後の発話の前後に残す余白（語頭・語尾を切らないため）
+TRIM_PADDING_SECONDS = 0.3
+
+
+def trim_silence(
+    audio: np.ndarray,
+    sample_rate: int,
+    threshold: float,
+    padding: float = TRIM_PADDING_SECONDS,
+) -> np.ndarray:
+    """振幅が threshold 未満の先頭・末尾の区間を、padding 秒の余白を残して取り除く。
+
+    全体が threshold 未満の場合は、何も取り除かずにそのまま返す。
+    ステレオ（フレーム数 × 2 の配列）では、どちらかのチャンネルが threshold 以上なら発話とみなす。
+    """
+    level = np.abs(audio) if audio.ndim == 1 else np.abs(audio).max(axis=1)
+    loud = np.flatnonzero(level >= threshold)
+    if loud.size == 0:
+        return audio
+    margin = int(sample_rate * padding)
+    start = max(int(loud[0]) - margin, 0)
+    end = min(int(loud[-1]) + 1 + margin, len(audio))
+    return audio[start:end]
+
+
+# 録音中に自動停止（StopDetector・入力デバイスの切断）を確認する間隔
+STOP_CHECK_SECONDS = 0.25
+
+
+class StopDetector(Protocol):
+    """録音中に直近の音声を見て、自動で録音を止めるかを判定する。
+
+    `window_seconds` 秒分の直近の音声が STOP_CHECK_SECONDS ごとに渡される。
+    無音での停止のほか、停止フレーズの検出（キーワードスポッティング）なども実装できる。
+    """
+
+    window_seconds: float
+
+    def should_stop(self, recent: np.ndarray, sample_rate: int) -> bool: ...
+
+
+class SilenceStopDetector:
+    """発話の後、振幅が threshold 未満の状態が silence_seconds 秒続いたら停止する。
+
+    録音を始めてから何も話していないうちは停止しない。
+    """
+
+    def __init__(self, silence_seconds: float, threshold: float):
+        self.window_seconds = silence_seconds
+        self._threshold = threshold
+        self._heard_speech = False
+
+    def should_stop(self, recent: np.ndarray, sample_rate: int) -> bool:
+        if np.any(np.abs(recent) >= self._threshold):
+            self._heard_speech = True
+            return False
+        return self._heard_speech and len(recent) >= int(self.window_seconds * sample_rate)
+
+
+# --stdin で受け付ける生PCMの形式（モノラル・リトルエンディアン）と1サンプルのバイト数
+PCM_FORMATS = {"s16le": "<i2", "f32le": "<f4"}
+
+
+def decode_pcm(raw: bytes, pcm_format: str) -> np.ndarray:
+    """モノラル・リトルエンディアンの生PCMバイト列を float32 (-1.0〜1.0) に変換する。
+
+    末尾の1サンプルに満たない端数バイト（パイプが途中で切れた場合など）は捨てる。
+
+    Raises:
+        ValueError: 未対応の形式の場合
+        DeviceUnavailableError: 1サンプル分のデータも無い場合
+    """
+    if pcm_format not in PCM_FORMATS:
+        raise ValueError(f"未対応のPCM形式です: {pcm_format}（{' / '.join(PCM_FORMATS)}）")
+    dtype = np.dtype(PCM_FORMATS[pcm_format])
+    usable = len(raw) - len(raw) % dtype.itemsize
+    if usable == 0:
+        raise DeviceUnavailableError("標準入力から音声データを受け取れませんでした")
+    samples = np.frombuffer(raw[:usable], dtype=dtype)
+    if dtype.kind == "i":
+        return samples.astype(np.float32) / 32768.0
+    return np.clip(samples.astype(np.float32), -1.0, 1.0)
+
+
+def no_input_hint(received_data: bool, platform: str = sys.platform) -> str:
+    """入力が届かない・無音のままの場合に表示する対処のヒントを返す。"""
+    if platform == "darwin":
+        return (
+            "マイクから音声が届いていません。macOS のマイク権限が許可されていない可能性があります。"
+            "システム設定 > プライバシーとセキュリティ > マイク で、"
+            "使用中のアプリ（ターミナル / VoiceNote）を許可してください。"
+        )
+    if not received_data:
+        return (
+            "入力デバイスから音声データが届いていません。"
+            "デバイスの接続と、録音に使用するデバイスの選択を確認してください。"
+        )
+    return (
+        "入力が無音のままです。マイクのミュート・入力レベル・"
+        "録音に使用するデバイスの選択を確認してください。"
+    )
 
 
 class ThreadedRecorder:
     """
     GUI用スレッドセーフ録音クラス。
     start() で録音開始、stop() で停止、get_data() でnumpy配列を取得。
+    デバイスが指定レートに対応していない場合はデバイス既定のレートで録音し、
+    実際のレートを `sample_rate`、理由を `fallback_warning` に保持する。
+    float32 形式に対応していないデバイスでは対応している整数形式（`dtype`）で録音して
+    float32 に変換し、理由を `format_warning` に保持する。
+    `buffer_frames` を指定するとその固定バッファサイズでストリームを開き、デバイスが
+    受け付けなければ既定のバッファサイズで開き直して理由を `buffer_warning` に保持する。
+    `device_id` は録音に使うデバイス（None ならデフォルト）、`started_at` は録音を開始した日時。
+    `gain_db` が 0 以外なら入力をその分だけ増幅し（保存する音声・文字起こしの入力の両方）、
+    増幅で音が割れたら `clipped` をセットする。
+    `channels` は録音するチャンネル数。既定の 1 ではモノラルのストリームを開き（ステレオの
+    デバイスでもダウンミックスは PortAudio が行う）、get_data() は1次元の配列を返す。
+    2 ならステレオで録音して get_data() は（フレーム数 × 2）の配列を返す。デバイスが
+    ステレオに対応していなければモノラルで録音し、理由を `channel_warning` に保持する。
+    録音中に入力が途絶えた場合（Bluetooth マイクの切断など）は PortAudio を初期化し直し、
+    同じ名前のデバイスを探し直して開き直し、同じバッファに録音を続け、
+    欠けた区間を `interruptions` に記録する。再接続できなければ
+    ストリームを閉じて `lost` をセットする（それまでの録音データは get_data() で取得できる）。
     """
 
-    def __init__(self, device_id: int | None = None):
-        self._device_id = device_id
+    def __init__(
+        self,
+        device_id: int | None = None,
+        sample_rate: int = SAMPLE_RATE,
+        buffer_frames: int | None = None,
+        gain_db: float = 0.0,
+        channels: int = 1,
+    ):
+        self.device_id = device_id
+        self._requested_rate = sample_rate
+        self.sample_rate = sample_rate
+        self.buffer_frames = buffer_frames
+        self.gain_db = gain_db
+        self._requested_channels = channels
+        self.channels = 1
+        self.dtype = SAMPLE_FORMATS[0]
+        self.fallback_warning: str | None = None
+        self.format_warning: str | None = None
+        self.buffer_warning: str | None = None
+        self.channel_warning: str | None = None
+        self.started_at: datetime | None = None
+        self.interruptions: list[str] = []
+        self.lost = threading.Event()
+        self.clipped = threading.Event()
         self._data: list[np.ndarray] = []
         self._lock = threading.Lock()
         self._stream: sd.InputStream | None = None
+        self._stream_lock = threading.Lock()
+        self._stopped = threading.Event()
+        self._last_block = 0.0
         self._running = False
+        self._received = threading.Event()
+        self._signal = threading.Event()
 
-    def _callback(self, indata, frames, time, status):
+    def _callback(self, indata, frames, time_info, status):
         if self._running:
+            block = to_float32(indata)
+            if self.gain_db:
+                block, clipped = apply_gain(block, self.gain_db)
+                if clipped:
+                    self.clipped.set()
             with self._lock:
-                self._data.append(indata.copy())
+                self._data.append(block)
+            self._last_block = time.monotonic()
+            self._received.set()
+            if not self._signal.is_set() and np.any(block):
+                self._signal.set()
+
+    @property
+    def warnings(self) -> list[str]:
+        """録音開始時にフォールバックした理由（レート・形式・バッファサイズ・チャンネル数）。"""
+        return [
+            w
+            for w in (
+                self.fallback_warning,
+                self.format_warning,
+                self.buffer_warning,
+                self.channel_warning,
+            )
+            if w
+        ]
+
+    @property
+    def clipping_warning(self) -> str:
+        """`clipped` がセットされたときに表示する警告。"""
+        return f"入力ゲイン（gain_db={self.gain_db:g}）で音が割れています。gain_db を下げてください"
 
     def start(self):
         self._data = []
+        self.interruptions = []
+        self.lost.clear()
+        self.clipped.clear()
+        self._stopped.clear()
+        self._received.clear()
+        self._signal.clear()
+        self.channels, self.channel_warning = negotiate_channels(
+            self.device_id, self._requested_channels
+        )
+        self.dtype, self.format_warning = negotiate_sample_format(self.device_id)
+        self.sample_rate, self.fallback_warning = negotiate_sample_rate(
+            self.device_id, self._requested_rate, self.dtype
+        )
         self._running = True
-        self._stream = sd.InputStream(
-            samplerate=SAMPLE_RATE,
-            channels=1,
-            dtype="float32",
-            device=self._device_id,
-            callback=self._callback,
+        try:
+            self._stream = self._open_stream()
+            self._stream.start()
+            self.started_at = datetime.now()
+            self._last_block = time.monotonic()
+        except Exception:
+            # 開けなかったストリームを残さず、別デバイスで start() をやり直せるようにする
+            if self._stream is not None:
+                self._stream.close()
+                self._stream = None
+            self._running = False
+            raise
+        threading.Thread(target=self._watch_stream, daemon=True).start()
+
+    def _watch_stream(self):
+        """録音中にブロックが途絶えたら再接続を試み、できなければ録音を終える。"""
+        while not self._stopped.wait(DEVICE_STALL_SECONDS / 4):
+            if time.monotonic() - self._last_block < DEVICE_STALL_SECONDS:
+                continue
+            if not self._reconnect():
+                return
+
+    def _reconnect(self) -> bool:
+        """同じデバイスでストリームを開き直す。stop() されるか再接続をあきらめたら False。
+
+        デバイスの番号は PortAudio を初期化し直すと変わりうるため、名前で探し直す
+        （デフォルトデバイスで録音している場合は、その時点のデフォルトを開く）。
+        """
+        _logger.warning("入力デバイスからの音声が途絶えました。再接続を試みます")
+        stalled_since = self._last_block
+        error: Exception | None = None
+        device_name = None
+        if self.device_id is not None:
+            with contextlib.suppress(Exception):
+                device_name = sd.query_devices(self.device_id)["name"]
+        for attempt in range(1, RECONNECT_ATTEMPTS + 1):
+            if self._stopped.wait(RECONNECT_INTERVAL_SECONDS):
+                return False
+            with self._stream_lock:
+                if self._stopped.is_set():
+                    return False
+                self._close_stream()
+                try:
+                    _reinitialize_portaudio()
+                    if device_name is not None:
+                        self.device_id = _input_device_id_by_name(device_name)
+                    self._stream = self._open_stream()
+                    self._stream.start()
+                except Exception as e:
+                    self._close_stream()
+                    error = e
+                    _logger.warning("再接続に失敗 (%d/%d): %s", attempt, RECONNECT_ATTEMPTS, e)
+                    continue
+            gap = time.monotonic() - stalled_since
+            self._last_block = time.monotonic()
+            self._report_interruption(
+                f"入力デバイスが途切れたため約{gap:.1f}秒の音声が欠けています"
+                "（再接続して録音を続けます）"
+            )
+            return True
+        self._report_interruption(
+            f"入力デバイスに再接続できませんでした（{error}）。ここまでの録音で終了します"
         )
-        self._stream.start()
+        self.lost.set()
+        return False
 
-    def stop(self):
-        self._running = False
+    def _report_interruption(self, message: str):
+        _logger.warning(message)
+        self.interruptions.append(message)
+
+    def _close_stream(self):
+        """切断されたデバイスのストリームは stop/close が失敗することがあるため、例外は無視する。"""
         if self._stream is not None:
-            self._stream.stop()
-            self._stream.close()
+            with contextlib.suppress(Exception):
+                self._stream.close()
             self._stream = None
 
+    def _open_stream(self) -> sd.InputStream:
+        """ストリームを開く。buffer_frames を受け付けないデバイスでは既定のバッファサイズにする。
+
+        PortAudio には対応するバッファサイズの範囲を問い合わせる API が無いため、
+        実際に開けるかどうかで判定する。
+        """
+        self.buffer_warning = None
+        if self.buffer_frames:
+            try:
+                return self._input_stream(self.buffer_frames)
+            except sd.PortAudioError as e:
+                self.buffer_warning = (
+                    f"バッファサイズ {self.buffer_frames} フレームで録音できません（{e}）。"
+                    "既定のバッファサイズで録音します"
+                )
+        return self._input_stream(0)
+
+    def _input_stream(self, blocksize: int) -> sd.InputStream:
+        # blocksize=0 は PortAudio に最適なサイズを任せる（既定）
+        return sd.InputStream(
+            samplerate=self.sample_rate,
+            blocksize=blocksize,
+            channels=self.channels,
+            dtype=self.dtype,
+            device=self.device_id,
+            callback=self._callback,
+        )
+
+    def stop(self, drain: float = STOP_DRAIN_SECONDS):
+        """録音を停止する。
+
+        drain 秒待ってからストリームを止める。stream.stop() は処理中のコールバックの完了を
+        待つため、_running を下ろすのはその後にして最後のブロックまで取り込む。
+        """
+        self._stopped.set()
+        with self._stream_lock:
+            if self._stream is not None:
+                if drain > 0:
+                    time.sleep(drain)
+                self._stream.stop()
+                self._stream.close()
+                self._stream = None
+        self._running = False
+
+    def input_problem_hint(self, timeout: float = SIGNAL_CHECK_SECONDS) -> str | None:
+        """録音開始後 timeout 秒以内に無音でない入力が届くか確認し、届かなければヒントを返す。"""
+        if self._signal.wait(timeout):
+            return None
+        return no_input_hint(self._received.is_set())
+
+    def recent(self, seconds: float) -> np.ndarray:
+        """直近 seconds 秒分の録音データを返す（録音がまだ短ければあるだけ）。
+
+        ステレオでも停止判定に使えるよう、チャンネルを平均したモノラルの1次元配列を返す。
+        """
+        needed = int(seconds * self.sample_rate)
+        blocks: list[np.ndarray] = []
+        with self._lock:
+            count = 0
+            for block in reversed(self._data):
+                if count >= needed:
+                    break
+                blocks.append(block)
+                count += len(block)
+        if not blocks:
+            return np.zeros(0, dtype=np.float32)
+        frames = np.concatenate(blocks[::-1], axis=0)
+        mono = frames.mean(axis=1) if self.channels > 1 else frames.flatten()
+        return mono.astype(np.float32)[-needed:]
+
     def get_data(self) -> np.ndarray:
         with self._lock:
             if not self._data:
-                raise RuntimeError("録音データがありません")
-            return np.concatenate(self._data, axis=0).flatten()
+                raise DeviceUnavailableError("録音データがありません")
+            data = np.concatenate(self._data, axis=0)
+        return data if self.channels > 1 else data.flatten()
+
+
+def start_recorder(
+    device_id: int | None,
+    sample_rate: int = SAMPLE_RATE,
+    on_warning: Callable[[str], None] | None = None,
+    buffer_frames: int | None = None,
+    gain_db: float = 0.0,
+    channels: int = 1,
+) -> ThreadedRecorder:
+    """録音を開始した ThreadedRecorder を返す。
+
+    デフォルト入力デバイスを開けない場合（他のアプリが使用中など）は、他の入力デバイスを
+    順に試す。システム音声（ループバック）の入力は意図しない音を録ってしまうため試さない。
+    デバイスを明示した場合はフォールバックせず、そのままエラーにする。
+
+    Raises:
+        DeviceUnavailableError: 指定したデバイスを開けない場合や、デフォルトも含め
+            開ける入力デバイスが無い場合
+    """
+    recorder = ThreadedRecorder(device_id, sample_rate, buffer_frames, gain_db, channels)
+    try:
+        recorder.start()
+        return recorder
+    except sd.PortAudioError as e:
+        if device_id is not None:
+            raise DeviceUnavailableError(f"入力デバイスを開けません: {e}") from e
+        error = e
+
+    default_name = default_input_name()
+    candidates = [d for d in list_devices() if d["name"] != default_name and not d["loopback"]]
+    for d in candidates:
+        if on_warning:
+            on_warning(f"入力デバイスを開けませんでした（{error}）。'{d['name']}' を試します")
+        recorder = ThreadedRecorder(d["id"], sample_rate, buffer_frames, gain_db, channels)
+        try:
+            recorder.start()
+            return recorder
+        except sd.PortAudioError as e:
+            error = e
+    raise DeviceUnavailableError(f"録音を開始できる入力デバイスがありません（{error}）")
+
+
+def level_dbfs(level: float) -> float:
+    """振幅（0〜1）を dBFS にする。LEVEL_FLOOR_DBFS 未満（無音を含む）は LEVEL_FLOOR_DBFS。"""
+    if level <= 0:
+        return LEVEL_FLOOR_DBFS
+    return max(LEVEL_FLOOR_DBFS, float(20 * np.log10(level)))
+
+
+@dataclass(frozen=True)
+class MicTestResult:
+    """マイクテスト（run_mic_test）の結果。"""
+
+    device_name: str
+    # 録音全体の振幅のピーク（0〜1）
+    peak: float
+    # 無音でない入力が届かなかった場合の対処のヒント。届いていれば None
+    hint: str | None
+
+    @property
+    def captured(self) -> bool:
+        return self.hint is None
+
+    @property
+    def peak_dbfs(self) -> float:
+        return level_dbfs(self.peak)
+
+    @property
+    def warning(self) -> str | None:
+        """入力が届かない・小さすぎる場合の警告。問題なければ None。"""
+        if self.hint:
+            return self.hint
+        if self.peak_dbfs < LOW_INPUT_PEAK_DBFS:
+            return (
+                f"入力レベルが低すぎます（ピーク {self.peak_dbfs:.1f} dBFS）。"
+                "マイクの入力レベルか、設定の gain_db を確認してください。"
+            )
+        return None
+
+
+def run_mic_test(
+    device: str | None = None,
+    seconds: float = MIC_TEST_SECONDS,
+    on_level: Callable[[float], None] | None = None,
+    sample_rate: int = SAMPLE_RATE,
+    buffer_frames: int | None = None,
+    gain_db: float = 0.0,
+    channels: int = 1,
+) -> MicTestResult:
+    """入力デバイスで seconds 秒だけ試し録音し、音声が届いたかとピークレベルを返す。
+
+    初回設定で、既定の入力デバイスの取り違えやマイク権限の不足を最初の録音の前に見つけるために使う。
+    録音中は on_level に直近の振幅のピーク（0〜1）を LEVEL_METER_INTERVAL_SECONDS ごとに渡す。
+    sample_rate・buffer_frames・gain_db・channels は record_audio と同じで、録音時の値を渡す。
+
+    Raises:
+        DeviceNotFoundError: デバイスが見つからない場合
+        DeviceUnavailableError: 開ける入力デバイスが無い場合
+    """
+    recorder = start_recorder(
+        resolve_device_id(device),
+        sample_rate,
+        buffer_frames=buffer_frames,
+        gain_db=gain_db,
+        channels=channels,
+    )
+    if recorder.device_id is not None:
+        device_name = sd.query_devices(recorder.device_id)["name"]
+    else:
+        device_name = default_input_name()
+    deadline = time.monotonic() + seconds
+    try:
+        while time.monotonic() < deadline:
+            time.sleep(LEVEL_METER_INTERVAL_SECONDS)
+            if on_level:
+                recent = recorder.recent(LEVEL_METER_INTERVAL_SECONDS)
+                on_level(float(np.max(np.abs(recent), initial=0.0)))
+    finally:
+        recorder.stop(drain=0)
+    try:
+        peak = float(np.max(np.abs(recorder.get_data())))
+    except RuntimeError:
+        peak = 0.0
+    return MicTestResult(device_name, peak, recorder.input_problem_hint(timeout=0))
 
 
 def record_audio(
     device: str | None = None,
     on_start: Callable[[str], None] | None = None,
     on_stop: Callable[[], None] | None = None,
-) -> np.ndarray:
+    on_warning: Callable[[str], None] | None = None,
+    sample_rate: int = SAMPLE_RATE,
+    stop_event: threading.Event | None = None,
+    buffer_frames: int | None = None,
+    stop_detector: StopDetector | None = None,
+    gain_db: float = 0.0,
+    max_seconds: float = 0.0,
+    channels: int = 1,
+) -> tuple[np.ndarray, int]:
     """
     音声を録音する（CLI用・Ctrl+Cで停止）
 
     Args:
         device: 入力デバイス名またはID（Noneの場合はデフォルト）
         on_start: 録音開始時に解決済みデバイス名を受け取るコールバック
-        on_stop: Ctrl+C受信（録音停止処理開始）時に呼ばれるコールバック
+        on_stop: Ctrl+C受信・自動停止（録音停止処理開始）時に呼ばれるコールバック
+        on_warning: デフォルトデバイスを開けず別のデバイスを試す場合、指定レートで録音できない
+            場合、開始直後に入力が届かない場合や、録音中に入力デバイスが途切れた場合に
+            メッセージを受け取るコールバック。再接続できなければ、そこまでの録音を返す
+        sample_rate: 録音サンプルレート（デバイスが非対応ならデバイス既定のレート）
+        stop_event: 停止を呼び出し側で制御する場合のイベント。指定するとSIGINTハンドラを
+            登録せず、このイベントがセットされた時点で録音を止める（on_stop は呼ばれない）
+        buffer_frames: ストリームのバッファサイズ（フレーム数）。None ならデバイス既定。
+            デバイスが受け付けない場合は既定のサイズで録音し、on_warning に通知する
+        stop_detector: 指定すると、判定が停止を返した時点で自動的に録音を止める
+            （on_stop も呼ぶ）。無音での停止は SilenceStopDetector
+        gain_db: 入力の増幅量（dB）。増幅で音が割れたら on_warning に1度だけ通知する
+        max_seconds: 0 より大きければ、その秒数を録音した時点で自動的に止め（on_stop も呼ぶ）、
+            ちょうどその長さに切りそろえて返す
+        channels: 2 ならステレオで録音する（デバイスが非対応ならモノラルにして on_warning に通知）
 
     Returns:
-        録音された音声データ（float32のnumpy配列）
+        録音された音声データ（float32のnumpy配列。ステレオならフレーム数 × 2）と、
+        実際のサンプルレートのタプル。
+        0.1.0 では配列だけを返していた（CHANGELOG.md を参照）
 
     Raises:
-        ValueError: デバイスが見つからない場合
-        RuntimeError: 録音データが空の場合
+        DeviceNotFoundError: デバイスが見つからない場合
+        DeviceUnavailableError: 開ける入力デバイスが無い場合や、録音データが空の場合
     """
     device_id = resolve_device_id(device)
 
-    recorder = ThreadedRecorder(device_id)
-    stop_event = threading.Event()
+    if stop_event is None:
+        stop_event = threading.Event()
 
-    def _signal_handler(sig, frame):
-        if on_stop:
-            on_stop()
-        stop_event.set()
+        def _signal_handler(sig, frame):
+            if on_stop:
+                on_stop()
+            stop_event.set()
 
-    signal.signal(signal.SIGINT, _signal_handler)
+        signal.signal(signal.SIGINT, _signal_handler)
 
-    device_name = sd.query_devices(device_id)["name"] if device_id is not None else "デフォルト"
+    recorder = start_recorder(device_id, sample_rate, on_warning, buffer_frames, gain_db, channels)
+    if recorder.device_id is not None:
+        device_name = sd.query_devices(recorder.device_id)["name"]
+    else:
+        device_name = "デフォルト"
     if on_start:
         on_start(device_name)
 
-    recorder.start()
-    stop_event.wait()
+    for warning in recorder.warnings:
+        if on_warning:
+            on_warning(warning)
+    hint = recorder.input_problem_hint()
+    if hint and on_warning:
+        on_warning(hint)
+    reported = 0
+    clipping_reported = False
+
+    def report_clipping():
+        nonlocal clipping_reported
+        if recorder.clipped.is_set() and not clipping_reported:
+            clipping_reported = True
+            if on_warning:
+                on_warning(recorder.clipping_warning)
+
+    while not stop_event.wait(STOP_CHECK_SECONDS):
+        for message in recorder.interruptions[reported:]:
+            if on_warning:
+                on_warning(message)
+        reported = len(recorder.interruptions)
+        report_clipping()
+        if recorder.lost.is_set():
+            if on_stop:
+                on_stop()
+            break
+        max_samples = int(max_seconds * recorder.sample_rate)
+        if max_samples and len(recorder.recent(max_seconds)) >= max_samples:
+            _logger.info("指定の長さ（%g秒）に達したため録音を自動停止", max_seconds)
+            if on_stop:
+                on_stop()
+            break
+        if stop_detector is None:
+            continue
+        recent = recorder.recent(stop_detector.window_seconds)
+        if stop_detector.should_stop(recent, recorder.sample_rate):
+            _logger.info("停止条件を満たしたため録音を自動停止")
+            if on_stop:
+                on_stop()
+            break
     recorder.stop()
+    # 停止直前のブロックで割れた場合も知らせる
+    report_clipping()
 
-    return recorder.get_data()
+    data = recorder.get_data()
+    if max_seconds > 0:
+        data = data[: int(max_seconds * recorder.sample_rate)]
+    return data, recorder.sample_rate
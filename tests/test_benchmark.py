@@ -0,0 +1,114 @@
+"""benchmark モジュールのユニットテスト。"""
+
+import itertools
+import types
+from pathlib import Path
+
+import numpy as np
+import pytest
+from scipy.io import wavfile
+
+import benchmark
+from benchmark import BenchmarkResult, generate_sample, parse_thread_counts, run_benchmark
+from config import VoiceNoteConfig
+
+
+class _FakeModel:
+    def __init__(self):
+        self.kwargs: dict = {}
+        self.decoded = 0
+
+    def transcribe(self, path, **kwargs):
+        self.kwargs = kwargs
+
+        def segments():
+            for _ in range(3):
+                self.decoded += 1
+                yield None
+
+        return segments(), types.SimpleNamespace(duration=30.0)
+
+
+class _FakeLoader:
+    """load_model の代わり。lru_cache と同じく cache_clear を持つ。"""
+
+    def __init__(self):
+        self.model = _FakeModel()
+        self.loads: list = []
+        self.cleared = 0
+
+    def __call__(self, name, cpu_threads=0):
+        self.loads.append((name, cpu_threads))
+        return self.model
+
+    def cache_clear(self):
+        self.cleared += 1
+
+
+class TestParseThreadCounts:
+    def test_parses_comma_separated_counts(self):
+        assert parse_thread_counts("1, 2,4,") == [1, 2, 4]
+
+    @pytest.mark.parametrize("text", ["", "two", "1,-2"])
+    def test_rejects_invalid_counts(self, text: str):
+        with pytest.raises(ValueError, match="スレッド数"):
+            parse_thread_counts(text)
+
+
+class TestBenchmarkResult:
+    def test_realtime_factor_is_seconds_per_audio_second(self):
+        assert BenchmarkResult(4, 1.0, 6.0, 30.0).realtime_factor == pytest.approx(0.2)
+
+    def test_realtime_factor_of_empty_audio_is_zero(self):
+        assert BenchmarkResult(4, 1.0, 6.0, 0.0).realtime_factor == 0.0
+
+
+class TestRunBenchmark:
+    def test_reloads_model_for_each_thread_count(self, monkeypatch: pytest.MonkeyPatch):
+        loader = _FakeLoader()
+        monkeypatch.setattr(benchmark, "load_model", loader)
+        clock = itertools.count(0.0, 0.5)
+        monkeypatch.setattr(benchmark.time, "perf_counter", lambda: next(clock))
+        progress: list[str] = []
+
+        results = run_benchmark(
+            Path("/tmp/sample.wav"), VoiceNoteConfig(whisper_model="small"), [1, 0], progress.append
+        )
+
+        assert loader.loads == [("small", 1), ("small", 0)]
+        assert loader.cleared == 2
+        assert [r.cpu_threads for r in results] == [1, 0]
+        assert results[0] == BenchmarkResult(1, 0.5, 0.5, 30.0)
+        # セグメントを最後まで取り出して計測する
+        assert loader.model.decoded == 6
+        assert progress == ["cpu_threads=1 で計測中...", "cpu_threads=自動 で計測中..."]
+
+    def test_decodes_whole_audio_without_vad_or_fallback(self, monkeypatch: pytest.MonkeyPatch):
+        loader = _FakeLoader()
+        monkeypatch.setattr(benchmark, "load_model", loader)
+
+        run_benchmark(Path("/tmp/sample.wav"), VoiceNoteConfig(vad_filter=True), [0])
+
+        assert loader.model.kwargs["vad_filter"] is False
+        assert loader.model.kwargs["temperature"] == 0.0
+
+    def test_english_only_model_transcribes_as_english(self, monkeypatch: pytest.MonkeyPatch):
+        loader = _FakeLoader()
+        monkeypatch.setattr(benchmark, "load_model", loader)
+
+        run_benchmark(
+            Path("/tmp/sample.wav"), VoiceNoteConfig(whisper_model="base.en", language="ja"), [0]
+        )
+
+        assert loader.model.kwargs["language"] == "en"
+
+
+class TestGenerateSample:
+    def test_writes_16khz_mono_wav_of_requested_length(self, tmp_path: Path):
+        path = generate_sample(tmp_path / "sample.wav", seconds=2)
+
+        rate, data = wavfile.read(str(path))
+        assert rate == 16000
+        assert data.ndim == 1
+        assert len(data) == 32000
+        assert np.max(np.abs(data)) > 0
@@ -0,0 +1,62 @@
+"""clipboard モジュールのユニットテスト。"""
+
+import subprocess
+
+import pytest
+
+import clipboard
+from clipboard import clipboard_command, copy_to_clipboard
+
+
+class TestClipboardCommand:
+    def test_uses_platform_tools_on_macos_and_windows(self):
+        assert clipboard_command("darwin") == ["pbcopy"]
+        assert clipboard_command("win32") == ["clip"]
+
+    def test_prefers_first_available_linux_tool(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(clipboard.shutil, "which", lambda name: name == "xclip" or None)
+
+        assert clipboard_command("linux") == ["xclip", "-selection", "clipboard"]
+
+    def test_returns_none_without_linux_tools(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(clipboard.shutil, "which", lambda name: None)
+
+        assert clipboard_command("linux") is None
+
+
+class TestCopyToClipboard:
+    def test_passes_text_on_stdin(self, monkeypatch: pytest.MonkeyPatch):
+        calls = []
+        monkeypatch.setattr(
+            clipboard.subprocess, "run", lambda command, **kwargs: calls.append((command, kwargs))
+        )
+
+        copy_to_clipboard("文字起こし", platform="darwin")
+
+        assert calls[0][0] == ["pbcopy"]
+        assert calls[0][1]["input"] == "文字起こし".encode()
+
+    def test_encodes_utf16_for_windows_clip(self, monkeypatch: pytest.MonkeyPatch):
+        calls = []
+        monkeypatch.setattr(
+            clipboard.subprocess, "run", lambda command, **kwargs: calls.append(kwargs)
+        )
+
+        copy_to_clipboard("文字起こし", platform="win32")
+
+        assert calls[0]["input"].decode("utf-16") == "文字起こし"
+
+    def test_raises_when_no_command_available(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(clipboard.shutil, "which", lambda name: None)
+
+        with pytest.raises(RuntimeError, match="xclip"):
+            copy_to_clipboard("x", platform="linux")
+
+    def test_wraps_command_failure(self, monkeypatch: pytest.MonkeyPatch):
+        def fail(command, **kwargs):
+            raise subprocess.CalledProcessError(1, command)
+
+        monkeypatch.setattr(clipboard.subprocess, "run", fail)
+
+        with pytest.raises(RuntimeError, match="コピーに失敗"):
+            copy_to_clipboard("x", platform="darwin")
@@ -5,7 +5,28 @@ from pathlib import Path
 
 import pytest
 
-from config import InvalidConfigError, VoiceNoteConfig, load_config, resolve_api_key, save_config
+from config import (
+    InvalidConfigError,
+    VoiceNoteConfig,
+    _ask_vault_folder,
+    _default_model_choice,
+    apply_preset,
+    builtin_beam_size_notice,
+    config_dir,
+    default_recording_dir,
+    is_english_only_model,
+    known_obsidian_vaults,
+    load_config,
+    model_download_warning,
+    model_language_warning,
+    obsidian_config_paths,
+    parse_meta_items,
+    resolve_api_key,
+    resolve_editor,
+    resolve_model_settings,
+    save_config,
+    vault_root,
+)
 
 
 class TestLoadConfig:
@@ -66,9 +87,73 @@ class TestVoiceNoteConfigDefaults:
         assert config.save_folder == ""
         assert config.whisper_model == "small"
         assert config.transcription_mode == "local"
+        assert config.language is None
+        assert config.detect_languages == []
+        assert config.multilingual is False
+        assert config.beam_size == 5
+        assert config.best_of == 5
+        assert config.temperature is None
+        assert config.cpu_threads == 0
+        assert config.model_download_timeout == 30.0
+        assert config.model_overrides == {}
+        assert config.presets == {}
         assert config.vad_filter is True
+        assert config.vad_min_silence_ms == 2000
+        assert config.vad_max_chunk_seconds == 0.0
+        assert config.chunk_minutes == 0.0
+        assert config.chunk_overlap_seconds == 2.0
+        assert config.no_speech_threshold == 0.6
+        assert config.suppress_non_speech is True
+        assert config.dedupe is False
+        assert config.hallucination_filter == "off"
+        assert "ご視聴ありがとうございました" in config.hallucination_phrases
+        assert config.filler_filter is False
+        assert "えーと" in config.filler_words
+        assert config.input_device is None
+        assert config.capture_sample_rate == 16000
+        assert config.buffer_frames is None
+        assert config.gain_db == 0.0
+        assert config.stereo_capture is False
+        assert config.recording_format == "wav"
+        assert config.trim_silence is False
+        assert config.trim_threshold == 0.01
+        assert config.stop_on_silence_seconds == 0.0
+        assert config.highpass_hz == 0.0
+        assert config.resample_quality == "high"
+        assert config.resampler == {}
+        assert config.temp_dir is None
+        assert config.keep_temp is False
+        assert config.min_free_disk_mb == 500
         assert config.format_mode == "rule"
+        assert config.postprocess_command is None
+        assert config.summary_command is None
+        assert config.output_format == "md"
+        assert config.escape_markdown is False
+        assert config.wrap_callout is False
+        assert config.callout_type == "note"
+        assert config.callout_folded is False
+        assert config.segment_separator == "pause"
+        assert config.timestamp_format == "%Y-%m-%d_%H%M%S"
+        assert config.chapter_gap_seconds == 0.0
+        assert config.split_gap_seconds == 0.0
+        assert config.dual_output is False
+        assert config.audio_links is False
+        assert config.audio_link_template == "![[{file}#t={seconds}]]"
+        assert config.frontmatter_extra == {}
+        assert config.extra_tags == []
+        assert config.note_header == ""
+        assert config.note_footer == ""
+        assert config.append_to is None
+        assert config.extra_destinations == []
+        assert config.post_save_command is None
+        assert config.post_save_required is False
         assert config.openai_api_key is None
+        assert config.editor is None
+        assert config.log_file is None
+        assert config.incremental_save is False
+        assert config.skip_empty is True
+        assert config.min_transcript_chars == 1
+        assert config.batch_index_name == "{timestamp}_index"
 
 
 class TestToDict:
@@ -151,6 +236,276 @@ class TestFromDict:
         config = VoiceNoteConfig.from_dict({"save_folder": "/tmp", "unknown_key": "x"})
         assert config.save_folder == "/tmp"
 
+    def test_accepts_custom_timestamp_format(self):
+        config = VoiceNoteConfig.from_dict({"timestamp_format": "%Y%m%dT%H%M%S"})
+        assert config.timestamp_format == "%Y%m%dT%H%M%S"
+
+    @pytest.mark.parametrize("timestamp_format", ["%Y/%m/%d", "%H:%M", ""])
+    def test_rejects_unsafe_timestamp_format(self, timestamp_format: str):
+        with pytest.raises(ValueError, match="timestamp_format"):
+            VoiceNoteConfig.from_dict({"timestamp_format": timestamp_format})
+
+    @pytest.mark.parametrize(
+        "destination",
+        [
+            {"output_format": "txt"},
+            {"save_folder": "/archive", "output_format": "docx"},
+            {"save_folder": "/archive", "append_to": "log.md"},
+        ],
+    )
+    def test_rejects_invalid_extra_destination(self, destination: dict):
+        with pytest.raises(ValueError, match="extra_destinations"):
+            VoiceNoteConfig.from_dict({"extra_destinations": [destination]})
+
+    def test_accepts_extra_destinations(self):
+        destinations = [{"save_folder": "/archive", "output_format": "txt"}]
+        config = VoiceNoteConfig.from_dict({"extra_destinations": destinations})
+        assert config.extra_destinations == destinations
+
+    def test_rejects_unknown_hallucination_filter(self):
+        with pytest.raises(ValueError, match="hallucination_filter"):
+            VoiceNoteConfig.from_dict({"hallucination_filter": "end"})
+
+    def test_accepts_resampler_overrides(self):
+        resampler = {"half_length": 16, "cutoff": 0.9, "kaiser_beta": 8}
+        assert VoiceNoteConfig.from_dict({"resampler": resampler}).resampler == resampler
+
+    @pytest.mark.parametrize(
+        ("resampler", "match"),
+        [
+            ({"sinc_len": 256}, "指定できない項目"),
+            ({"half_length": 0}, "half_length"),
+            ({"half_length": 8.5}, "half_length"),
+            ({"cutoff": 1.5}, "cutoff"),
+            ({"kaiser_beta": True}, "kaiser_beta"),
+        ],
+    )
+    def test_rejects_invalid_resampler(self, resampler: dict, match: str):
+        with pytest.raises(ValueError, match=match):
+            VoiceNoteConfig.from_dict({"resampler": resampler})
+
+    @pytest.mark.parametrize("timeout", [0, -5, "30"])
+    def test_rejects_non_positive_model_download_timeout(self, timeout):
+        with pytest.raises(ValueError, match="model_download_timeout"):
+            VoiceNoteConfig.from_dict({"model_download_timeout": timeout})
+
+    @pytest.mark.parametrize("overlap", [-1, "2", True])
+    def test_rejects_invalid_chunk_overlap_seconds(self, overlap):
+        with pytest.raises(ValueError, match="chunk_overlap_seconds"):
+            VoiceNoteConfig.from_dict({"chunk_overlap_seconds": overlap})
+
+    def test_rejects_non_string_input_device(self):
+        with pytest.raises(ValueError, match="input_device"):
+            VoiceNoteConfig.from_dict({"input_device": 2})
+
+    def test_rejects_audio_link_template_with_unknown_placeholder(self):
+        with pytest.raises(ValueError, match="audio_link_template"):
+            VoiceNoteConfig.from_dict({"audio_link_template": "![[{file}#t={minutes}]]"})
+
+    def test_rejects_unknown_segment_separator(self):
+        with pytest.raises(ValueError, match="segment_separator"):
+            VoiceNoteConfig.from_dict({"segment_separator": "comma"})
+
+    def test_rejects_unknown_recording_format(self):
+        with pytest.raises(ValueError, match="recording_format"):
+            VoiceNoteConfig.from_dict({"recording_format": "mp3"})
+
+    def test_rejects_preset_that_changes_save_folder(self):
+        with pytest.raises(ValueError, match="プリセット 'memo'"):
+            VoiceNoteConfig.from_dict({"presets": {"memo": {"save_folder": "/x"}}})
+
+    def test_rejects_non_scalar_frontmatter_extra(self):
+        with pytest.raises(ValueError, match="frontmatter_extra"):
+            VoiceNoteConfig.from_dict({"frontmatter_extra": {"project": ["a", "b"]}})
+
+    def test_rejects_tag_with_spaces(self):
+        with pytest.raises(ValueError, match="タグ"):
+            VoiceNoteConfig.from_dict({"extra_tags": ["project x"]})
+
+    @pytest.mark.parametrize("highpass_hz", [-80, "80", True])
+    def test_rejects_invalid_highpass_hz(self, highpass_hz):
+        with pytest.raises(ValueError, match="highpass_hz"):
+            VoiceNoteConfig.from_dict({"highpass_hz": highpass_hz})
+
+    @pytest.mark.parametrize("append_to", ["/notes/log.md", "../log.md", "logs/../../log.md"])
+    def test_rejects_append_to_outside_save_folder(self, append_to: str):
+        with pytest.raises(ValueError, match="追記先"):
+            VoiceNoteConfig.from_dict({"append_to": append_to})
+
+    def test_accepts_relative_append_to(self):
+        config = VoiceNoteConfig.from_dict({"append_to": "logs/running.md"})
+
+        assert config.append_to == "logs/running.md"
+
+    def test_rejects_negative_chapter_gap_seconds(self):
+        with pytest.raises(ValueError, match="chapter_gap_seconds"):
+            VoiceNoteConfig.from_dict({"chapter_gap_seconds": -30})
+
+    def test_rejects_unknown_resample_quality(self):
+        with pytest.raises(ValueError, match="resample_quality"):
+            VoiceNoteConfig.from_dict({"resample_quality": "best"})
+
+    def test_load_config_reports_unknown_resample_quality(self, tmp_path: Path):
+        path = tmp_path / "config.json"
+        path.write_text(json.dumps({"resample_quality": "best"}), encoding="utf-8")
+
+        with pytest.raises(InvalidConfigError, match="resample_quality"):
+            load_config(path)
+
+    def test_rejects_batch_index_name_with_path_separator(self):
+        with pytest.raises(ValueError, match="batch_index_name"):
+            VoiceNoteConfig.from_dict({"batch_index_name": "../{timestamp}_index"})
+
+    def test_rejects_unknown_output_format(self):
+        with pytest.raises(ValueError, match="output_format"):
+            VoiceNoteConfig.from_dict({"output_format": "docx"})
+
+    def test_rejects_extra_tags_that_is_not_a_list(self):
+        with pytest.raises(ValueError, match="extra_tags はタグのリスト"):
+            VoiceNoteConfig.from_dict({"extra_tags": "meeting"})
+
+
+class TestParseMetaItems:
+    def test_splits_on_first_equals(self):
+        assert parse_meta_items(["project=VoiceNote", "url=https://x/?a=b"]) == {
+            "project": "VoiceNote",
+            "url": "https://x/?a=b",
+        }
+
+    @pytest.mark.parametrize("item", ["project", "=value"])
+    def test_rejects_items_without_key(self, item: str):
+        with pytest.raises(ValueError, match="key=value"):
+            parse_meta_items([item])
+
+
+class TestConfigDir:
+    @pytest.fixture(autouse=True)
+    def _clear_env(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.delenv("VOICENOTE_CONFIG_DIR", raising=False)
+        monkeypatch.delenv("XDG_CONFIG_HOME", raising=False)
+
+    @staticmethod
+    def _no_home(monkeypatch: pytest.MonkeyPatch):
+        def home():
+            raise RuntimeError("Could not determine home directory.")
+
+        monkeypatch.setattr(Path, "home", home)
+
+    def test_defaults_to_dot_config_under_home(self, monkeypatch, tmp_path: Path):
+        monkeypatch.setattr(Path, "home", lambda: tmp_path)
+        monkeypatch.setenv("XDG_CONFIG_HOME", str(tmp_path / "xdg"))
+        assert config_dir() == tmp_path / ".config" / "voicenote"
+
+    def test_env_override_comes_first(self, monkeypatch, tmp_path: Path):
+        monkeypatch.setattr(Path, "home", lambda: tmp_path)
+        monkeypatch.setenv("VOICENOTE_CONFIG_DIR", str(tmp_path / "custom"))
+        assert config_dir() == tmp_path / "custom"
+
+    def test_falls_back_to_xdg_config_home_without_home(self, monkeypatch, tmp_path: Path):
+        self._no_home(monkeypatch)
+        monkeypatch.setenv("XDG_CONFIG_HOME", str(tmp_path))
+        assert config_dir() == tmp_path / "voicenote"
+
+    def test_treats_unexpanded_tilde_as_no_home(self, monkeypatch, tmp_path: Path):
+        monkeypatch.setattr(Path, "home", lambda: Path("~"))
+        monkeypatch.setenv("XDG_CONFIG_HOME", str(tmp_path))
+        assert config_dir() == tmp_path / "voicenote"
+
+    def test_last_resort_is_current_folder(self, monkeypatch, tmp_path: Path):
+        self._no_home(monkeypatch)
+        monkeypatch.chdir(tmp_path)
+        assert config_dir() == tmp_path / ".voicenote"
+
+
+class TestDefaultRecordingDir:
+    def test_is_desktop_under_home(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        monkeypatch.setattr(Path, "home", lambda: tmp_path)
+        assert default_recording_dir() == tmp_path / "Desktop"
+
+    def test_falls_back_to_current_folder_without_home(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        TestConfigDir._no_home(monkeypatch)
+        monkeypatch.chdir(tmp_path)
+        assert default_recording_dir() == tmp_path
+
+
+class TestObsidianVaults:
+    @staticmethod
+    def _write(path: Path, vaults: dict) -> Path:
+        path.write_text(json.dumps({"vaults": vaults}), encoding="utf-8")
+        return path
+
+    def test_lists_existing_vaults_most_recently_opened_first(self, tmp_path: Path):
+        (tmp_path / "old").mkdir()
+        (tmp_path / "new").mkdir()
+        obsidian_json = self._write(
+            tmp_path / "obsidian.json",
+            {
+                "a1": {"path": str(tmp_path / "old"), "ts": 100},
+                "b2": {"path": str(tmp_path / "new"), "ts": 200, "open": True},
+                "c3": {"path": str(tmp_path / "deleted"), "ts": 300},
+            },
+        )
+
+        assert known_obsidian_vaults([obsidian_json]) == [tmp_path / "new", tmp_path / "old"]
+
+    def test_skips_missing_and_malformed_files(self, tmp_path: Path):
+        (tmp_path / "vault").mkdir()
+        broken = tmp_path / "broken.json"
+        broken.write_text("{", encoding="utf-8")
+        other = self._write(tmp_path / "other.json", {"x": "not-a-dict"})
+        valid = self._write(tmp_path / "obsidian.json", {"a1": {"path": str(tmp_path / "vault")}})
+
+        vaults = known_obsidian_vaults([tmp_path / "missing.json", broken, other, valid])
+
+        assert vaults == [tmp_path / "vault"]
+
+    def test_linux_candidates_include_flatpak_and_snap(self, monkeypatch, tmp_path: Path):
+        monkeypatch.setattr(Path, "home", lambda: tmp_path)
+        monkeypatch.delenv("XDG_CONFIG_HOME", raising=False)
+
+        paths = obsidian_config_paths("linux")
+
+        assert paths[0] == tmp_path / ".config" / "obsidian" / "obsidian.json"
+        assert any("md.obsidian.Obsidian" in str(p) for p in paths)
+        assert any("snap" in p.parts for p in paths)
+
+    def test_vault_subfolder_must_stay_inside_vault(self, monkeypatch, tmp_path: Path):
+        answers = iter(["1", "/etc", "C:\\Users", "../outside", "notes/../../x", "notes/voice"])
+        monkeypatch.setattr("config.Prompt.ask", lambda *args, **kwargs: next(answers))
+
+        assert _ask_vault_folder([tmp_path]) == tmp_path / "notes" / "voice"
+        assert next(answers, None) is None
+
+    def test_creates_nested_vault_subfolder(self, monkeypatch, tmp_path: Path):
+        answers = iter(["1", "recordings/voice/{year}"])
+        monkeypatch.setattr("config.Prompt.ask", lambda *args, **kwargs: next(answers))
+
+        folder = _ask_vault_folder([tmp_path])
+
+        assert folder == tmp_path / "recordings" / "voice" / "{year}"
+        assert (tmp_path / "recordings" / "voice").is_dir()
+
+    def test_asks_again_when_vault_subfolder_cannot_be_created(
+        self, monkeypatch, tmp_path: Path
+    ):
+        (tmp_path / "file").write_text("", encoding="utf-8")
+        answers = iter(["1", "file/voice", "voice"])
+        monkeypatch.setattr("config.Prompt.ask", lambda *args, **kwargs: next(answers))
+
+        assert _ask_vault_folder([tmp_path]) == tmp_path / "voice"
+        assert next(answers, None) is None
+
+    def test_windows_candidate_is_under_appdata(self, monkeypatch, tmp_path: Path):
+        monkeypatch.setenv("APPDATA", str(tmp_path))
+
+        assert obsidian_config_paths("win32") == [tmp_path / "obsidian" / "obsidian.json"]
+
+    def test_dot_obsidian_folder_points_to_its_vault(self, tmp_path: Path):
+        assert vault_root(tmp_path / "vault" / ".obsidian") == tmp_path / "vault"
+        assert vault_root(tmp_path / "vault" / "notes") == tmp_path / "vault" / "notes"
+
 
 class TestResolveApiKey:
     def test_prefers_env_over_config(self, monkeypatch):
@@ -167,3 +522,189 @@ class TestResolveApiKey:
         monkeypatch.delenv("OPENAI_API_KEY", raising=False)
         config = VoiceNoteConfig()
         assert resolve_api_key(config) is None
+
+
+class TestResolveEditor:
+    def test_prefers_config_over_env(self, monkeypatch):
+        monkeypatch.setenv("EDITOR", "vim")
+        config = VoiceNoteConfig(editor="code --wait")
+        assert resolve_editor(config) == ["code", "--wait"]
+
+    def test_falls_back_to_env(self, monkeypatch):
+        monkeypatch.setenv("EDITOR", "nano")
+        assert resolve_editor(VoiceNoteConfig()) == ["nano"]
+
+    def test_returns_none_when_unset(self, monkeypatch):
+        monkeypatch.delenv("EDITOR", raising=False)
+        assert resolve_editor(VoiceNoteConfig()) is None
+
+    def test_returns_none_for_blank_command(self, monkeypatch):
+        monkeypatch.delenv("EDITOR", raising=False)
+        assert resolve_editor(VoiceNoteConfig(editor="   ")) is None
+
+
+class TestResolveModelSettings:
+    def test_user_overrides_take_precedence_over_global(self):
+        config = VoiceNoteConfig(
+            whisper_model="large-v3",
+            beam_size=5,
+            model_overrides={"large-v3": {"beam_size": 10, "cpu_threads": 8}},
+        )
+        resolved = resolve_model_settings(config)
+        assert resolved.beam_size == 10
+        assert resolved.cpu_threads == 8
+
+    def test_only_selected_model_is_applied(self):
+        config = VoiceNoteConfig(
+            whisper_model="small", model_overrides={"large-v3": {"beam_size": 10}}
+        )
+        assert resolve_model_settings(config).beam_size == 5
+
+    def test_builtin_defaults_apply_without_user_entry(self):
+        assert resolve_model_settings(VoiceNoteConfig(whisper_model="tiny")).beam_size == 1
+
+    def test_user_entry_replaces_builtin_defaults(self):
+        config = VoiceNoteConfig(whisper_model="tiny", model_overrides={"tiny": {"language": "ja"}})
+        resolved = resolve_model_settings(config)
+        assert resolved.language == "ja"
+        assert resolved.beam_size == 5
+
+    def test_ignores_keys_that_cannot_be_overridden(self):
+        config = VoiceNoteConfig(
+            whisper_model="small", model_overrides={"small": {"save_folder": "/x", "beam_size": 3}}
+        )
+        resolved = resolve_model_settings(config)
+        assert resolved.save_folder == ""
+        assert resolved.beam_size == 3
+
+
+class TestBuiltinBeamSizeNotice:
+    def test_notices_when_builtin_default_lowers_beam_size(self):
+        notice = builtin_beam_size_notice(VoiceNoteConfig(whisper_model="tiny"))
+
+        assert "beam_size 1" in notice
+        assert "model_overrides" in notice
+
+    def test_silent_when_user_sets_model_entry(self):
+        config = VoiceNoteConfig(whisper_model="tiny", model_overrides={"tiny": {"beam_size": 1}})
+
+        assert builtin_beam_size_notice(config) is None
+
+    def test_silent_for_models_without_builtin_default(self):
+        assert builtin_beam_size_notice(VoiceNoteConfig(whisper_model="small")) is None
+
+    def test_silent_when_configured_beam_size_already_matches(self):
+        config = VoiceNoteConfig(whisper_model="base", beam_size=1)
+
+        assert builtin_beam_size_notice(config) is None
+
+
+class TestApplyPreset:
+    _PRESETS = {
+        "meeting": {"whisper_model": "medium", "language": "ja", "beam_size": 5},
+        "memo": {"whisper_model": "tiny", "language": "ja"},
+    }
+
+    def test_overrides_transcription_settings_only(self):
+        config = VoiceNoteConfig(save_folder="/vault", presets=self._PRESETS)
+
+        applied = apply_preset(config, "meeting")
+
+        assert applied.whisper_model == "medium"
+        assert applied.language == "ja"
+        assert applied.save_folder == "/vault"
+
+    def test_preset_wins_over_model_overrides(self):
+        config = VoiceNoteConfig(
+            presets={"quick": {"whisper_model": "tiny", "beam_size": 3}},
+            model_overrides={"tiny": {"beam_size": 2, "cpu_threads": 4}},
+        )
+
+        resolved = resolve_model_settings(apply_preset(config, "quick"))
+
+        assert resolved.beam_size == 3
+        assert resolved.cpu_threads == 4
+
+    def test_keeps_builtin_defaults_for_unset_keys(self):
+        config = VoiceNoteConfig(presets=self._PRESETS)
+
+        resolved = resolve_model_settings(apply_preset(config, "memo"))
+
+        assert resolved.beam_size == 1
+        assert resolved.language == "ja"
+
+    def test_unknown_preset_lists_available(self):
+        config = VoiceNoteConfig(presets=self._PRESETS)
+
+        with pytest.raises(ValueError, match="meeting, memo"):
+            apply_preset(config, "lecture")
+
+
+class TestIsEnglishOnlyModel:
+    @pytest.mark.parametrize(("name", "expected"), [("small.en", True), ("small", False)])
+    def test_detects_en_suffix(self, name: str, expected: bool):
+        assert is_english_only_model(name) is expected
+
+
+class TestModelLanguageWarning:
+    def test_warns_about_non_english_language(self):
+        warning = model_language_warning(VoiceNoteConfig(whisper_model="base.en", language="ja"))
+
+        assert "base.en" in warning
+        assert "language 'ja'" in warning
+
+    def test_warns_about_language_detection_and_multilingual(self):
+        config = VoiceNoteConfig(
+            whisper_model="base.en", detect_languages=["ja", "en"], multilingual=True
+        )
+
+        warning = model_language_warning(config)
+
+        assert "detect_languages（ja, en）" in warning
+        assert "multilingual" in warning
+
+    def test_uses_language_from_model_overrides(self):
+        config = VoiceNoteConfig(
+            whisper_model="base.en", model_overrides={"base.en": {"language": "ja"}}
+        )
+
+        assert "language 'ja'" in model_language_warning(config)
+
+    @pytest.mark.parametrize(
+        "config",
+        [
+            VoiceNoteConfig(whisper_model="base.en"),
+            VoiceNoteConfig(whisper_model="base.en", language="en"),
+            VoiceNoteConfig(whisper_model="base.en", detect_languages=["en"]),
+            VoiceNoteConfig(whisper_model="small", language="ja"),
+            VoiceNoteConfig(whisper_model="base.en", language="ja", transcription_mode="openai"),
+        ],
+    )
+    def test_no_warning_for_matching_settings(self, config: VoiceNoteConfig):
+        assert model_language_warning(config) is None
+
+
+class TestModelDownloadWarning:
+    @pytest.mark.parametrize("name", ["large-v2", "large-v3"])
+    def test_warns_about_large_models(self, name: str):
+        assert "3GB" in model_download_warning(name)
+
+    def test_no_warning_for_small_models(self):
+        assert model_download_warning("small") is None
+
+    def test_unlisted_large_model_warns_without_size(self):
+        warning = model_download_warning("large-v3-turbo")
+
+        assert "large-v3-turbo" in warning
+        assert "GB" not in warning
+
+
+class TestDefaultModelChoice:
+    def test_defaults_to_small_without_previous_config(self):
+        assert _default_model_choice(None) == "3"
+
+    def test_preselects_configured_model(self):
+        assert _default_model_choice(VoiceNoteConfig(whisper_model="medium")) == "4"
+
+    def test_falls_back_to_small_for_unknown_model(self):
+        assert _default_model_choice(VoiceNoteConfig(whisper_model="/models/custom")) == "3"
@@ -19,6 +19,16 @@ class TestBuildDeviceLabels:
         ]
         assert build_device_labels(devices) == ["[0] 内蔵マイク", "[2] USB マイク"]
 
+    def test_marks_loopback_devices(self):
+        devices = [
+            {"id": 0, "name": "内蔵マイク", "input_channels": 1, "loopback": False},
+            {"id": 4, "name": "BlackHole 2ch", "input_channels": 2, "loopback": True},
+        ]
+        assert build_device_labels(devices) == [
+            "[0] 内蔵マイク",
+            "[4] BlackHole 2ch (システム音声)",
+        ]
+
     def test_returns_placeholder_when_empty(self):
         assert build_device_labels([]) == ["デバイスなし"]
 
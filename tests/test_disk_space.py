@@ -0,0 +1,52 @@
+"""disk_space モジュールのユニットテスト。"""
+
+from collections import namedtuple
+from pathlib import Path
+
+import pytest
+
+import disk_space
+from disk_space import InsufficientDiskSpaceError, ensure_free_space, free_megabytes
+
+_Usage = namedtuple("_Usage", "total used free")
+_MB = 1024 * 1024
+
+
+class TestFreeMegabytes:
+    def test_uses_nearest_existing_parent(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        queried: list[Path] = []
+
+        def usage(path):
+            queried.append(path)
+            return _Usage(0, 0, 2048 * _MB)
+
+        monkeypatch.setattr(disk_space.shutil, "disk_usage", usage)
+
+        assert free_megabytes(tmp_path / "not" / "yet") == 2048
+        assert queried == [tmp_path]
+
+
+class TestEnsureFreeSpace:
+    def _free(self, monkeypatch: pytest.MonkeyPatch, megabytes: int):
+        monkeypatch.setattr(
+            disk_space.shutil, "disk_usage", lambda path: _Usage(0, 0, megabytes * _MB)
+        )
+
+    def test_raises_with_free_and_required_sizes(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        self._free(monkeypatch, 100)
+
+        with pytest.raises(InsufficientDiskSpaceError, match="空き 100MB / 必要 500MB") as e:
+            ensure_free_space(tmp_path, 500, "録音")
+        assert "録音を中止" in str(e.value)
+
+    def test_passes_with_enough_space(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        self._free(monkeypatch, 600)
+
+        ensure_free_space(tmp_path, 500, "録音")
+
+    def test_zero_disables_check(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        self._free(monkeypatch, 0)
+
+        ensure_free_space(tmp_path, 0, "録音")
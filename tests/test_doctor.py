@@ -0,0 +1,215 @@
+"""doctor モジュールのユニットテスト。"""
+
+import io
+import urllib.error
+from pathlib import Path
+
+import pytest
+
+import doctor
+from config import VoiceNoteConfig
+from doctor import (
+    check_config,
+    check_input_device,
+    check_model_dir,
+    check_model_download,
+    check_model_language,
+    check_save_folder,
+    model_cache_dir,
+)
+from errors import DeviceNotFoundError
+
+
+class _FakeRecorder:
+    def __init__(self, sample_rate: int, start_error: Exception | None = None, hint=None):
+        self.sample_rate = sample_rate
+        self._start_error = start_error
+        self._hint = hint
+        self.stopped = False
+
+    def start(self):
+        if self._start_error:
+            raise self._start_error
+
+    def input_problem_hint(self, timeout: float) -> str | None:
+        return self._hint
+
+    def stop(self, drain: float = 0):
+        self.stopped = True
+
+
+class TestCheckConfig:
+    def test_missing_config_fails_with_setup_hint(self, tmp_path: Path):
+        check, config = check_config(tmp_path / "config.json")
+
+        assert not check.ok
+        assert "--config" in check.detail
+        assert config is None
+
+    def test_invalid_config_fails(self, tmp_path: Path):
+        path = tmp_path / "config.json"
+        path.write_text("{", encoding="utf-8")
+
+        check, config = check_config(path)
+
+        assert not check.ok
+        assert config is None
+
+    def test_valid_config_is_returned(self, tmp_path: Path):
+        path = tmp_path / "config.json"
+        path.write_text('{"save_folder": "notes"}', encoding="utf-8")
+
+        check, config = check_config(path)
+
+        assert check.ok
+        assert config.save_folder == "notes"
+
+
+class TestCheckSaveFolder:
+    def test_fails_without_config(self):
+        assert not check_save_folder(None).ok
+
+    def test_fails_when_parent_is_missing(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path / "missing" / "notes"))
+
+        assert not check_save_folder(config).ok
+
+    def test_passes_for_creatable_folder(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path / "notes" / "{year}"))
+
+        check = check_save_folder(config)
+
+        assert check.ok
+        assert check.detail == str(tmp_path / "notes")
+
+
+class TestCheckInputDevice:
+    def test_fails_when_device_cannot_be_opened(self):
+        check = check_input_device(
+            None,
+            lambda config: _FakeRecorder(
+                config.capture_sample_rate, start_error=RuntimeError("busy")
+            ),
+        )
+
+        assert not check.ok
+        assert "busy" in check.detail
+
+    def test_fails_with_hint_when_no_audio_arrives(self):
+        recorder = _FakeRecorder(16000, hint="マイクの音量を確認してください")
+
+        check = check_input_device(None, lambda config: recorder)
+
+        assert not check.ok
+        assert check.detail == "マイクの音量を確認してください"
+        assert recorder.stopped
+
+    def test_passes_at_configured_rate(self):
+        config = VoiceNoteConfig(capture_sample_rate=48000)
+
+        check = check_input_device(config, lambda c: _FakeRecorder(c.capture_sample_rate))
+
+        assert check.ok
+        assert "48000Hz" in check.detail
+
+    def test_opens_configured_device_with_recording_settings(
+        self, monkeypatch: pytest.MonkeyPatch
+    ):
+        created: list[tuple] = []
+
+        def fake_recorder(device_id, sample_rate, buffer_frames, gain_db, channels):
+            created.append((device_id, sample_rate, buffer_frames, gain_db, channels))
+            return _FakeRecorder(sample_rate)
+
+        monkeypatch.setattr(doctor, "resolve_device_id", lambda name: {"USB Mic": 3}[name])
+        monkeypatch.setattr(doctor, "ThreadedRecorder", fake_recorder)
+        config = VoiceNoteConfig(
+            input_device="USB Mic",
+            capture_sample_rate=48000,
+            buffer_frames=2048,
+            gain_db=6.0,
+            stereo_capture=True,
+        )
+
+        check = check_input_device(config)
+
+        assert check.ok
+        assert created == [(3, 48000, 2048, 6.0, 2)]
+
+    def test_fails_when_configured_device_is_missing(self, monkeypatch: pytest.MonkeyPatch):
+        def missing(name):
+            raise DeviceNotFoundError(f"デバイス '{name}' が見つかりません")
+
+        monkeypatch.setattr(doctor, "resolve_device_id", missing)
+
+        check = check_input_device(VoiceNoteConfig(input_device="AirPods"))
+
+        assert not check.ok
+        assert "AirPods" in check.detail
+
+
+class TestCheckModelLanguage:
+    def test_fails_for_english_only_model_with_other_language(self):
+        check = check_model_language(VoiceNoteConfig(whisper_model="small.en", language="ja"))
+
+        assert not check.ok
+        assert "small.en" in check.detail
+
+    def test_passes_with_default_config(self):
+        check = check_model_language(None)
+
+        assert check.ok
+        assert check.detail == "small（language: 自動判定）"
+
+
+class TestModelDir:
+    def test_honors_hf_home(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        monkeypatch.delenv("HF_HUB_CACHE", raising=False)
+        monkeypatch.setenv("HF_HOME", str(tmp_path))
+
+        assert model_cache_dir() == tmp_path / "hub"
+
+    def test_falls_back_to_xdg_cache_home_without_home(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        def home():
+            raise RuntimeError("Could not determine home directory.")
+
+        monkeypatch.delenv("HF_HUB_CACHE", raising=False)
+        monkeypatch.delenv("HF_HOME", raising=False)
+        monkeypatch.setattr(Path, "home", home)
+        monkeypatch.setenv("XDG_CACHE_HOME", str(tmp_path))
+
+        assert model_cache_dir() == tmp_path / "huggingface" / "hub"
+
+    def test_passes_when_nearest_existing_parent_is_writable(self, tmp_path: Path):
+        assert check_model_dir(tmp_path / "hf" / "hub").ok
+
+    def test_fails_when_not_writable(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
+        monkeypatch.setattr(doctor.os, "access", lambda path, mode: False)
+
+        assert not check_model_dir(tmp_path).ok
+
+
+class TestCheckModelDownload:
+    def test_fails_when_unreachable(self, monkeypatch: pytest.MonkeyPatch):
+        def offline(request, timeout):
+            raise urllib.error.URLError("Name or service not known")
+
+        monkeypatch.setattr(doctor.urllib.request, "urlopen", offline)
+
+        check = check_model_download("https://example.invalid")
+
+        assert not check.ok
+        assert "ダウンロード済みのモデル" in check.detail
+
+    def test_http_error_response_counts_as_reachable(self, monkeypatch: pytest.MonkeyPatch):
+        def forbidden(request, timeout):
+            raise urllib.error.HTTPError(request.full_url, 403, "Forbidden", {}, io.BytesIO())
+
+        monkeypatch.setattr(doctor.urllib.request, "urlopen", forbidden)
+
+        check = check_model_download("https://example.com")
+
+        assert check.ok
+        assert "403" in check.detail
@@ -2,11 +2,20 @@
 
 LLM 整形 (`_apply_llm_format`) は外部 API 呼び出しのためスコープ外。
 ルールベース整形 (`_apply_rule_based_format`) と `format_transcription` の
-分岐 (none/rule) のみをテストする。
+分岐 (none/rule)、外部コマンドによる要約 (`summarize_transcription`) をテストする。
 """
 
+import shlex
+import sys
+
 from config import VoiceNoteConfig
-from formatter import _apply_rule_based_format, format_transcription
+from formatter import (
+    _apply_rule_based_format,
+    clean_transcript,
+    format_transcription,
+    postprocess_transcription,
+    summarize_transcription,
+)
 
 
 class TestApplyRuleBasedFormat:
@@ -28,6 +37,23 @@ class TestApplyRuleBasedFormat:
         assert "驚いた！\n" in result
         assert "本当ですか？\n" in result
 
+    def test_keeps_blank_lines_between_segments(self):
+        result = _apply_rule_based_format(
+            "晴れです。散歩しました。\n\n明日は雨です。", keep_line_breaks=True
+        )
+        assert result == "晴れです。\n散歩しました。\n\n明日は雨です。"
+
+    def test_keeps_each_bullet_on_one_line(self):
+        result = _apply_rule_based_format(
+            "- 晴れです。散歩しました。\n- 明日は雨です。", keep_line_breaks=True
+        )
+        assert result == "- 晴れです。散歩しました。\n- 明日は雨です。"
+
+    def test_collapses_blank_line_after_sentence_end_by_default(self):
+        # segment_separator が pause（既定）のときの従来の出力
+        result = _apply_rule_based_format("晴れです。\n\n明日は雨です。")
+        assert result == "晴れです。\n明日は雨です。"
+
     def test_removes_filler_eeto(self):
         result = _apply_rule_based_format("えーと 今日は晴れです")
         assert "えーと" not in result
@@ -61,6 +87,21 @@ class TestApplyRuleBasedFormat:
         assert not result.endswith(" ")
 
 
+class TestCleanTranscript:
+    def test_joins_paragraphs_split_mid_sentence(self):
+        text = "今日は\n\n晴れです。\n\n明日は\n雨です。"
+        assert clean_transcript(text) == "今日は晴れです。\n\n明日は雨です。"
+
+    def test_joins_non_japanese_fragments_with_space(self):
+        assert clean_transcript("Hello\n\nworld.") == "Hello world."
+
+    def test_strips_bullets_and_extra_spaces(self):
+        assert clean_transcript("- はい、\n-   そうです。") == "はい、そうです。"
+
+    def test_keeps_paragraph_after_sentence_end(self):
+        assert clean_transcript("終わり。」\n\n次") == "終わり。」\n\n次"
+
+
 class TestFormatTranscription:
     def test_returns_text_unchanged_when_mode_none(self):
         text = "えーと これは整形されない。"
@@ -76,6 +117,34 @@ class TestFormatTranscription:
         assert "えーと" not in result
         assert "一文目です。\n" in result
 
+    def test_rule_mode_removes_filler_words_when_filler_filter_is_enabled(self):
+        config = VoiceNoteConfig(format_mode="rule", filler_filter=True, filler_words=["なるほど"])
+
+        result = format_transcription("なるほど、いいです。次です。", config)
+
+        assert result == "いいです。\n次です。"
+
+    def test_rule_mode_keeps_filler_words_when_filler_filter_is_disabled(self):
+        config = VoiceNoteConfig(format_mode="rule", filler_words=["なるほど", "えー"])
+
+        result = format_transcription("なるほど、えー いいです。", config)
+
+        assert result == "なるほど、えー いいです。"
+
+    def test_rule_mode_collapses_blank_lines_for_pause_separator(self):
+        config = VoiceNoteConfig(format_mode="rule", segment_separator="pause")
+
+        result = format_transcription("晴れです。\n\n明日は雨です。", config)
+
+        assert result == "晴れです。\n明日は雨です。"
+
+    def test_rule_mode_keeps_blank_lines_for_blank_separator(self):
+        config = VoiceNoteConfig(format_mode="rule", segment_separator="blank")
+
+        result = format_transcription("晴れです。\n\n明日は雨です。", config)
+
+        assert result == "晴れです。\n\n明日は雨です。"
+
     def test_unknown_mode_returns_input_unchanged(self):
         text = "そのまま"
         assert format_transcription(text, VoiceNoteConfig(format_mode="unknown")) == text
@@ -132,3 +201,68 @@ class TestFormatTranscriptionLlmMode:
         config = VoiceNoteConfig(format_mode="llm", openai_api_key=None)
         result = format_transcription("えーと 一文目です。", config)
         assert "えーと" not in result
+
+
+class TestSummarizeTranscription:
+    def _command(self, script: str) -> str:
+        return f"{shlex.quote(sys.executable)} -c {shlex.quote(script)}"
+
+    def test_returns_command_stdout_for_text_on_stdin(self):
+        script = "import sys; print('要約: ' + sys.stdin.read().strip())"
+        config = VoiceNoteConfig(summary_command=self._command(script))
+
+        assert summarize_transcription("本文", config) == "要約: 本文"
+
+    def test_returns_none_when_unset(self):
+        assert summarize_transcription("本文", VoiceNoteConfig()) is None
+
+    def test_warns_and_returns_none_when_command_fails(self):
+        script = "import sys; sys.stderr.write('model not found'); sys.exit(1)"
+        config = VoiceNoteConfig(summary_command=self._command(script))
+        messages: list[str] = []
+
+        assert summarize_transcription("本文", config, progress_callback=messages.append) is None
+        assert any(m.startswith("⚠") and "model not found" in m for m in messages)
+
+    def test_warns_and_returns_none_when_command_is_missing(self):
+        config = VoiceNoteConfig(summary_command="voicenote-no-such-command")
+        messages: list[str] = []
+
+        assert summarize_transcription("本文", config, progress_callback=messages.append) is None
+        assert messages[-1].startswith("⚠")
+
+
+class TestPostprocessTranscription:
+    def _command(self, script: str) -> str:
+        return f"{shlex.quote(sys.executable)} -c {shlex.quote(script)}"
+
+    def test_returns_command_stdout_for_text_on_stdin(self):
+        script = "import sys; print(sys.stdin.read().strip() + '。')"
+        config = VoiceNoteConfig(postprocess_command=self._command(script))
+
+        assert postprocess_transcription("本文", config) == "本文。"
+
+    def test_returns_text_unchanged_when_unset(self):
+        assert postprocess_transcription("本文", VoiceNoteConfig()) == "本文"
+
+    def test_keeps_text_and_warns_when_command_fails(self):
+        script = "import sys; sys.stderr.write('boom'); sys.exit(2)"
+        config = VoiceNoteConfig(postprocess_command=self._command(script))
+        messages: list[str] = []
+
+        assert postprocess_transcription("本文", config, messages.append) == "本文"
+        assert any(m.startswith("⚠") and "boom" in m for m in messages)
+
+    def test_keeps_text_and_warns_when_command_is_missing(self):
+        config = VoiceNoteConfig(postprocess_command="voicenote-no-such-command")
+        messages: list[str] = []
+
+        assert postprocess_transcription("本文", config, messages.append) == "本文"
+        assert messages[-1].startswith("⚠")
+
+    def test_keeps_text_when_output_is_empty(self):
+        config = VoiceNoteConfig(postprocess_command=self._command("pass"))
+        messages: list[str] = []
+
+        assert postprocess_transcription("本文", config, messages.append) == "本文"
+        assert "空" in messages[-1]
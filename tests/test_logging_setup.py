@@ -0,0 +1,44 @@
+"""logging_setup モジュールのユニットテスト。"""
+
+import logging
+from pathlib import Path
+
+from logging_setup import add_log_file
+
+
+class TestAddLogFile:
+    def test_appends_timestamped_info_lines_immediately(self, tmp_path: Path):
+        path = tmp_path / "logs" / "voicenote.log"
+        path.parent.mkdir()
+        path.write_text("以前のログ\n", encoding="utf-8")
+        logger = logging.getLogger("voicenote")
+        previous_level = logger.level
+        logger.setLevel(logging.DEBUG)
+        add_log_file(path)
+        handler = logger.handlers[-1]
+        try:
+            logger.debug("詳細")
+            logger.info("文字起こし完了: model=small")
+            logger.error("保存エラー")
+
+            # close() 前でも書き出されている
+            lines = path.read_text(encoding="utf-8").splitlines()
+        finally:
+            logger.removeHandler(handler)
+            handler.close()
+            logger.setLevel(previous_level)
+
+        assert lines[0] == "以前のログ"
+        assert lines[1].endswith("[INFO] 文字起こし完了: model=small")
+        assert lines[2].endswith("[ERROR] 保存エラー")
+        assert len(lines) == 3
+
+    def test_creates_parent_directory(self, tmp_path: Path):
+        path = tmp_path / "nested" / "voicenote.log"
+        logger = logging.getLogger("voicenote")
+        add_log_file(path)
+        handler = logger.handlers[-1]
+        logger.removeHandler(handler)
+        handler.close()
+
+        assert path.parent.is_dir()
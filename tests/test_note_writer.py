@@ -1,16 +1,41 @@
 """note_writer モジュールのユニットテスト。"""
 
+import json
 import re
+from datetime import datetime
 from pathlib import Path
 
 import pytest
 
-from note_writer import save_transcript
+from note_writer import (
+    DEFAULT_AUDIO_LINK_TEMPLATE,
+    BatchIndexEntry,
+    NoteWriteError,
+    PartialNote,
+    SaveFolderUnavailableError,
+    _yaml_value,
+    append_transcript,
+    check_audio_link_template,
+    check_callout_type,
+    check_timestamp_format,
+    escape_markdown_body,
+    escape_markdown_text,
+    expand_note_template,
+    expand_save_folder,
+    format_audio_link,
+    normalize_tags,
+    render_chapters,
+    render_timestamped_segments,
+    save_batch_index,
+    save_transcript,
+    template_root,
+    wrap_callout,
+)
 
 
 class TestSaveTranscript:
     def test_creates_save_folder_if_missing(self, tmp_path: Path):
-        target = tmp_path / "nested" / "notes"
+        target = tmp_path / "notes"
         saved = save_transcript(target, "本文", format_mode="none")
         assert saved.parent == target
         assert target.is_dir()
@@ -19,6 +44,17 @@ class TestSaveTranscript:
         saved = save_transcript(tmp_path, "本文", format_mode="none")
         assert re.match(r"^\d{4}-\d{2}-\d{2}_\d{6}_raw\.md$", saved.name)
 
+    def test_same_timestamp_does_not_overwrite(self, tmp_path: Path):
+        # 年だけの書式で、同じ秒に保存した状況を再現する
+        first = save_transcript(tmp_path, "1回目", timestamp_format="%Y")
+        second = save_transcript(tmp_path, "2回目", timestamp_format="%Y")
+        third = save_transcript(tmp_path, "3回目", timestamp_format="%Y")
+
+        assert second.name == first.name.replace("_raw.md", "_raw-1.md")
+        assert third.name == first.name.replace("_raw.md", "_raw-2.md")
+        assert "1回目" in first.read_text(encoding="utf-8")
+        assert "2回目" in second.read_text(encoding="utf-8")
+
     def test_writes_transcription_body(self, tmp_path: Path):
         saved = save_transcript(tmp_path, "これは文字起こし結果です", format_mode="rule")
         content = saved.read_text(encoding="utf-8")
@@ -35,6 +71,40 @@ class TestSaveTranscript:
         assert "- raw" in content
         assert "created:" in content
 
+    def test_extra_tags_follow_default_tags_without_duplicates(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", tags=["#meeting", "raw", "project-x", "会議"])
+
+        content = saved.read_text(encoding="utf-8")
+        assert (
+            'tags:\n  - recording\n  - raw\n  - meeting\n  - project-x\n  - "会議"\n---\n'
+            in content
+        )
+
+    def test_frontmatter_contains_metadata(self, tmp_path: Path):
+        metadata = {"duration": 12.3, "model": "large-v3", "language": "ja", "sample_rate": 16000}
+        saved = save_transcript(tmp_path, "本文", format_mode="none", metadata=metadata)
+        frontmatter = saved.read_text(encoding="utf-8").split("---\n")[1]
+        assert "duration: 12.3\n" in frontmatter
+        assert "model: large-v3\n" in frontmatter
+        assert "language: ja\n" in frontmatter
+        assert "sample_rate: 16000\n" in frontmatter
+
+    def test_quotes_extra_values_with_special_characters(self, tmp_path: Path):
+        metadata = {"project": "VoiceNote", "location": "会議室: 3F #2", "my key": "x"}
+        saved = save_transcript(tmp_path, "本文", format_mode="none", metadata=metadata)
+        frontmatter = saved.read_text(encoding="utf-8").split("---\n")[1]
+        assert "project: VoiceNote\n" in frontmatter
+        assert 'location: "会議室: 3F #2"\n' in frontmatter
+        assert '"my key": x\n' in frontmatter
+
+    def test_extra_metadata_cannot_override_builtin_fields(self, tmp_path: Path):
+        metadata = {"type": "meeting", "created": "yesterday"}
+        saved = save_transcript(tmp_path, "本文", format_mode="none", metadata=metadata)
+        frontmatter = saved.read_text(encoding="utf-8").split("---\n")[1]
+        assert "type: transcription\n" in frontmatter
+        assert "meeting" not in frontmatter
+        assert "yesterday" not in frontmatter
+
     def test_returns_path_pointing_to_existing_file(self, tmp_path: Path):
         saved = save_transcript(tmp_path, "本文", format_mode="none")
         assert saved.exists()
@@ -44,9 +114,392 @@ class TestSaveTranscript:
         saved = save_transcript(str(tmp_path), "本文", format_mode="none")
         assert saved.exists()
 
-    def test_raises_runtime_error_when_write_blocked(self, tmp_path: Path):
+    def test_raises_note_write_error_when_write_blocked(self, tmp_path: Path):
         # ファイルをディレクトリとして指定した場合は mkdir が失敗する
         blocker = tmp_path / "blocker"
         blocker.write_text("")
-        with pytest.raises((RuntimeError, FileExistsError, NotADirectoryError)):
+        with pytest.raises(NoteWriteError):
             save_transcript(blocker / "child", "本文")
+
+
+class TestSaveTranscriptRecordedAt:
+    def test_created_is_recording_start_and_saved_is_save_time(self, tmp_path: Path):
+        recorded_at = datetime(2026, 1, 16, 9, 0, 0)
+        saved = save_transcript(tmp_path, "本文", format_mode="none", recorded_at=recorded_at)
+        frontmatter = saved.read_text(encoding="utf-8").split("---\n")[1]
+        assert "created: 2026-01-16T09:00:00\n" in frontmatter
+        saved_at = re.search(r"^saved: (.+)$", frontmatter, re.MULTILINE)
+        assert saved_at is not None
+        assert datetime.fromisoformat(saved_at.group(1)) > recorded_at
+
+    def test_no_saved_field_without_recording_start(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", format_mode="none")
+        assert "saved:" not in saved.read_text(encoding="utf-8")
+
+    def test_file_name_and_date_folder_follow_recording_start(self, tmp_path: Path):
+        recorded_at = datetime(2025, 12, 31, 23, 59, 30)
+        saved = save_transcript(
+            tmp_path / "{year}-{month}", "本文", format_mode="none", recorded_at=recorded_at
+        )
+        assert saved == tmp_path / "2025-12" / "2025-12-31_235930_raw.md"
+
+    def test_json_includes_both_times(self, tmp_path: Path):
+        recorded_at = datetime(2026, 1, 16, 9, 0, 0)
+        saved = save_transcript(tmp_path, "本文", output_format="json", recorded_at=recorded_at)
+        data = json.loads(saved.read_text(encoding="utf-8"))
+        assert data["created"] == "2026-01-16T09:00:00"
+        assert "saved" in data
+
+
+class TestSaveTranscriptName:
+    def test_overwrites_file_of_same_name(self, tmp_path: Path):
+        first = save_transcript(tmp_path, "前回", name="memo")
+        second = save_transcript(tmp_path, "今回", name="memo")
+
+        assert first == second == tmp_path / "memo_raw.md"
+        assert "今回" in second.read_text(encoding="utf-8")
+        assert "前回" not in second.read_text(encoding="utf-8")
+
+    def test_kind_and_format_follow_name(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", output_format="txt", kind="clean", name="memo")
+
+        assert saved == tmp_path / "memo_clean.txt"
+
+    @pytest.mark.parametrize("name", ["", "../memo", "sub/memo"])
+    def test_rejects_names_with_folders(self, tmp_path: Path, name: str):
+        with pytest.raises(ValueError, match="ノートの名前"):
+            save_transcript(tmp_path, "本文", name=name)
+
+
+class TestNoteHeaderFooter:
+    def test_wraps_body_after_frontmatter(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", header="# 録音メモ\n", footer="- [ ] 見直した")
+        body = saved.read_text(encoding="utf-8").split("---\n", 2)[2]
+        assert body == "# 録音メモ\n\n本文\n\n- [ ] 見直した\n"
+
+    def test_txt_ignores_header_and_footer(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", output_format="txt", header="# 見出し")
+        assert saved.read_text(encoding="utf-8") == "本文\n"
+
+    def test_expands_date_and_timestamp_placeholders(self):
+        now = datetime(2026, 3, 7, 12, 34, 56)
+        expanded = expand_note_template("{year}-{month}-{day} ({weekday}) {timestamp} {title}", now)
+        assert expanded == "2026-03-07 (Sat) 2026-03-07_123456 {title}"
+
+
+class TestEscapeMarkdownBody:
+    @pytest.mark.parametrize(
+        ("line", "expected"),
+        [
+            ("---", r"\---"),
+            ("===", r"\==="),
+            ("***", r"\***"),
+            ("```python", r"\```python"),
+            ("~~~", r"\~~~"),
+            ("  ---", r"  \---"),
+        ],
+    )
+    def test_escapes_structure_breaking_lines(self, line: str, expected: str):
+        assert escape_markdown_body(f"前の文\n{line}\n後の文") == f"前の文\n{expected}\n後の文"
+
+    def test_leaves_ordinary_text_untouched(self):
+        text = "A --- B\n- 箇条書き\n`code` と ** 強調"
+        assert escape_markdown_body(text) == text
+
+
+class TestEscapeMarkdownText:
+    def test_escapes_tags_and_links(self):
+        assert escape_markdown_text("#hashtag と [bracket]") == r"\#hashtag と \[bracket\]"
+
+    def test_escapes_emphasis_and_code(self):
+        assert escape_markdown_text("`x` と **強調**") == r"\`x\` と \*\*強調\*\*"
+
+    def test_escapes_list_markers_only_at_line_start(self):
+        text = "- 項目\n1. 番号\n1.5 と a - b"
+        assert escape_markdown_text(text) == "\\- 項目\n1\\. 番号\n1.5 と a - b"
+
+    def test_includes_structure_breaking_lines(self):
+        assert escape_markdown_text("前\n---\n後") == "前\n\\---\n後"
+
+    def test_keep_bullets_leaves_leading_bullet_markers(self):
+        text = "- #タグ\n- [リンク]"
+        assert escape_markdown_text(text, keep_bullets=True) == "- \\#タグ\n- \\[リンク\\]"
+
+    def test_leaves_plain_text_untouched(self):
+        assert escape_markdown_text("今日は、晴れです。") == "今日は、晴れです。"
+
+
+class TestTimestampFormat:
+    def test_custom_format_is_used_for_filename(self, tmp_path: Path):
+        saved = save_transcript(tmp_path, "本文", timestamp_format="%Y%m%dT%H%M%S")
+        assert re.match(r"^\d{8}T\d{6}_raw\.md$", saved.name)
+
+    def test_check_accepts_default(self):
+        check_timestamp_format("%Y-%m-%d_%H%M%S")
+
+    def test_check_rejects_path_separator(self):
+        with pytest.raises(ValueError, match="'/'"):
+            check_timestamp_format("%Y/%m")
+
+
+class TestSaveFolderUnavailable:
+    def test_raises_clear_error_when_parent_is_missing(self, tmp_path: Path):
+        # 外部ドライブが外れた状態を、存在しない親フォルダで再現する
+        target = tmp_path / "unmounted-drive" / "notes"
+
+        with pytest.raises(SaveFolderUnavailableError, match="--config"):
+            save_transcript(target, "本文")
+
+        assert not (tmp_path / "unmounted-drive").exists()
+
+    def test_checks_only_before_placeholders(self, tmp_path: Path):
+        saved = save_transcript(tmp_path / "notes" / "{year}" / "{month}", "本文")
+        assert saved.exists()
+
+    def test_batch_index_checks_too(self, tmp_path: Path):
+        with pytest.raises(SaveFolderUnavailableError):
+            save_batch_index(tmp_path / "missing" / "notes", [])
+
+
+class TestSaveTranscriptOutputFormat:
+    def test_txt_contains_only_body(self, tmp_path: Path):
+        saved = save_transcript(
+            tmp_path, "本文だけ", metadata={"model": "small"}, output_format="txt"
+        )
+        assert saved.suffix == ".txt"
+        assert saved.read_text(encoding="utf-8") == "本文だけ\n"
+
+    def test_json_contains_metadata_and_segments(self, tmp_path: Path):
+        segments = [{"start": 0.0, "end": 1.5, "text": "こんにちは"}]
+        saved = save_transcript(
+            tmp_path,
+            "こんにちは",
+            format_mode="rule",
+            metadata={"model": "small", "duration": 1.5},
+            output_format="json",
+            segments=segments,
+        )
+        assert saved.suffix == ".json"
+        data = json.loads(saved.read_text(encoding="utf-8"))
+        assert data["text"] == "こんにちは"
+        assert data["format_mode"] == "rule"
+        assert data["model"] == "small"
+        assert data["segments"] == segments
+
+    def test_rejects_unknown_format(self, tmp_path: Path):
+        with pytest.raises(ValueError, match="保存形式"):
+            save_transcript(tmp_path, "本文", output_format="docx")
+        assert list(tmp_path.iterdir()) == []
+
+
+class TestYamlValue:
+    def test_numbers_are_unquoted(self):
+        assert _yaml_value(16000) == "16000"
+        assert _yaml_value(12.5) == "12.5"
+
+    def test_plain_identifier_is_unquoted(self):
+        assert _yaml_value("large-v3") == "large-v3"
+
+    def test_reserved_word_is_quoted(self):
+        assert _yaml_value("no") == '"no"'
+
+    def test_special_characters_are_quoted(self):
+        assert _yaml_value("a: b") == '"a: b"'
+        assert _yaml_value("東京") == '"東京"'
+
+
+class TestExpandSaveFolder:
+    def test_returns_path_unchanged_without_placeholders(self):
+        assert expand_save_folder("/vault/recordings") == Path("/vault/recordings")
+
+    def test_expands_year_month_day(self):
+        now = datetime(2026, 3, 7, 12, 0, 0)
+        expanded = expand_save_folder("/vault/recordings/{year}/{month}/{day}", now)
+        assert expanded == Path("/vault/recordings/2026/03/07")
+
+    def test_expands_weekday(self):
+        now = datetime(2026, 3, 7, 12, 0, 0)  # 土曜日
+        assert expand_save_folder("/vault/{weekday}", now) == Path("/vault/Sat")
+
+    def test_expands_placeholder_inside_component(self):
+        now = datetime(2026, 3, 7, 12, 0, 0)
+        assert expand_save_folder("/vault/{year}-{month}", now) == Path("/vault/2026-03")
+
+    def test_leaves_unknown_braces_untouched(self):
+        now = datetime(2026, 3, 7, 12, 0, 0)
+        expanded = expand_save_folder("/vault/{misc}/{year}", now)
+        assert expanded == Path("/vault/{misc}/2026")
+
+    def test_rejects_parent_traversal_after_placeholder(self):
+        with pytest.raises(ValueError):
+            expand_save_folder("/vault/recordings/{year}/../../..")
+
+
+class TestTemplateRoot:
+    def test_returns_prefix_before_first_placeholder(self):
+        assert template_root("/vault/recordings/{year}/{month}") == Path("/vault/recordings")
+
+    def test_returns_path_itself_without_placeholders(self):
+        assert template_root("/vault/recordings") == Path("/vault/recordings")
+
+
+class TestSaveTranscriptWithPlaceholders:
+    def test_saves_into_expanded_dated_folder(self, tmp_path: Path):
+        saved = save_transcript(tmp_path / "{year}" / "{month}", "本文")
+        now = datetime.now()
+        assert saved.parent == tmp_path / f"{now.year:04d}" / f"{now.month:02d}"
+        assert saved.exists()
+
+
+class TestSaveBatchIndex:
+    def test_lists_notes_as_wikilinks_with_duration_and_segments(self, tmp_path: Path):
+        entries = [
+            BatchIndexEntry(
+                source=Path("/in/meeting.m4a"),
+                note=tmp_path / "2026-01-16_123456_raw.md",
+                duration=725.4,
+                segment_count=184,
+            ),
+        ]
+
+        path = save_batch_index(tmp_path, entries)
+
+        assert re.fullmatch(r"\d{4}-\d{2}-\d{2}_\d{6}_index\.md", path.name)
+        content = path.read_text(encoding="utf-8")
+        assert "type: transcription-index" in content
+        assert "| meeting.m4a | [[2026-01-16_123456_raw]] | 12:05 | 184 |" in content
+
+    def test_links_non_markdown_notes_with_extension(self, tmp_path: Path):
+        entries = [BatchIndexEntry(source=Path("a.wav"), note=tmp_path / "x_raw.txt")]
+
+        content = save_batch_index(tmp_path, entries).read_text(encoding="utf-8")
+
+        assert "[[x_raw.txt]]" in content
+
+    def test_records_failures_and_escapes_pipes(self, tmp_path: Path):
+        entries = [
+            BatchIndexEntry(source=Path("a|b.wav"), note=None, error="decode\nfailed"),
+        ]
+
+        path = save_batch_index(tmp_path, entries, index_name="custom")
+
+        assert path == tmp_path / "custom.md"
+        content = path.read_text(encoding="utf-8")
+        assert "| a\\|b.wav | ⚠ 失敗: decode failed | - | - |" in content
+
+    @pytest.mark.parametrize("index_name", ["../outside", "sub/index", "..", "a\\b", ""])
+    def test_rejects_index_name_outside_save_folder(self, tmp_path: Path, index_name: str):
+        with pytest.raises(ValueError, match="batch_index_name"):
+            save_batch_index(tmp_path / "notes", [], index_name=index_name)
+
+        assert not any(tmp_path.rglob("*.md"))
+
+
+class TestAppendTranscript:
+    def test_creates_note_with_heading_when_missing(self, tmp_path: Path):
+        path = append_transcript(
+            tmp_path, "logs/running.md", "最初のメモ", now=datetime(2026, 1, 16, 9, 5)
+        )
+
+        assert path == tmp_path / "logs" / "running.md"
+        assert path.read_text(encoding="utf-8") == "## 2026-01-16 09:05\n\n最初のメモ\n"
+
+    def test_appends_after_existing_content(self, tmp_path: Path):
+        note = tmp_path / "running.md"
+        note.write_text("# ログ", encoding="utf-8")
+
+        append_transcript(tmp_path, "running.md", "追記", now=datetime(2026, 1, 16, 9, 5))
+
+        assert note.read_text(encoding="utf-8") == "# ログ\n\n## 2026-01-16 09:05\n\n追記\n"
+
+    def test_resolves_relative_to_folder_before_placeholders(self, tmp_path: Path):
+        path = append_transcript(tmp_path / "{year}", "running.md", "x")
+
+        assert path == tmp_path / "running.md"
+
+    @pytest.mark.parametrize("note_path", ["../outside.md", "/abs/running.md"])
+    def test_rejects_paths_outside_save_folder(self, tmp_path: Path, note_path: str):
+        with pytest.raises(ValueError, match="相対パス"):
+            append_transcript(tmp_path, note_path, "x")
+
+
+class TestWrapCallout:
+    def test_prefixes_every_line_and_keeps_blank_lines(self):
+        body = wrap_callout("一行目\n二行目\n\n次の段落")
+
+        assert body == "> [!note]\n> 一行目\n> 二行目\n>\n> 次の段落"
+
+    def test_folded_callout_with_custom_type(self):
+        assert wrap_callout("本文", "quote", folded=True) == "> [!quote]-\n> 本文"
+
+    @pytest.mark.parametrize("callout_type", ["", "note]", "メモ", "a b"])
+    def test_check_rejects_unusable_type(self, callout_type: str):
+        with pytest.raises(ValueError, match="callout_type"):
+            check_callout_type(callout_type)
+
+
+class TestNormalizeTags:
+    def test_strips_hash_and_dedupes(self):
+        assert normalize_tags(["#meeting", " meeting ", "project-x"]) == ["meeting", "project-x"]
+
+    @pytest.mark.parametrize("tag", ["", "#", "two words"])
+    def test_rejects_empty_or_spaced_tags(self, tag: str):
+        with pytest.raises(ValueError, match="タグ"):
+            normalize_tags([tag])
+
+
+class TestRenderChapters:
+    def test_adds_timestamp_heading_to_each_chapter(self):
+        body = render_chapters([(0.0, "はじめ"), (125.4, "次の話題")])
+
+        assert body == "## [00:00]\n\nはじめ\n\n## [02:05]\n\n次の話題"
+
+
+class TestAudioLinks:
+    def test_default_template_embeds_audio_at_start_seconds(self):
+        link = format_audio_link(DEFAULT_AUDIO_LINK_TEMPLATE, "rec.wav", 65.7)
+
+        assert link == "![[rec.wav#t=65]]"
+
+    def test_time_placeholder_is_minutes_and_seconds(self):
+        assert format_audio_link("[[{file}#t={time}|{time}]]", "rec.wav", 65.0) == (
+            "[[rec.wav#t=01:05|01:05]]"
+        )
+
+    def test_segments_start_with_link_instead_of_timestamp(self):
+        body = render_timestamped_segments(
+            [(0.0, "はじめ"), (90.0, "つづき")], lambda start: f"<{start:g}>"
+        )
+
+        assert body == "<0> はじめ\n<90> つづき"
+
+    @pytest.mark.parametrize(
+        "template", ["![[rec.wav#t={seconds}]]", "{file}#t={minute}", "{file}#t={"]
+    )
+    def test_check_rejects_unusable_template(self, template: str):
+        with pytest.raises(ValueError, match="audio_link_template"):
+            check_audio_link_template(template)
+
+
+class TestPartialNote:
+    def test_file_is_created_on_first_append(self, tmp_path: Path):
+        note = PartialNote(tmp_path / "{year}", now=datetime(2026, 1, 16, 12, 34, 56))
+
+        assert note.path == tmp_path / "2026" / "2026-01-16_123456_raw.partial.md"
+        assert not note.path.exists()
+
+        note.append(5.0, "最初")
+        note.append(65.2, "次")
+
+        content = note.path.read_text(encoding="utf-8")
+        assert "type: transcription-partial" in content
+        assert content.endswith("[00:05] 最初\n[01:05] 次\n")
+
+    def test_discard_removes_file(self, tmp_path: Path):
+        note = PartialNote(tmp_path)
+        note.append(0.0, "x")
+
+        note.discard()
+        note.discard()
+
+        assert not note.path.exists()
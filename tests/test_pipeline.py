@@ -1,6 +1,12 @@
 """pipeline モジュールのユニットテスト (純粋ロジック部分のみ)。"""
 
+import json
+import os
 import re
+import shlex
+import sys
+import types
+from datetime import datetime
 from pathlib import Path
 
 import numpy as np
@@ -9,8 +15,26 @@ from scipy.io import wavfile
 
 import pipeline
 from config import InvalidConfigError, VoiceNoteConfig
-from pipeline import load_or_configure, save_wav
+from pipeline import (
+    EmptyTranscriptionError,
+    SavedTranscript,
+    audio_recorded_at,
+    find_audio_files,
+    format_segments,
+    is_empty_transcription,
+    latest_recording,
+    load_or_configure,
+    print_only_config,
+    save_result,
+    save_wav,
+    transcribe_batch,
+    transcribe_to_note,
+    transcript_summary,
+    trim_recording,
+)
+from note_writer import SaveFolderUnavailableError
 from recorder import SAMPLE_RATE
+from transcriber import PartialTranscriptionError, Segment, TranscriptionResult
 
 
 class TestSaveWav:
@@ -31,6 +55,12 @@ class TestSaveWav:
         rate, _ = wavfile.read(str(saved))
         assert rate == SAMPLE_RATE
 
+    def test_writes_at_given_capture_rate(self, tmp_path: Path):
+        audio = np.zeros(48000, dtype=np.float32)
+        saved = save_wav(audio, tmp_path, sample_rate=48000)
+        rate, _ = wavfile.read(str(saved))
+        assert rate == 48000
+
     def test_converts_float32_to_int16(self, tmp_path: Path):
         # -1.0〜1.0 の float32 が int16 (-32768〜32767) に変換される
         audio = np.array([0.0, 0.5, 1.0, -1.0, -0.5], dtype=np.float32)
@@ -43,6 +73,15 @@ class TestSaveWav:
         assert int(data[2]) == 32767
         assert int(data[3]) == -32767
 
+    def test_round_trips_float32_within_int16_precision(self, tmp_path: Path):
+        t = np.arange(SAMPLE_RATE) / SAMPLE_RATE
+        audio = (0.8 * np.sin(2 * np.pi * 440 * t)).astype(np.float32)
+        saved = save_wav(audio, tmp_path)
+        _, data = wavfile.read(str(saved))
+        restored = data.astype(np.float32) / 32767
+        # int16 への変換は切り捨てなので、誤差は 1LSB 未満
+        assert np.allclose(restored, audio, rtol=0, atol=1 / 16384)
+
     def test_preserves_sample_count(self, tmp_path: Path):
         n_samples = SAMPLE_RATE * 2  # 2秒分
         audio = np.linspace(-1.0, 1.0, n_samples, dtype=np.float32)
@@ -50,11 +89,168 @@ class TestSaveWav:
         _, data = wavfile.read(str(saved))
         assert len(data) == n_samples
 
+    def test_writes_stereo_as_two_channels(self, tmp_path: Path):
+        audio = np.tile(np.array([0.5, -0.5], dtype=np.float32), (SAMPLE_RATE, 1))
+        saved = save_wav(audio, tmp_path)
+        _, data = wavfile.read(str(saved))
+        assert data.shape == (SAMPLE_RATE, 2)
+        assert data[0, 0] > 0 > data[0, 1]
+
     def test_accepts_string_dest_dir(self, tmp_path: Path):
         audio = np.zeros(SAMPLE_RATE, dtype=np.float32)
         saved = save_wav(audio, str(tmp_path))
         assert saved.exists()
 
+    def test_rejects_unknown_format(self, tmp_path: Path):
+        audio = np.zeros(SAMPLE_RATE, dtype=np.float32)
+        with pytest.raises(ValueError, match="mp3"):
+            save_wav(audio, tmp_path, audio_format="mp3")
+
+    def test_compressed_formats_are_encoded_with_pyav(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        encoded: list = []
+
+        def fake_encode(audio_int16, path, sample_rate, codec):
+            encoded.append((audio_int16.dtype, path.suffix, sample_rate, codec))
+            path.write_bytes(b"")
+
+        monkeypatch.setattr(pipeline, "_encode_audio", fake_encode)
+        audio = np.zeros(SAMPLE_RATE, dtype=np.float32)
+
+        flac = save_wav(audio, tmp_path / "flac", sample_rate=48000, audio_format="flac")
+        opus = save_wav(audio, tmp_path / "opus", audio_format="opus")
+
+        assert re.match(r"^\d{4}-\d{2}-\d{2}_\d{6}_recording\.flac$", flac.name)
+        assert opus.suffix == ".ogg"
+        assert encoded == [
+            (np.int16, ".flac", 48000, "flac"),
+            (np.int16, ".ogg", SAMPLE_RATE, "libopus"),
+        ]
+
+    def test_opus_resamples_rates_libopus_does_not_accept(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        encoded: list = []
+
+        def fake_encode(audio_int16, path, sample_rate, codec):
+            encoded.append((len(audio_int16), sample_rate))
+            path.write_bytes(b"")
+
+        monkeypatch.setattr(pipeline, "_encode_audio", fake_encode)
+        audio = np.zeros(44100, dtype=np.float32)
+
+        save_wav(audio, tmp_path, sample_rate=44100, audio_format="opus")
+
+        assert encoded == [(48000, 48000)]
+
+    def test_encode_failure_falls_back_to_wav(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        def broken_encode(audio_int16, path, sample_rate, codec):
+            path.write_bytes(b"partial")
+            raise ValueError("libopus を開けません")
+
+        monkeypatch.setattr(pipeline, "_encode_audio", broken_encode)
+        audio = np.full(SAMPLE_RATE, 0.5, dtype=np.float32)
+        messages: list[str] = []
+
+        saved = save_wav(audio, tmp_path, audio_format="opus", progress_callback=messages.append)
+
+        assert saved.suffix == ".wav"
+        assert list(tmp_path.iterdir()) == [saved]
+        rate, data = wavfile.read(saved)
+        assert rate == SAMPLE_RATE
+        assert len(data) == SAMPLE_RATE
+        assert messages and messages[0].startswith("⚠") and "libopus を開けません" in messages[0]
+
+
+class TestLatestRecording:
+    def test_returns_newest_recording(self, tmp_path: Path):
+        for name in (
+            "2026-01-16_090000_recording.wav",
+            "2026-01-16_101500_recording.wav",
+            "2026-01-17_080000_raw.md",
+            "memo.wav",
+        ):
+            (tmp_path / name).write_bytes(b"")
+
+        assert latest_recording(tmp_path) == tmp_path / "2026-01-16_101500_recording.wav"
+
+    def test_includes_compressed_recordings(self, tmp_path: Path):
+        (tmp_path / "2026-01-16_090000_recording.wav").write_bytes(b"")
+        (tmp_path / "2026-01-16_101500_recording.ogg").write_bytes(b"")
+
+        assert latest_recording(tmp_path) == tmp_path / "2026-01-16_101500_recording.ogg"
+
+    def test_returns_none_without_recordings(self, tmp_path: Path):
+        assert latest_recording(tmp_path) is None
+        assert latest_recording(tmp_path / "missing") is None
+
+
+class _FakeContainer:
+    def __init__(self, metadata: dict):
+        self.metadata = metadata
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        return False
+
+
+class TestAudioRecordedAt:
+    def _fake_av(self, monkeypatch: pytest.MonkeyPatch, metadata: dict):
+        module = types.SimpleNamespace(open=lambda path: _FakeContainer(metadata))
+        monkeypatch.setitem(sys.modules, "av", module)
+
+    def test_prefers_container_creation_time(self, tmp_path: Path, monkeypatch):
+        audio = tmp_path / "memo.m4a"
+        audio.write_bytes(b"")
+        self._fake_av(monkeypatch, {"creation_time": "2026-01-16T00:30:00.000000Z"})
+
+        expected = datetime.fromisoformat("2026-01-16T00:30:00+00:00").astimezone()
+        assert audio_recorded_at(audio) == expected.replace(tzinfo=None)
+
+    def test_falls_back_to_modification_time(self, tmp_path: Path, monkeypatch):
+        audio = tmp_path / "memo.wav"
+        audio.write_bytes(b"")
+        mtime = datetime(2026, 1, 16, 9, 0, 0).timestamp()
+        os.utime(audio, (mtime, mtime))
+        self._fake_av(monkeypatch, {})
+
+        assert audio_recorded_at(audio) == datetime(2026, 1, 16, 9, 0, 0)
+
+    def test_ignores_unparsable_creation_time(self, tmp_path: Path, monkeypatch):
+        audio = tmp_path / "memo.m4a"
+        audio.write_bytes(b"")
+        mtime = datetime(2026, 1, 16, 9, 0, 0).timestamp()
+        os.utime(audio, (mtime, mtime))
+        self._fake_av(monkeypatch, {"creation_time": "昨日"})
+
+        assert audio_recorded_at(audio) == datetime(2026, 1, 16, 9, 0, 0)
+
+    def test_returns_none_for_missing_file(self, tmp_path: Path, monkeypatch):
+        self._fake_av(monkeypatch, {})
+
+        assert audio_recorded_at(tmp_path / "missing.wav") is None
+
+
+class TestTrimRecording:
+    def test_keeps_audio_when_disabled(self):
+        audio = np.zeros(SAMPLE_RATE, dtype=np.float32)
+
+        assert trim_recording(audio, SAMPLE_RATE, VoiceNoteConfig()) is audio
+
+    def test_trims_with_configured_threshold(self):
+        audio = np.zeros(SAMPLE_RATE * 3, dtype=np.float32)
+        audio[SAMPLE_RATE : SAMPLE_RATE * 2] = 0.05
+        config = VoiceNoteConfig(trim_silence=True, trim_threshold=0.1)
+        assert len(trim_recording(audio, SAMPLE_RATE, config)) == len(audio)
+
+        config = VoiceNoteConfig(trim_silence=True, trim_threshold=0.02)
+        assert len(trim_recording(audio, SAMPLE_RATE, config)) < len(audio)
+
 
 class TestLoadOrConfigure:
     def test_propagates_invalid_config_error_without_exiting(
@@ -76,8 +272,782 @@ class TestLoadOrConfigure:
         broken_config_path = blocker / "child" / "config.json"
         monkeypatch.setattr(pipeline, "CONFIG_PATH", broken_config_path)
         monkeypatch.setattr(
-            pipeline, "configure_interactive", lambda: VoiceNoteConfig(save_folder="/tmp/x")
+            pipeline,
+            "configure_interactive",
+            lambda previous: VoiceNoteConfig(save_folder="/tmp/x"),
         )
 
         with pytest.raises(RuntimeError):
             load_or_configure(force_config=True)
+
+    def test_reconfigure_passes_existing_config_as_defaults(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        config_path = tmp_path / "config.json"
+        config_path.write_text('{"whisper_model": "medium"}', encoding="utf-8")
+        monkeypatch.setattr(pipeline, "CONFIG_PATH", config_path)
+        received: list = []
+
+        def fake_configure(previous):
+            received.append(previous)
+            return VoiceNoteConfig(save_folder="/tmp/x")
+
+        monkeypatch.setattr(pipeline, "configure_interactive", fake_configure)
+
+        load_or_configure(force_config=True)
+
+        assert received[0].whisper_model == "medium"
+
+    def test_reconfigure_keeps_settings_that_are_not_prompted(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        config_path = tmp_path / "config.json"
+        config_path.write_text(
+            json.dumps(
+                {
+                    "save_folder": str(tmp_path),
+                    "input_device": "USB マイク",
+                    "presets": {"meeting": {"whisper_model": "medium"}},
+                }
+            ),
+            encoding="utf-8",
+        )
+        monkeypatch.setattr(pipeline, "CONFIG_PATH", config_path)
+        monkeypatch.setattr("config.known_obsidian_vaults", lambda: [])
+
+        def fake_ask(prompt, **kwargs):
+            if "保存先フォルダ" in prompt:
+                return str(tmp_path / "notes")
+            if "マイクテスト" in prompt:
+                return "n"
+            return kwargs.get("default", "")
+
+        monkeypatch.setattr("config.Prompt.ask", fake_ask)
+
+        config = load_or_configure(force_config=True)
+
+        assert config.save_folder == str(tmp_path / "notes")
+        assert config.input_device == "USB マイク"
+        assert config.presets == {"meeting": {"whisper_model": "medium"}}
+        saved = json.loads(config_path.read_text(encoding="utf-8"))
+        assert saved["input_device"] == "USB マイク"
+        assert saved["presets"] == {"meeting": {"whisper_model": "medium"}}
+
+    def test_reads_and_writes_explicit_config_path(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(pipeline, "CONFIG_PATH", tmp_path / "default.json")
+        config_path = tmp_path / "project" / "config.json"
+        monkeypatch.setattr(
+            pipeline,
+            "configure_interactive",
+            lambda previous: VoiceNoteConfig(save_folder="/tmp/x"),
+        )
+
+        load_or_configure(config_path=config_path)
+
+        assert load_or_configure(config_path=config_path).save_folder == "/tmp/x"
+        assert not (tmp_path / "default.json").exists()
+
+
+class TestTranscribeToNoteSaveFolder:
+    def test_fails_before_transcribing_when_save_folder_is_unavailable(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        def fail_transcribe(*args, **kwargs):
+            raise AssertionError("保存先が無いときは文字起こしを始めない")
+
+        monkeypatch.setattr(pipeline, "transcribe", fail_transcribe)
+        config = VoiceNoteConfig(save_folder=str(tmp_path / "unmounted" / "notes"))
+
+        with pytest.raises(SaveFolderUnavailableError):
+            transcribe_to_note(Path("a.wav"), config)
+
+
+class TestTranscribeToNotePartial:
+    def test_saves_partial_note_when_transcription_fails_midway(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            raise PartialTranscriptionError(
+                "文字起こしエラー: boom", (Segment(0.0, 1.0, "途中まで"),)
+            )
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        with pytest.raises(RuntimeError, match="途中までの結果を保存しました"):
+            transcribe_to_note(Path("a.wav"), config)
+
+        [partial] = tmp_path.glob("*.partial.md")
+        assert "[00:00] 途中まで" in partial.read_text(encoding="utf-8")
+
+    def test_incremental_save_writes_segments_and_discards_on_success(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        seen_partials: list[str] = []
+
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            on_segment(Segment(0.0, 1.0, "逐次"))
+            seen_partials.extend(
+                p.read_text(encoding="utf-8") for p in tmp_path.glob("*.partial.md")
+            )
+            return TranscriptionResult(text="逐次", model="tiny")
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", incremental_save=True
+        )
+
+        saved = transcribe_to_note(Path("a.wav"), config)
+
+        assert len(seen_partials) == 1 and "逐次" in seen_partials[0]
+        assert list(tmp_path.glob("*.partial.md")) == []
+        assert saved.path.exists()
+
+    def test_on_segment_receives_segments_as_they_are_decoded(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            on_segment(Segment(0.0, 1.0, "一つ目"))
+            on_segment(Segment(1.0, 2.0, "二つ目"))
+            return TranscriptionResult(text="一つ目 二つ目", model="tiny")
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+        received: list[str] = []
+
+        transcribe_to_note(Path("a.wav"), config, on_segment=lambda s: received.append(s.text))
+
+        assert received == ["一つ目", "二つ目"]
+
+
+class TestIsEmptyTranscription:
+    @pytest.mark.parametrize("text", ["", "   ", "\n\n\t"])
+    def test_whitespace_only_is_empty(self, text: str):
+        assert is_empty_transcription(text, min_chars=1)
+
+    def test_counts_characters_without_whitespace(self):
+        assert is_empty_transcription("あ い", min_chars=3)
+        assert not is_empty_transcription("あ い う", min_chars=3)
+
+
+class TestTranscriptSummary:
+    def test_reports_length_segments_characters_and_words(self):
+        result = TranscriptionResult(
+            text="今日は 晴れ\nHello world",
+            model="small",
+            duration=125.4,
+            segments=(Segment(0.0, 2.0, "今日は 晴れ"), Segment(2.0, 4.0, "Hello world")),
+        )
+
+        assert transcript_summary(result) == "長さ 2分05秒 / 2セグメント / 15文字 / 4語"
+
+    def test_omits_unknown_length_and_segments(self):
+        result = TranscriptionResult(text="a" * 1200, model="whisper-1")
+
+        assert transcript_summary(result) == "1,200文字 / 1語"
+
+
+class TestTranscribeToNoteSkipEmpty:
+    def _fake_transcribe(self, text: str):
+        def fake(audio_file, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text=text, model="tiny")
+
+        return fake
+
+    def test_raises_without_saving_when_empty(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(pipeline, "transcribe", self._fake_transcribe("  \n"))
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        with pytest.raises(EmptyTranscriptionError) as exc_info:
+            transcribe_to_note(Path("a.wav"), config)
+
+        assert exc_info.value.result.text == "  \n"
+        assert list(tmp_path.iterdir()) == []
+
+    def test_saves_empty_note_when_skip_empty_disabled(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(pipeline, "transcribe", self._fake_transcribe(""))
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", skip_empty=False)
+
+        saved = transcribe_to_note(Path("a.wav"), config)
+
+        assert saved.path.exists()
+
+
+class TestTranscribeToNoteOutputFormat:
+    def test_json_output_includes_segments(self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch):
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            segments = (Segment(0.0, 1.0, "一"), Segment(1.0, 2.0, "二"))
+            return TranscriptionResult(text="一 二", model="tiny", segments=segments)
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", output_format="json"
+        )
+
+        saved = transcribe_to_note(Path("a.wav"), config)
+
+        assert saved.path.suffix == ".json"
+        data = json.loads(saved.path.read_text(encoding="utf-8"))
+        assert data["segments"] == [
+            {"start": 0.0, "end": 1.0, "text": "一"},
+            {"start": 1.0, "end": 2.0, "text": "二"},
+        ]
+
+
+class TestTranscribeToNoteAppendTo:
+    def test_appends_to_given_note_instead_of_creating_one(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text="今日のメモ", model="tiny")
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", append_to="running.md"
+        )
+
+        first = transcribe_to_note(Path("a.wav"), config)
+        second = transcribe_to_note(Path("b.wav"), config)
+
+        assert first.path == second.path == tmp_path / "running.md"
+        assert list(tmp_path.iterdir()) == [first.path]
+        assert first.path.read_text(encoding="utf-8").count("今日のメモ") == 2
+
+
+class TestSaveResultChapters:
+    def _result(self) -> TranscriptionResult:
+        segments = (
+            Segment(0.0, 5.0, "はじめ"),
+            Segment(6.0, 10.0, "つづき"),
+            Segment(70.0, 75.0, "次の話題"),
+        )
+        return TranscriptionResult(
+            text="はじめ つづき\n\n次の話題", model="tiny", segments=segments
+        )
+
+    def test_inserts_chapter_headings_at_long_gaps(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", chapter_gap_seconds=30.0
+        )
+
+        saved = save_result(self._result(), config)
+
+        content = saved.path.read_text(encoding="utf-8")
+        assert "## [00:00]\n\nはじめ つづき\n\n## [01:10]\n\n次の話題\n" in content
+        assert saved.text == content.split("---\n", 2)[2].rstrip("\n")
+
+    def test_no_headings_when_disabled(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        saved = save_result(self._result(), config)
+
+        assert "## [" not in saved.path.read_text(encoding="utf-8")
+
+
+class TestSaveResultSplitNotes:
+    def _result(self) -> TranscriptionResult:
+        segments = (
+            Segment(0.0, 3.0, "一つ目の日記"),
+            Segment(125.0, 130.0, "二つ目の日記"),
+        )
+        return TranscriptionResult(
+            text="一つ目の日記\n\n二つ目の日記", model="tiny", segments=segments
+        )
+
+    def test_saves_one_dated_note_per_part(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", split_gap_seconds=60.0
+        )
+        messages: list[str] = []
+
+        saved = save_result(
+            self._result(),
+            config,
+            progress_callback=messages.append,
+            recorded_at=datetime(2026, 1, 16, 12, 0, 0),
+        )
+
+        assert saved.text == "一つ目の日記"
+        assert len(saved.split_paths) == 1
+        second = saved.split_paths[0].read_text(encoding="utf-8")
+        assert "二つ目の日記" in second
+        assert "一つ目の日記" not in second
+        assert "created: 2026-01-16T12:02:05" in second
+        assert "2 個のノート" in messages[0]
+
+    def test_single_note_without_long_silence(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", split_gap_seconds=300.0
+        )
+
+        saved = save_result(self._result(), config)
+
+        assert saved.split_paths == ()
+        assert len(list(tmp_path.iterdir())) == 1
+
+
+class TestSaveResultSegmentSeparator:
+    @pytest.mark.parametrize(
+        ("separator", "text", "expected"),
+        [
+            (
+                "blank",
+                "晴れです。散歩しました。\n\n明日は雨です。",
+                "晴れです。\n散歩しました。\n\n明日は雨です。\n",
+            ),
+            (
+                "bullet",
+                "- 晴れです。散歩しました。\n- 明日は雨です。",
+                "- 晴れです。散歩しました。\n- 明日は雨です。\n",
+            ),
+        ],
+    )
+    def test_rule_format_keeps_separator(
+        self, tmp_path: Path, separator: str, text: str, expected: str
+    ):
+        # 既定の format_mode（rule）で整形しても区切りが崩れない
+        config = VoiceNoteConfig(save_folder=str(tmp_path), segment_separator=separator)
+
+        saved = save_result(TranscriptionResult(text=text, model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8").endswith(f"---\n{expected}")
+
+
+class TestSaveResultNoteName:
+    def test_replaces_note_of_same_name(self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch):
+        texts = iter(["最初の文字起こし", "設定を変えた文字起こし"])
+
+        def fake_transcribe(audio_file, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text=next(texts), model="tiny")
+
+        monkeypatch.setattr(pipeline, "transcribe", fake_transcribe)
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        first = transcribe_to_note(Path("memo.m4a"), config, note_name="memo")
+        second = transcribe_to_note(Path("memo.m4a"), config, note_name="memo")
+
+        assert first.path == second.path == tmp_path / "memo_raw.md"
+        assert list(tmp_path.iterdir()) == [second.path]
+        assert "設定を変えた文字起こし" in second.path.read_text(encoding="utf-8")
+
+    def test_dual_output_replaces_both_notes(self, tmp_path: Path):
+        result = TranscriptionResult(
+            text="今日は", model="tiny", segments=(Segment(0.0, 3.0, "今日は"),)
+        )
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", dual_output=True)
+
+        save_result(result, config, note_name="memo")
+        saved = save_result(result, config, note_name="memo")
+
+        assert saved.path == tmp_path / "memo_clean.md"
+        assert saved.raw_path == tmp_path / "memo_raw.md"
+        assert len(list(tmp_path.iterdir())) == 2
+
+    def test_numbers_split_parts_after_the_first(self, tmp_path: Path):
+        segments = (Segment(0.0, 3.0, "一つ目の日記"), Segment(125.0, 130.0, "二つ目の日記"))
+        result = TranscriptionResult(
+            text="一つ目の日記\n\n二つ目の日記", model="tiny", segments=segments
+        )
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", split_gap_seconds=60.0
+        )
+
+        saved = save_result(result, config, note_name="memo")
+
+        assert saved.path == tmp_path / "memo_raw.md"
+        assert saved.split_paths == (tmp_path / "memo-2_raw.md",)
+
+
+class TestSaveResultDualOutput:
+    def _result(self) -> TranscriptionResult:
+        segments = (
+            Segment(0.0, 3.0, "今日は"),
+            Segment(5.0, 8.0, "晴れです。"),
+            Segment(65.0, 70.0, "以上です。"),
+        )
+        text = "今日は\n\n晴れです。\n\n以上です。"
+        return TranscriptionResult(text=text, model="tiny", segments=segments)
+
+    def test_saves_timestamped_raw_and_clean_notes(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", dual_output=True)
+
+        saved = save_result(self._result(), config)
+
+        assert saved.path.name.endswith("_clean.md")
+        assert saved.raw_path.name == saved.path.name.replace("_clean", "_raw")
+        raw = saved.raw_path.read_text(encoding="utf-8")
+        assert "[00:00] 今日は\n[00:05] 晴れです。\n[01:05] 以上です。\n" in raw
+        assert saved.text == "今日は晴れです。\n\n以上です。"
+
+    def test_tags_follow_note_kind(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", dual_output=True)
+
+        saved = save_result(self._result(), config)
+
+        assert "tags:\n  - recording\n  - raw\n---" in saved.raw_path.read_text(encoding="utf-8")
+        clean = saved.path.read_text(encoding="utf-8")
+        assert "tags:\n  - recording\n  - clean\n---" in clean
+
+    def test_single_note_when_disabled(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        saved = save_result(self._result(), config)
+
+        assert saved.raw_path is None
+        assert [p.name for p in tmp_path.iterdir()] == [saved.path.name]
+
+
+class TestSaveResultAudioLinks:
+    def _result(self) -> TranscriptionResult:
+        segments = (Segment(0.0, 3.0, "今日は"), Segment(90.5, 93.0, "以上です。"))
+        return TranscriptionResult(text="今日は\n\n以上です。", model="tiny", segments=segments)
+
+    def test_raw_note_links_each_segment_to_audio(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", dual_output=True, audio_links=True
+        )
+
+        saved = save_result(self._result(), config, audio_file=tmp_path / "rec.wav")
+
+        raw = saved.raw_path.read_text(encoding="utf-8")
+        assert "![[rec.wav#t=0]] 今日は\n![[rec.wav#t=90]] 以上です。\n" in raw
+        assert "[[" not in saved.text
+
+    def test_single_note_body_is_linked_segments_without_formatting(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="rule",
+            audio_links=True,
+            audio_link_template="[[{file}#t={seconds}|{time}]]",
+        )
+
+        saved = save_result(self._result(), config, audio_file=tmp_path / "rec.wav")
+
+        assert saved.text == "[[rec.wav#t=0|00:00]] 今日は\n[[rec.wav#t=90|01:30]] 以上です。"
+        assert "format_mode: none\n" in saved.path.read_text(encoding="utf-8")
+
+    def test_no_links_without_audio_file(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", audio_links=True)
+
+        saved = save_result(self._result(), config)
+
+        assert saved.text == "今日は\n\n以上です。"
+
+
+class TestSaveResultFrontmatterExtra:
+    def test_extra_fields_are_written_but_recording_metadata_wins(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="none",
+            frontmatter_extra={"project": "VoiceNote", "model": "上書き"},
+        )
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        frontmatter = saved.path.read_text(encoding="utf-8").split("---\n")[1]
+        assert "project: VoiceNote\n" in frontmatter
+        assert "model: tiny\n" in frontmatter
+
+
+class TestSaveResultNoteTemplate:
+    def test_header_and_footer_surround_body_and_summary(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(pipeline, "summarize_transcription", lambda text, config, cb: "要点")
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="none",
+            note_header="# {year}年の録音",
+            note_footer="- [ ] 見直した",
+        )
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        body = saved.path.read_text(encoding="utf-8").split("---\n", 2)[2]
+        assert re.fullmatch(
+            r"# \d{4}年の録音\n\n本文\n\n## 要約\n\n要点\n\n- \[ \] 見直した\n", body
+        )
+
+
+class TestSaveResultCallout:
+    def test_wraps_body_but_not_frontmatter_or_summary(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(pipeline, "summarize_transcription", lambda text, config, cb: "要点")
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="none",
+            wrap_callout=True,
+            callout_type="quote",
+            callout_folded=True,
+        )
+
+        saved = save_result(TranscriptionResult(text="一段落目\n\n二段落目", model="tiny"), config)
+
+        content = saved.path.read_text(encoding="utf-8")
+        assert content.startswith("---\n")
+        body = content.split("---\n", 2)[2]
+        assert body == "> [!quote]-\n> 一段落目\n>\n> 二段落目\n\n## 要約\n\n要点\n"
+
+    def test_plain_text_is_not_wrapped(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", output_format="txt", wrap_callout=True
+        )
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8") == "本文\n"
+
+
+class TestSaveResultPostSaveCommand:
+    def _command(self, script: str) -> str:
+        return f"{shlex.quote(sys.executable)} -c {shlex.quote(script)}"
+
+    def _config(self, tmp_path: Path, script: str, **kwargs) -> VoiceNoteConfig:
+        return VoiceNoteConfig(
+            save_folder=str(tmp_path / "notes"),
+            format_mode="none",
+            post_save_command=self._command(script),
+            **kwargs,
+        )
+
+    def test_runs_command_with_note_path(self, tmp_path: Path):
+        log = tmp_path / "log.txt"
+        script = (
+            "import os, sys, pathlib; "
+            f"pathlib.Path({str(log)!r}).write_text("
+            "sys.argv[1] + '|' + os.environ['VOICENOTE_NOTE_PATH'])"
+        )
+        config = self._config(tmp_path, script)
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        note = str(saved.path.absolute())
+        assert log.read_text() == f"{note}|{note}"
+
+    def test_failure_is_reported_without_failing(self, tmp_path: Path):
+        messages: list[str] = []
+        config = self._config(tmp_path, "import sys; sys.exit('同期に失敗')")
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config, messages.append)
+
+        assert saved.path.exists()
+        assert any(m.startswith("⚠") and "同期に失敗" in m for m in messages)
+
+    def test_failure_raises_when_required(self, tmp_path: Path):
+        config = self._config(tmp_path, "import sys; sys.exit(3)", post_save_required=True)
+
+        with pytest.raises(RuntimeError, match="終了コード 3"):
+            save_result(TranscriptionResult(text="本文", model="tiny"), config)
+        assert list((tmp_path / "notes").iterdir())
+
+    def test_missing_command_is_reported(self, tmp_path: Path):
+        messages: list[str] = []
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="none",
+            post_save_command="voicenote-no-such-command",
+        )
+
+        save_result(TranscriptionResult(text="本文", model="tiny"), config, messages.append)
+
+        assert any("実行できません" in m for m in messages)
+
+
+class TestSaveResultMarkdownSafety:
+    def test_delimiters_and_fences_in_transcription_cannot_break_note(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+        text = "---\ntitle: 偽物\n---\n```\n最後まで"
+
+        saved = save_result(TranscriptionResult(text=text, model="tiny"), config)
+
+        content = saved.path.read_text(encoding="utf-8")
+        lines = content.splitlines()
+        # frontmatter の区切りはノート側で書いた2行だけ
+        assert lines.count("---") == 2
+        assert "model: tiny" in content.split("---\n")[1]
+        assert not any(line.startswith("```") for line in lines)
+        assert content.endswith("\\---\ntitle: 偽物\n\\---\n\\```\n最後まで\n")
+
+    def test_txt_output_is_not_escaped(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", output_format="txt")
+
+        saved = save_result(TranscriptionResult(text="---", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8") == "---\n"
+
+    def test_escape_markdown_escapes_tags_and_brackets(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path), format_mode="none", escape_markdown=True
+        )
+
+        saved = save_result(TranscriptionResult(text="#hashtag [bracket]", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8").endswith("\\#hashtag \\[bracket\\]\n")
+
+    def test_special_characters_are_kept_by_default(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        saved = save_result(TranscriptionResult(text="#hashtag [bracket]", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8").endswith("#hashtag [bracket]\n")
+
+
+class TestSaveResultPostprocess:
+    def test_postprocessed_text_is_formatted_and_saved(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(
+            pipeline, "postprocess_transcription", lambda text, config, cb: f"{text}。"
+        )
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        assert saved.text == "本文。"
+        assert saved.path.read_text(encoding="utf-8").endswith("---\n本文。\n")
+
+
+class TestSaveResultExtraDestinations:
+    def test_saves_each_destination_in_its_format_formatting_once(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        calls: list[str] = []
+
+        def record_format(text, config, cb):
+            calls.append(text)
+            return text
+
+        monkeypatch.setattr(pipeline, "_format", record_format)
+        archive = tmp_path / "archive"
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path / "vault"),
+            extra_destinations=[{"save_folder": str(archive), "output_format": "txt"}],
+        )
+
+        saved = save_result(TranscriptionResult(text="---", model="tiny"), config)
+
+        assert calls == ["---"]
+        assert saved.path.suffix == ".md"
+        assert saved.path.read_text(encoding="utf-8").endswith("\n\\---\n")
+        assert [p.parent for p in saved.extra_paths] == [archive]
+        # Markdown 用のエスケープは txt には入らない
+        assert saved.extra_paths[0].read_text(encoding="utf-8") == "---\n"
+
+    def test_unavailable_destination_is_skipped_with_warning(self, tmp_path: Path):
+        config = VoiceNoteConfig(
+            save_folder=str(tmp_path),
+            format_mode="none",
+            extra_destinations=[{"save_folder": str(tmp_path / "missing" / "archive")}],
+        )
+        messages: list[str] = []
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config, messages.append)
+
+        assert saved.path.exists()
+        assert saved.extra_paths == ()
+        assert any(m.startswith("⚠") and "missing" in m for m in messages)
+
+
+class TestSaveResultSummary:
+    def test_appends_summary_section(self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(pipeline, "summarize_transcription", lambda text, config, cb: "要点")
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none")
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8").endswith("本文\n\n## 要約\n\n要点\n")
+
+    def test_skips_summary_for_plain_text(self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(pipeline, "summarize_transcription", lambda text, config, cb: "要点")
+        config = VoiceNoteConfig(save_folder=str(tmp_path), format_mode="none", output_format="txt")
+
+        saved = save_result(TranscriptionResult(text="本文", model="tiny"), config)
+
+        assert saved.path.read_text(encoding="utf-8") == "本文\n"
+
+
+class TestFindAudioFiles:
+    def test_returns_audio_files_sorted_by_name(self, tmp_path: Path):
+        for name in ["b.mp3", "a.wav", "c.M4A", "notes.txt"]:
+            (tmp_path / name).write_bytes(b"")
+        (tmp_path / "sub.wav").mkdir()
+
+        found = find_audio_files(tmp_path)
+
+        assert [p.name for p in found] == ["a.wav", "b.mp3", "c.M4A"]
+
+
+class TestTranscribeBatch:
+    def _fake_transcribe_to_note(self, save_folder: Path):
+        def fake(audio_file, config, progress_callback=None):
+            if audio_file.name.startswith("bad"):
+                raise RuntimeError("壊れたファイル")
+            note = save_folder / f"{audio_file.stem}_raw.md"
+            note.write_text("", encoding="utf-8")
+            result = TranscriptionResult(
+                text="x",
+                model="tiny",
+                duration=65.0,
+                segments=(Segment(0.0, 1.0, "x"), Segment(1.0, 2.0, "y")),
+            )
+            return SavedTranscript(path=note, result=result)
+
+        return fake
+
+    def test_writes_index_and_continues_after_failure(
+        self, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+    ):
+        audio_dir = tmp_path / "audio"
+        audio_dir.mkdir()
+        for name in ["a.wav", "bad.wav", "c.wav"]:
+            (audio_dir / name).write_bytes(b"")
+        notes = tmp_path / "notes"
+        notes.mkdir()
+        monkeypatch.setattr(pipeline, "transcribe_to_note", self._fake_transcribe_to_note(notes))
+        config = VoiceNoteConfig(save_folder=str(notes), batch_index_name="batch")
+        messages: list[str] = []
+
+        entries, index_path = transcribe_batch(audio_dir, config, progress_callback=messages.append)
+
+        assert [e.source.name for e in entries] == ["a.wav", "bad.wav", "c.wav"]
+        assert entries[1].note is None
+        assert entries[1].error == "壊れたファイル"
+        assert entries[2].segment_count == 2
+        assert index_path == notes / "batch.md"
+        content = index_path.read_text(encoding="utf-8")
+        assert "[[a_raw]]" in content
+        assert "[[c_raw]]" in content
+        assert any(m.startswith("⚠") and "bad.wav" in m for m in messages)
+
+    def test_raises_when_folder_has_no_audio(self, tmp_path: Path):
+        config = VoiceNoteConfig(save_folder=str(tmp_path))
+
+        with pytest.raises(ValueError):
+            transcribe_batch(tmp_path, config)
+
+
+class TestPrintOnly:
+    def test_config_uses_local_model_deterministically(self, tmp_path: Path):
+        (tmp_path / "model.bin").write_bytes(b"")
+
+        config = print_only_config(tmp_path)
+
+        assert config.whisper_model == str(tmp_path)
+        assert config.transcription_mode == "local"
+        assert config.temperature == 0.0
+
+    def test_rejects_folder_without_model(self, tmp_path: Path):
+        with pytest.raises(ValueError, match="model.bin"):
+            print_only_config(tmp_path)
+
+    def test_formats_one_line_per_segment(self):
+        segments = (Segment(0.0, 1.5, "こんにちは"), Segment(1.5, 3.25, "テスト"))
+
+        assert format_segments(segments) == "[0.00 --> 1.50] こんにちは\n[1.50 --> 3.25] テスト"
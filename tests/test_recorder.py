@@ -0,0 +1,790 @@
+"""recorder モジュールのユニットテスト (実デバイス非依存の部分のみ)。"""
+
+import threading
+
+import numpy as np
+import pytest
+
+import recorder
+from errors import DeviceNotFoundError, DeviceUnavailableError
+from recorder import (
+    SilenceStopDetector,
+    ThreadedRecorder,
+    apply_gain,
+    decode_pcm,
+    is_loopback_name,
+    level_dbfs,
+    list_devices,
+    loopback_hint,
+    negotiate_channels,
+    negotiate_sample_format,
+    negotiate_sample_rate,
+    record_audio,
+    resolve_device_id,
+    run_mic_test,
+    start_recorder,
+    supported_sample_formats,
+    to_float32,
+    trim_silence,
+)
+
+
+_DEVICES = [
+    {"name": "MacBook Air マイク", "max_input_channels": 1, "max_output_channels": 0},
+    {"name": "MacBook Air スピーカー", "max_input_channels": 0, "max_output_channels": 2},
+    {"name": "BlackHole 2ch", "max_input_channels": 2, "max_output_channels": 2},
+]
+
+
+class TestLoopbackDevices:
+    @pytest.mark.parametrize(
+        "name", ["BlackHole 2ch", "Monitor of Built-in Audio", "ステレオ ミキサー (Realtek)"]
+    )
+    def test_detects_loopback_names(self, name: str):
+        assert is_loopback_name(name)
+
+    def test_plain_microphone_is_not_loopback(self):
+        assert not is_loopback_name("USB Microphone")
+
+    def test_list_devices_marks_loopback_and_hides_outputs_by_default(
+        self, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: _DEVICES)
+
+        devices = list_devices()
+
+        assert [d["id"] for d in devices] == [0, 2]
+        assert [d["loopback"] for d in devices] == [False, True]
+
+    def test_list_devices_can_include_outputs(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: _DEVICES)
+
+        devices = list_devices(include_outputs=True)
+
+        assert [d["id"] for d in devices] == [0, 1, 2]
+        assert devices[1]["input_channels"] == 0
+        assert devices[1]["loopback"] is False
+
+    def test_resolves_loopback_input_by_name(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: _DEVICES)
+
+        assert resolve_device_id("blackhole") == 2
+
+    @pytest.mark.parametrize("device", ["スピーカー", "1"])
+    def test_rejects_output_only_device_with_hint(
+        self, monkeypatch: pytest.MonkeyPatch, device: str
+    ):
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: _DEVICES)
+
+        with pytest.raises(ValueError, match="出力専用"):
+            resolve_device_id(device)
+
+    def test_unknown_device_raises_device_not_found(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: _DEVICES)
+
+        with pytest.raises(DeviceNotFoundError, match="見つかりません"):
+            resolve_device_id("存在しないマイク")
+
+    @pytest.mark.parametrize(
+        ("platform", "keyword"),
+        [("darwin", "BlackHole"), ("win32", "ステレオ ミキサー"), ("linux", "Monitor")],
+    )
+    def test_loopback_hint_is_platform_specific(self, platform: str, keyword: str):
+        assert keyword in loopback_hint(platform)
+
+
+class TestNegotiateSampleRate:
+    def test_uses_requested_rate_when_supported(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "check_input_settings", lambda **kwargs: None)
+
+        assert negotiate_sample_rate(None, 16000) == (16000, None)
+
+    def test_falls_back_to_device_default_with_warning(self, monkeypatch: pytest.MonkeyPatch):
+        def reject(**kwargs):
+            raise recorder.sd.PortAudioError("Invalid sample rate")
+
+        monkeypatch.setattr(recorder.sd, "check_input_settings", reject)
+        monkeypatch.setattr(
+            recorder.sd, "query_devices", lambda device, kind: {"default_samplerate": 48000.0}
+        )
+
+        rate, warning = negotiate_sample_rate(2, 16000)
+
+        assert rate == 48000
+        assert "16000Hz" in warning and "48000Hz" in warning
+
+
+class TestSampleFormat:
+    def _install(self, monkeypatch: pytest.MonkeyPatch, supported: set):
+        def check(device=None, channels=1, dtype="float32", samplerate=None):
+            if dtype not in supported:
+                raise recorder.sd.PortAudioError("Sample format not supported")
+
+        monkeypatch.setattr(recorder.sd, "check_input_settings", check)
+
+    def test_lists_supported_formats_in_preference_order(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, {"uint8", "int16"})
+
+        assert supported_sample_formats(None) == ["int16", "uint8"]
+
+    def test_prefers_float32(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, {"float32", "int16"})
+
+        assert negotiate_sample_format(None) == ("float32", None)
+
+    def test_falls_back_to_integer_format_with_warning(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, {"int16"})
+
+        dtype, warning = negotiate_sample_format(3)
+
+        assert dtype == "int16"
+        assert "float32" in warning and "int16" in warning
+
+    def test_keeps_float32_when_nothing_can_be_checked(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, set())
+
+        assert negotiate_sample_format(None) == ("float32", None)
+
+    @pytest.mark.parametrize(
+        ("values", "dtype", "expected"),
+        [
+            ([0, 16384, -32768], "int16", [0.0, 0.5, -1.0]),
+            ([128, 192, 0], "uint8", [0.0, 0.5, -1.0]),
+            ([0, -(2**31)], "int32", [0.0, -1.0]),
+            ([0.25], "float32", [0.25]),
+        ],
+    )
+    def test_converts_to_float32(self, values: list, dtype: str, expected: list):
+        converted = to_float32(np.array(values, dtype=dtype))
+
+        assert converted.dtype == np.float32
+        assert converted.tolist() == pytest.approx(expected)
+
+
+class TestDecodePcm:
+    def test_decodes_s16le(self):
+        raw = np.array([0, 16384, -32768], dtype="<i2").tobytes()
+
+        data = decode_pcm(raw, "s16le")
+
+        assert data.dtype == np.float32
+        assert data.tolist() == [0.0, 0.5, -1.0]
+
+    def test_decodes_f32le_and_clips(self):
+        raw = np.array([0.25, 1.5], dtype="<f4").tobytes()
+
+        assert decode_pcm(raw, "f32le").tolist() == [0.25, 1.0]
+
+    def test_drops_trailing_partial_sample(self):
+        raw = np.array([100, 200], dtype="<i2").tobytes() + b"\x01"
+
+        assert len(decode_pcm(raw, "s16le")) == 2
+
+    def test_rejects_empty_input(self):
+        with pytest.raises(RuntimeError):
+            decode_pcm(b"\x00", "s16le")
+
+    def test_rejects_unknown_format(self):
+        with pytest.raises(ValueError):
+            decode_pcm(b"\x00\x00", "u8")
+
+
+class _FakeStream:
+    """stop() の時点でまだ処理中だったコールバックを、stop() の中で完了させるストリーム。"""
+
+    def __init__(self, callback, **kwargs):
+        self._callback = callback
+
+    def start(self):
+        self._callback(np.full((160, 1), 0.1, dtype=np.float32), 160, None, None)
+
+    def stop(self):
+        self._callback(np.full((160, 1), 0.2, dtype=np.float32), 160, None, None)
+
+    def close(self):
+        pass
+
+
+class TestThreadedRecorderStop:
+    def test_keeps_blocks_delivered_while_stopping(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "InputStream", _FakeStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        rec = ThreadedRecorder()
+
+        rec.start()
+        rec.stop(drain=0)
+
+        data = rec.get_data()
+        assert len(data) == 320
+        assert data[-1] == pytest.approx(0.2)
+
+
+class _ChannelStream:
+    """開いたときのチャンネル数で、左 0.2・右 0.4 の 160 フレームのブロックを届けるストリーム。"""
+
+    opened: list[int] = []
+
+    def __init__(self, callback, channels, **kwargs):
+        self._callback = callback
+        self._channels = channels
+        _ChannelStream.opened.append(channels)
+
+    def _deliver(self):
+        block = np.tile(np.array([0.2, 0.4][: self._channels], dtype=np.float32), (160, 1))
+        self._callback(block, 160, None, None)
+
+    def start(self):
+        self._deliver()
+
+    def stop(self):
+        self._deliver()
+
+    def close(self):
+        pass
+
+
+class TestThreadedRecorderChannels:
+    def _install(self, monkeypatch: pytest.MonkeyPatch, stereo_supported: bool = True):
+        def check(device=None, channels=1, **kwargs):
+            if channels > 1 and not stereo_supported:
+                raise recorder.sd.PortAudioError("Invalid number of channels")
+
+        _ChannelStream.opened = []
+        monkeypatch.setattr(recorder.sd, "InputStream", _ChannelStream)
+        monkeypatch.setattr(recorder.sd, "check_input_settings", check)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+
+    def test_records_mono_by_default(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        rec = ThreadedRecorder()
+
+        rec.start()
+        rec.stop(drain=0)
+
+        data = rec.get_data()
+        assert _ChannelStream.opened == [1]
+        assert data.shape == (320,)
+        assert rec.warnings == []
+
+    def test_stereo_keeps_both_channels_with_frame_count(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        rec = ThreadedRecorder(channels=2)
+
+        rec.start()
+        rec.stop(drain=0)
+
+        data = rec.get_data()
+        assert _ChannelStream.opened == [2]
+        # サンプル数はチャンネルごとに数え、2倍にはならない
+        assert data.shape == (320, 2)
+        assert data[0].tolist() == pytest.approx([0.2, 0.4])
+
+    def test_recent_mixes_stereo_down_to_mono(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        rec = ThreadedRecorder(sample_rate=1000, channels=2)
+
+        rec.start()
+        rec.stop(drain=0)
+
+        recent = rec.recent(0.1)
+        assert recent.shape == (100,)
+        assert recent[0] == pytest.approx(0.3)
+
+    def test_falls_back_to_mono_when_device_lacks_stereo(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, stereo_supported=False)
+        rec = ThreadedRecorder(channels=2)
+
+        rec.start()
+        rec.stop(drain=0)
+
+        assert _ChannelStream.opened == [1]
+        assert rec.get_data().shape == (320,)
+        assert "モノラル" in rec.warnings[0]
+
+    def test_record_audio_trims_stereo_by_frames(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        monkeypatch.setattr(recorder, "STOP_CHECK_SECONDS", 0)
+
+        data, rate = record_audio(
+            sample_rate=1000, stop_event=threading.Event(), max_seconds=0.1, channels=2
+        )
+
+        assert rate == 1000
+        assert data.shape == (100, 2)
+
+
+class TestNegotiateChannels:
+    def test_mono_is_used_without_checking_device(self, monkeypatch: pytest.MonkeyPatch):
+        def fail(**kwargs):
+            raise AssertionError("モノラルは確認しない")
+
+        monkeypatch.setattr(recorder.sd, "check_input_settings", fail)
+
+        assert negotiate_channels(None, 1) == (1, None)
+
+    def test_stereo_when_device_supports_it(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "check_input_settings", lambda **kwargs: None)
+
+        assert negotiate_channels(3, 2) == (2, None)
+
+
+class TestApplyGain:
+    def test_amplifies_by_decibels(self):
+        samples = np.array([0.1, -0.05], dtype=np.float32)
+
+        amplified, clipped = apply_gain(samples, 6.0)
+
+        assert amplified.dtype == np.float32
+        assert amplified.tolist() == pytest.approx([0.1995, -0.0998], abs=1e-3)
+        assert clipped is False
+
+    def test_clamps_and_reports_clipping(self):
+        samples = np.array([0.5, -0.8], dtype=np.float32)
+
+        amplified, clipped = apply_gain(samples, 12.0)
+
+        assert amplified.tolist() == [1.0, -1.0]
+        assert clipped is True
+
+
+class TestThreadedRecorderGain:
+    def _install(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "InputStream", _FakeStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+
+    def test_gain_is_applied_to_recorded_data(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        rec = ThreadedRecorder(gain_db=20.0 * np.log10(2))
+
+        rec.start()
+        rec.stop(drain=0)
+
+        data = rec.get_data()
+        assert data[0] == pytest.approx(0.2)
+        assert data[-1] == pytest.approx(0.4)
+        assert not rec.clipped.is_set()
+
+    def test_clipping_is_flagged_and_warned_once(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch)
+        warnings: list[str] = []
+        stop_event = threading.Event()
+        stop_event.set()
+
+        data, _ = record_audio(on_warning=warnings.append, stop_event=stop_event, gain_db=20.0)
+
+        assert data.max() == 1.0
+        assert [w for w in warnings if "gain_db=20" in w] == [
+            "入力ゲイン（gain_db=20）で音が割れています。gain_db を下げてください"
+        ]
+
+
+class TestThreadedRecorderBufferFrames:
+    def _install(self, monkeypatch: pytest.MonkeyPatch, rejected: set, blocksizes: list):
+        class SizedStream(_FakeStream):
+            def __init__(self, callback, blocksize=0, **kwargs):
+                blocksizes.append(blocksize)
+                if blocksize in rejected:
+                    raise recorder.sd.PortAudioError("Invalid number of frames per buffer")
+                super().__init__(callback)
+
+        monkeypatch.setattr(recorder.sd, "InputStream", SizedStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+
+    def test_uses_device_default_when_unset(self, monkeypatch: pytest.MonkeyPatch):
+        blocksizes: list = []
+        self._install(monkeypatch, set(), blocksizes)
+        rec = ThreadedRecorder()
+
+        rec.start()
+
+        assert blocksizes == [0]
+        assert rec.buffer_warning is None
+
+    def test_opens_with_fixed_buffer_size(self, monkeypatch: pytest.MonkeyPatch):
+        blocksizes: list = []
+        self._install(monkeypatch, set(), blocksizes)
+        rec = ThreadedRecorder(buffer_frames=256)
+
+        rec.start()
+
+        assert blocksizes == [256]
+        assert rec.buffer_warning is None
+
+    def test_falls_back_to_default_with_warning(self, monkeypatch: pytest.MonkeyPatch):
+        blocksizes: list = []
+        self._install(monkeypatch, {64}, blocksizes)
+        rec = ThreadedRecorder(buffer_frames=64)
+
+        rec.start()
+
+        assert blocksizes == [64, 0]
+        assert "64" in rec.buffer_warning
+        assert rec.get_data() is not None
+
+
+class TestThreadedRecorderReconnect:
+    def _install(self, monkeypatch: pytest.MonkeyPatch, failures: int) -> list:
+        """最初のストリームは開け、その後 failures 回は開けないストリームを用意する。"""
+        opened: list = []
+
+        class FlakyStream(_FakeStream):
+            def __init__(self, callback, **kwargs):
+                opened.append(True)
+                if 1 < len(opened) <= 1 + failures:
+                    raise recorder.sd.PortAudioError("Device unavailable")
+                super().__init__(callback)
+
+        monkeypatch.setattr(recorder.sd, "InputStream", FlakyStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder, "RECONNECT_INTERVAL_SECONDS", 0)
+        monkeypatch.setattr(recorder, "_reinitialize_portaudio", lambda: None)
+        return opened
+
+    def test_resumes_into_same_buffer_and_records_gap(self, monkeypatch: pytest.MonkeyPatch):
+        opened = self._install(monkeypatch, failures=2)
+        rec = ThreadedRecorder()
+        rec.start()
+
+        assert rec._reconnect()
+
+        assert len(opened) == 4
+        assert len(rec.interruptions) == 1
+        assert "欠けています" in rec.interruptions[0]
+        assert not rec.lost.is_set()
+        assert len(rec.get_data()) == 320
+
+    def test_gives_up_and_keeps_captured_audio(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, failures=recorder.RECONNECT_ATTEMPTS)
+        rec = ThreadedRecorder()
+        rec.start()
+
+        assert not rec._reconnect()
+        rec.stop(drain=0)
+
+        assert rec.lost.is_set()
+        assert "再接続できませんでした" in rec.interruptions[-1]
+        assert len(rec.get_data()) == 160
+
+    def test_record_audio_returns_audio_when_device_is_lost(
+        self, monkeypatch: pytest.MonkeyPatch
+    ):
+        self._install(monkeypatch, failures=recorder.RECONNECT_ATTEMPTS)
+        monkeypatch.setattr(recorder, "DEVICE_STALL_SECONDS", 0.01)
+        monkeypatch.setattr(recorder, "STOP_CHECK_SECONDS", 0.01)
+        warnings: list[str] = []
+        stops: list[bool] = []
+
+        data, _ = record_audio(
+            on_stop=lambda: stops.append(True),
+            on_warning=warnings.append,
+            stop_event=threading.Event(),
+        )
+
+        assert len(data) == 160
+        assert stops == [True]
+        assert any("再接続できませんでした" in w for w in warnings)
+
+
+    def test_stalled_device_is_found_again_after_reinitializing(
+        self, monkeypatch: pytest.MonkeyPatch
+    ):
+        devices = [
+            {"name": "内蔵マイク", "max_input_channels": 1},
+            {"name": "BlackHole 2ch", "max_input_channels": 2},
+            {"name": "AirPods", "max_input_channels": 1},
+        ]
+        opened: list = []
+        reinitialized: list[bool] = []
+
+        class RecordingStream(_FakeStream):
+            def __init__(self, callback, device=None, **kwargs):
+                opened.append(device)
+                super().__init__(callback)
+
+        def reinitialize():
+            # 戻ってきたデバイスは PortAudio を初期化し直すと番号が付け直される
+            reinitialized.append(True)
+            devices[:] = [d for d in devices if d["name"] != "BlackHole 2ch"]
+
+        stop_event = threading.Event()
+
+        def on_warning(message: str):
+            if "欠けています" in message:
+                stop_event.set()
+
+        monkeypatch.setattr(recorder.sd, "InputStream", RecordingStream)
+        monkeypatch.setattr(
+            recorder.sd, "query_devices", lambda i=None: devices if i is None else devices[i]
+        )
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder, "_reinitialize_portaudio", reinitialize)
+        monkeypatch.setattr(recorder, "DEVICE_STALL_SECONDS", 0.01)
+        monkeypatch.setattr(recorder, "STOP_CHECK_SECONDS", 0.01)
+        monkeypatch.setattr(recorder, "RECONNECT_INTERVAL_SECONDS", 0)
+
+        data, _ = record_audio(device="AirPods", on_warning=on_warning, stop_event=stop_event)
+
+        assert opened[:2] == [2, 1]
+        assert reinitialized
+        assert len(data) >= 320
+
+
+class TestRecordAudioStopEvent:
+    def test_caller_event_stops_recording_without_installing_sigint_handler(
+        self, monkeypatch: pytest.MonkeyPatch
+    ):
+        monkeypatch.setattr(recorder.sd, "InputStream", _FakeStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+
+        def fail_signal(*args):
+            raise AssertionError("SIGINT ハンドラを登録してはいけない")
+
+        monkeypatch.setattr(recorder.signal, "signal", fail_signal)
+        stop_event = threading.Event()
+        stop_event.set()
+
+        data, rate = record_audio(stop_event=stop_event)
+
+        assert rate == recorder.SAMPLE_RATE
+        assert len(data) == 320
+
+    def test_detector_stops_recording_and_notifies(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "InputStream", _FakeStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder, "STOP_CHECK_SECONDS", 0)
+        seen: list[int] = []
+
+        class StopAtOnce:
+            window_seconds = 1.0
+
+            def should_stop(self, recent, sample_rate):
+                seen.append(len(recent))
+                return True
+
+        stops: list[bool] = []
+
+        data, _ = record_audio(
+            on_stop=lambda: stops.append(True),
+            stop_event=threading.Event(),
+            stop_detector=StopAtOnce(),
+        )
+
+        assert seen == [160]
+        assert stops == [True]
+        assert len(data) == 320
+
+    def test_max_seconds_stops_and_trims_to_exact_length(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(recorder.sd, "InputStream", _FakeStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder, "STOP_CHECK_SECONDS", 0)
+        stops: list[bool] = []
+
+        data, rate = record_audio(
+            on_stop=lambda: stops.append(True),
+            sample_rate=1000,
+            stop_event=threading.Event(),
+            max_seconds=0.1,
+        )
+
+        assert rate == 1000
+        assert stops == [True]
+        assert len(data) == 100
+
+
+class TestSilenceStopDetector:
+    def test_does_not_stop_before_any_speech(self):
+        detector = SilenceStopDetector(1.0, threshold=0.01)
+
+        assert not detector.should_stop(np.zeros(1000, dtype=np.float32), 1000)
+
+    def test_stops_after_speech_then_long_silence(self):
+        detector = SilenceStopDetector(1.0, threshold=0.01)
+
+        assert not detector.should_stop(np.full(1000, 0.5, dtype=np.float32), 1000)
+        assert not detector.should_stop(np.zeros(500, dtype=np.float32), 1000)
+        assert detector.should_stop(np.zeros(1000, dtype=np.float32), 1000)
+
+    def test_speech_within_window_keeps_recording(self):
+        detector = SilenceStopDetector(1.0, threshold=0.01)
+        recent = np.zeros(1000, dtype=np.float32)
+        recent[-1] = 0.5
+
+        assert not detector.should_stop(recent, 1000)
+
+
+class TestStartRecorderFallback:
+    _DEVICES = [
+        {"name": "内蔵マイク", "max_input_channels": 1, "max_output_channels": 0},
+        {"name": "BlackHole 2ch", "max_input_channels": 2, "max_output_channels": 2},
+        {"name": "USB マイク", "max_input_channels": 1, "max_output_channels": 0},
+    ]
+
+    def _install(self, monkeypatch: pytest.MonkeyPatch, busy: set, opened: list):
+        class BusyStream:
+            def __init__(self, device=None, **kwargs):
+                opened.append(device)
+                if device in busy:
+                    raise recorder.sd.PortAudioError("Device unavailable")
+
+            def start(self):
+                pass
+
+        monkeypatch.setattr(recorder.sd, "InputStream", BusyStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder.sd, "query_devices", lambda: self._DEVICES)
+        monkeypatch.setattr(recorder, "default_input_name", lambda: "内蔵マイク")
+
+    def test_tries_other_inputs_when_default_is_busy(self, monkeypatch: pytest.MonkeyPatch):
+        opened: list = []
+        self._install(monkeypatch, {None}, opened)
+        warnings: list[str] = []
+
+        rec = start_recorder(None, on_warning=warnings.append)
+
+        # デフォルト自身とループバック入力は試さない
+        assert opened == [None, 2]
+        assert rec.device_id == 2
+        assert "USB マイク" in warnings[0]
+
+    def test_raises_when_no_input_can_be_opened(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, {None, 2}, [])
+
+        with pytest.raises(RuntimeError, match="入力デバイスがありません"):
+            start_recorder(None)
+
+    def test_no_input_is_reported_as_device_unavailable(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, {None, 2}, [])
+
+        with pytest.raises(DeviceUnavailableError):
+            start_recorder(None)
+
+    def test_explicit_device_does_not_fall_back(self, monkeypatch: pytest.MonkeyPatch):
+        opened: list = []
+        self._install(monkeypatch, {0}, opened)
+
+        with pytest.raises(DeviceUnavailableError, match="Device unavailable"):
+            start_recorder(0)
+        assert opened == [0]
+
+
+class _LevelStream:
+    """開始時に振幅 level のブロックを1つだけ届けるストリーム。"""
+
+    level = 0.5
+    opened: list[dict] = []
+
+    def __init__(self, callback, **kwargs):
+        self._callback = callback
+        _LevelStream.opened.append(kwargs)
+
+    def start(self):
+        block = np.full((160, 1), _LevelStream.level, dtype=np.float32)
+        self._callback(block, 160, None, None)
+
+    def stop(self):
+        pass
+
+    def close(self):
+        pass
+
+
+class TestRunMicTest:
+    def _install(self, monkeypatch: pytest.MonkeyPatch, level: float):
+        _LevelStream.level = level
+        monkeypatch.setattr(recorder.sd, "InputStream", _LevelStream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+        monkeypatch.setattr(recorder, "default_input_name", lambda: "内蔵マイク")
+        monkeypatch.setattr(recorder, "LEVEL_METER_INTERVAL_SECONDS", 0.01)
+
+    def test_reports_peak_and_levels_of_captured_audio(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, 0.5)
+        levels: list[float] = []
+
+        result = run_mic_test(seconds=0.05, on_level=levels.append)
+
+        assert result.device_name == "内蔵マイク"
+        assert result.captured
+        assert result.peak == pytest.approx(0.5)
+        assert result.peak_dbfs == pytest.approx(-6.02, abs=0.01)
+        assert result.warning is None
+        assert levels
+        assert max(levels) == pytest.approx(0.5)
+
+    def test_silent_input_is_not_captured(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, 0.0)
+
+        result = run_mic_test(seconds=0.02)
+
+        assert not result.captured
+        assert result.peak == 0.0
+        assert result.warning == result.hint
+
+    def test_warns_when_input_is_too_quiet(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, 0.001)
+
+        result = run_mic_test(seconds=0.02)
+
+        assert result.captured
+        assert "入力レベルが低すぎます" in result.warning
+
+    def test_opens_stream_with_recording_settings(self, monkeypatch: pytest.MonkeyPatch):
+        self._install(monkeypatch, 0.5)
+        _LevelStream.opened = []
+
+        run_mic_test(seconds=0.02, sample_rate=48000, buffer_frames=2048)
+
+        assert _LevelStream.opened[-1]["samplerate"] == 48000
+        assert _LevelStream.opened[-1]["blocksize"] == 2048
+
+
+class TestLevelDbfs:
+    def test_full_scale_is_zero(self):
+        assert level_dbfs(1.0) == pytest.approx(0.0)
+
+    def test_silence_is_clamped_to_floor(self):
+        assert level_dbfs(0.0) == recorder.LEVEL_FLOOR_DBFS
+        assert level_dbfs(1e-9) == recorder.LEVEL_FLOOR_DBFS
+
+
+class TestTrimSilence:
+    def test_removes_leading_and_trailing_silence_keeping_padding(self):
+        rate = 1000
+        audio = np.zeros(5000, dtype=np.float32)
+        audio[2000:3000] = 0.5
+
+        trimmed = trim_silence(audio, rate, threshold=0.01, padding=0.1)
+
+        # 発話 1000 サンプル + 前後 100 サンプルずつの余白
+        assert len(trimmed) == 1200
+        assert trimmed[0] == 0.0
+        assert trimmed[100] == pytest.approx(0.5)
+
+    def test_stereo_trims_frames_where_either_channel_is_loud(self):
+        audio = np.zeros((10, 2), dtype=np.float32)
+        audio[3, 0] = 0.5
+        audio[6, 1] = 0.5
+
+        trimmed = trim_silence(audio, 10, threshold=0.01, padding=0.1)
+
+        assert trimmed.shape == (6, 2)
+
+    def test_padding_is_clamped_to_audio_bounds(self):
+        audio = np.array([0.5, 0.0, 0.0, 0.0], dtype=np.float32)
+
+        assert len(trim_silence(audio, 10, threshold=0.01, padding=0.1)) == 2
+
+    def test_returns_input_unchanged_when_all_below_threshold(self):
+        audio = np.full(100, 0.001, dtype=np.float32)
+
+        assert trim_silence(audio, 1000, threshold=0.01) is audio
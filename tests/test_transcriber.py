@@ -1,20 +1,63 @@
 """transcriber モジュールのユニットテスト。
 
-`transcribe()` のモード分岐（local/openai ディスパッチ）のみを検証する。
+`transcribe()` のモード分岐（local/openai ディスパッチ）と音声前処理を検証する。
 実際の文字起こし処理（faster-whisper・OpenAI API 呼び出し）は monkeypatch で置き換える。
 """
 
+import os
+import sys
+import threading
+import types
+import wave
 from pathlib import Path
 
+import numpy as np
 import pytest
 
-from config import VoiceNoteConfig
-from transcriber import transcribe, transcribe_audio_openai
+from config import InvalidConfigError, VoiceNoteConfig
+from disk_space import InsufficientDiskSpaceError
+from errors import (
+    ModelDownloadError,
+    ModelDownloadTimeoutError,
+    ModelLoadError,
+    TranscriptionError,
+)
+from transcriber import (
+    MIN_RESAMPLE_SAMPLES,
+    TARGET_SAMPLE_RATE,
+    PartialTranscriptionError,
+    Segment,
+    TranscriptionResult,
+    _apply_highpass,
+    _collect_segments,
+    _create_temp_wav,
+    _merge_segments,
+    _preprocess_audio,
+    _read_sample_rate,
+    _read_wav,
+    _resample,
+    _vad_parameters,
+    apply_download_timeout,
+    dedupe_segments,
+    ensure_model_disk_space,
+    estimate_transcription_seconds,
+    filter_hallucinations,
+    load_model,
+    package_version,
+    preload_model,
+    remove_fillers,
+    sanitize_text,
+    split_chapters,
+    split_result,
+    transcribe,
+    transcribe_audio,
+    transcribe_audio_openai,
+)
 
 
 class TestTranscribeAudioOpenai:
     def test_raises_value_error_when_api_key_missing(self):
-        with pytest.raises(ValueError, match="OpenAI APIキー"):
+        with pytest.raises(InvalidConfigError, match="OpenAI APIキー"):
             transcribe_audio_openai(Path("/tmp/audio.wav"), None)
 
 
@@ -22,9 +65,9 @@ class TestTranscribe:
     def test_local_mode_calls_transcribe_audio(self, monkeypatch):
         calls = {}
 
-        def fake_transcribe_audio(audio_path, model_name, progress_callback=None, vad_filter=True):
-            calls["args"] = (audio_path, model_name, progress_callback, vad_filter)
-            return "local result"
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
+            calls["args"] = (audio_path, config, progress_callback)
+            return TranscriptionResult(text="local result", model=config.whisper_model)
 
         def fake_transcribe_audio_openai(audio_path, api_key, progress_callback=None):
             raise AssertionError("openai 版は呼ばれてはいけない")
@@ -38,18 +81,18 @@ class TestTranscribe:
         audio_path = Path("/tmp/audio.wav")
         result = transcribe(audio_path, config)
 
-        assert result == "local result"
-        assert calls["args"] == (audio_path, "small", None, False)
+        assert result.text == "local result"
+        assert calls["args"] == (audio_path, config, None)
 
     def test_openai_mode_calls_transcribe_audio_openai(self, monkeypatch):
         calls = {}
 
-        def fake_transcribe_audio(audio_path, model_name, progress_callback=None, vad_filter=True):
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
             raise AssertionError("local 版は呼ばれてはいけない")
 
         def fake_transcribe_audio_openai(audio_path, api_key, progress_callback=None):
             calls["args"] = (audio_path, api_key, progress_callback)
-            return "openai result"
+            return TranscriptionResult(text="openai result", model="whisper-1")
 
         monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
         monkeypatch.setattr("transcriber.transcribe_audio_openai", fake_transcribe_audio_openai)
@@ -59,15 +102,29 @@ class TestTranscribe:
         audio_path = Path("/tmp/audio.wav")
         result = transcribe(audio_path, config)
 
-        assert result == "openai result"
+        assert result.text == "openai result"
         assert calls["args"] == (audio_path, "sk-test", None)
 
+    def test_openai_mode_delivers_segments_to_on_segment(self, monkeypatch):
+        segments = (Segment(0.0, 1.0, "一つ目"), Segment(1.0, 2.0, "二つ目"))
+
+        def fake_transcribe_audio_openai(audio_path, api_key, progress_callback=None):
+            return TranscriptionResult(text="", model="whisper-1", segments=segments)
+
+        monkeypatch.setattr("transcriber.transcribe_audio_openai", fake_transcribe_audio_openai)
+        received: list[Segment] = []
+
+        config = VoiceNoteConfig(transcription_mode="openai", openai_api_key="sk-test")
+        transcribe(Path("/tmp/audio.wav"), config, on_segment=received.append)
+
+        assert tuple(received) == segments
+
     def test_progress_callback_is_passed_through(self, monkeypatch):
         received = {}
 
-        def fake_transcribe_audio(audio_path, model_name, progress_callback=None, vad_filter=True):
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
             received["callback"] = progress_callback
-            return "ok"
+            return TranscriptionResult(text="ok", model=config.whisper_model)
 
         monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
 
@@ -78,3 +135,1232 @@ class TestTranscribe:
         transcribe(Path("/tmp/audio.wav"), config, progress_callback=on_progress)
 
         assert received["callback"] is on_progress
+
+    def test_dedupe_collapses_repeated_segments(self, monkeypatch):
+        segments = (
+            Segment(0.0, 2.0, "ご視聴ありがとうございました"),
+            Segment(2.0, 4.0, "ご視聴ありがとうございました。"),
+            Segment(4.0, 6.0, "次の話題"),
+        )
+
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text="重複あり", model="small", segments=segments)
+
+        monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
+
+        config = VoiceNoteConfig(transcription_mode="local", dedupe=True)
+        result = transcribe(Path("/tmp/audio.wav"), config)
+
+        assert result.text == "ご視聴ありがとうございました 次の話題"
+        assert len(result.segments) == 2
+
+    def test_segment_separator_rejoins_segments(self, monkeypatch):
+        segments = (Segment(0.0, 2.0, "一つ目"), Segment(2.0, 4.0, "二つ目"))
+
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text="一つ目 二つ目", model="small", segments=segments)
+
+        monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
+
+        config = VoiceNoteConfig(transcription_mode="local", segment_separator="single")
+        result = transcribe(Path("/tmp/audio.wav"), config)
+
+        assert result.text == "一つ目\n二つ目"
+
+    def test_hallucination_filter_strips_trailing_phrase_and_reports(self, monkeypatch):
+        segments = (
+            Segment(0.0, 2.0, "会議を始めます"),
+            Segment(30.0, 32.0, "ご視聴ありがとうございました。"),
+        )
+
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text="", model="small", segments=segments)
+
+        monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
+        messages: list[str] = []
+
+        config = VoiceNoteConfig(transcription_mode="local", hallucination_filter="last")
+        result = transcribe(Path("/tmp/audio.wav"), config, progress_callback=messages.append)
+
+        assert result.text == "会議を始めます"
+        assert result.segments == segments[:1]
+        assert any("ご視聴ありがとうございました" in m for m in messages)
+
+    def test_filler_filter_removes_fillers_and_rebuilds_text(self, monkeypatch):
+        segments = (Segment(0.0, 2.0, "えーと、会議を始めます"), Segment(2.0, 3.0, "あのー"))
+
+        def fake_transcribe_audio(audio_path, config, progress_callback=None, on_segment=None):
+            return TranscriptionResult(text="", model="small", segments=segments)
+
+        monkeypatch.setattr("transcriber.transcribe_audio", fake_transcribe_audio)
+
+        config = VoiceNoteConfig(transcription_mode="local", filler_filter=True)
+        result = transcribe(Path("/tmp/audio.wav"), config)
+
+        assert result.text == "会議を始めます"
+        assert result.segments == (Segment(0.0, 2.0, "会議を始めます"),)
+
+
+class TestDedupeSegments:
+    def test_ignores_case_whitespace_and_punctuation(self):
+        segments = [
+            Segment(0.0, 1.0, "Thank you."),
+            Segment(1.0, 2.0, "thank  you!"),
+            Segment(2.0, 3.0, "THANK YOU"),
+        ]
+
+        assert dedupe_segments(segments) == (Segment(0.0, 3.0, "Thank you."),)
+
+    def test_keeps_non_consecutive_repeats(self):
+        segments = [
+            Segment(0.0, 1.0, "はい"),
+            Segment(1.0, 2.0, "いいえ"),
+            Segment(2.0, 3.0, "はい"),
+        ]
+
+        assert len(dedupe_segments(segments)) == 3
+
+    def test_similar_but_different_text_is_kept(self):
+        segments = [Segment(0.0, 1.0, "今日は晴れ"), Segment(1.0, 2.0, "今日は雨")]
+
+        assert len(dedupe_segments(segments)) == 2
+
+
+class TestFilterHallucinations:
+    _PHRASES = ["ご視聴ありがとうございました", "チャンネル登録よろしくお願いします"]
+
+    def _segments(self) -> list[Segment]:
+        return [
+            Segment(0.0, 1.0, "ご視聴ありがとうございました"),
+            Segment(1.0, 2.0, "本題です"),
+            Segment(2.0, 3.0, "チャンネル登録、よろしくお願いします！"),
+            Segment(3.0, 4.0, "ご視聴ありがとうございました。"),
+        ]
+
+    def test_last_scope_strips_only_trailing_phrases(self):
+        segments = self._segments()
+
+        kept, removed = filter_hallucinations(segments, self._PHRASES, "last")
+
+        assert kept == tuple(segments[:2])
+        assert removed == tuple(segments[2:])
+
+    def test_all_scope_strips_phrases_anywhere(self):
+        segments = self._segments()
+
+        kept, removed = filter_hallucinations(segments, self._PHRASES, "all")
+
+        assert kept == (segments[1],)
+        assert len(removed) == 3
+
+    def test_phrase_inside_longer_segment_is_kept(self):
+        segments = [Segment(0.0, 1.0, "ご視聴ありがとうございました、と彼は言った")]
+
+        kept, removed = filter_hallucinations(segments, self._PHRASES, "all")
+
+        assert kept == tuple(segments)
+        assert removed == ()
+
+
+class TestRemoveFillers:
+    _WORDS = ["えーと", "えー", "あのー", "um"]
+
+    def _texts(self, *texts: str) -> list[str]:
+        segments = [Segment(float(i), float(i + 1), t) for i, t in enumerate(texts)]
+        return [s.text for s in remove_fillers(segments, self._WORDS)]
+
+    def test_removes_standalone_fillers_with_following_comma(self):
+        assert self._texts("えーと、今日は、えー、晴れです") == ["今日は、晴れです"]
+
+    def test_filler_attached_to_a_word_is_kept(self):
+        assert self._texts("あのーそれで", "えーとですね") == ["あのーそれで", "えーとですね"]
+
+    def test_keeps_sentence_end_after_trailing_filler(self):
+        assert self._texts("そうですね、えー。次です", "はい。えー。次") == [
+            "そうですね。次です",
+            "はい。次",
+        ]
+
+    def test_lengthened_filler_and_english_words(self):
+        assert self._texts("えーーー、はい", "Um, I think so") == ["はい", "I think so"]
+
+    def test_drops_segments_of_only_fillers(self):
+        assert self._texts("えーと、あのー", "本題です") == ["本題です"]
+
+    def test_empty_word_list_keeps_segments(self):
+        segments = [Segment(0.0, 1.0, "えーと")]
+
+        assert remove_fillers(segments, []) == tuple(segments)
+
+
+class TestTranscriptionResultMetadata:
+    def test_includes_all_available_fields(self):
+        result = TranscriptionResult(
+            text="本文", model="small", language="ja", duration=12.345, sample_rate=16000
+        )
+        assert result.metadata() == {
+            "duration": 12.3,
+            "model": "small",
+            "language": "ja",
+            "sample_rate": 16000,
+        }
+
+    def test_omits_missing_fields(self):
+        result = TranscriptionResult(text="本文", model="whisper-1")
+        assert result.metadata() == {"model": "whisper-1"}
+
+
+class TestPackageVersion:
+    def test_returns_installed_version(self):
+        assert package_version("pytest") == pytest.__version__
+
+    def test_reports_missing_package(self):
+        assert package_version("voicenote-no-such-package") == "未インストール"
+
+
+class _FakeInfo:
+    language = "ja"
+    duration = 1.0
+
+
+class _FakeModel:
+    def __init__(self):
+        self.kwargs: dict = {}
+
+    def transcribe(self, path, **kwargs):
+        self.kwargs = kwargs
+        return iter([]), _FakeInfo()
+
+
+class TestEstimateTranscriptionSeconds:
+    def test_scales_with_audio_length_and_model(self):
+        small = estimate_transcription_seconds("small", 600, beam_size=5)
+        large = estimate_transcription_seconds("large-v3", 600, beam_size=5)
+
+        assert small == pytest.approx(180)
+        assert large > small
+
+    def test_greedy_is_faster_than_beam_search(self):
+        greedy = estimate_transcription_seconds("tiny", 600, beam_size=1)
+
+        assert greedy < estimate_transcription_seconds("tiny", 600, beam_size=5)
+
+    def test_english_only_model_uses_same_size_factor(self):
+        assert estimate_transcription_seconds("base.en", 60, 5) == pytest.approx(
+            estimate_transcription_seconds("base", 60, 5)
+        )
+
+    def test_unknown_model_has_no_estimate(self):
+        assert estimate_transcription_seconds("/models/custom", 60, 5) is None
+
+
+class TestTranscribeAudioProgress:
+    def test_reports_estimate_and_remaining_time(self, monkeypatch):
+        class _LongInfo:
+            language = "ja"
+            duration = 600.0
+
+        class _SegmentModel:
+            def transcribe(self, path, **kwargs):
+                segments = [
+                    types.SimpleNamespace(start=end - 150, end=end, text=f"{end}秒まで")
+                    for end in (150.0, 300.0, 600.0)
+                ]
+                return iter(segments), _LongInfo()
+
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: _SegmentModel())
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        messages: list[str] = []
+
+        transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig(), messages.append)
+
+        assert any("10.0分" in m and "約3分" in m and "頃に完了" in m for m in messages)
+        progress = [m for m in messages if "%（残り" in m]
+        assert [m.split()[1].split("%")[0] for m in progress] == ["25", "50"]
+
+
+class TestTranscribeAudioOptions:
+    def _install_fake_model(self, monkeypatch) -> _FakeModel:
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        return model
+
+    def test_defaults_match_faster_whisper(self, monkeypatch):
+        model = self._install_fake_model(monkeypatch)
+
+        transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig())
+
+        assert model.kwargs["no_speech_threshold"] == 0.6
+        assert model.kwargs["suppress_tokens"] == [-1]
+        assert model.kwargs["best_of"] == 5
+        assert model.kwargs["temperature"] == (0.0, 0.2, 0.4, 0.6, 0.8, 1.0)
+
+    def test_passes_configured_sampling_options(self, monkeypatch):
+        model = self._install_fake_model(monkeypatch)
+        config = VoiceNoteConfig(best_of=1, temperature=0.0)
+
+        transcribe_audio(Path("/tmp/audio.mp3"), config)
+
+        assert model.kwargs["best_of"] == 1
+        assert model.kwargs["temperature"] == 0.0
+
+    def test_passes_configured_no_speech_options(self, monkeypatch):
+        model = self._install_fake_model(monkeypatch)
+        config = VoiceNoteConfig(no_speech_threshold=0.9, suppress_non_speech=False)
+
+        transcribe_audio(Path("/tmp/audio.mp3"), config)
+
+        assert model.kwargs["no_speech_threshold"] == 0.9
+        assert model.kwargs["suppress_tokens"] == []
+
+    def test_applies_model_overrides(self, monkeypatch):
+        model = self._install_fake_model(monkeypatch)
+        config = VoiceNoteConfig(
+            whisper_model="large-v3",
+            language="en",
+            model_overrides={"large-v3": {"language": "ja", "beam_size": 8}},
+        )
+
+        transcribe_audio(Path("/tmp/audio.mp3"), config)
+
+        assert model.kwargs["language"] == "ja"
+        assert model.kwargs["beam_size"] == 8
+
+
+class TestEnsureModelDiskSpace:
+    def _setup(self, monkeypatch, tmp_path, free_mb: int) -> list[int]:
+        required: list[int] = []
+
+        def check(path, required_mb, purpose):
+            required.append(required_mb)
+            if free_mb < required_mb:
+                raise InsufficientDiskSpaceError("空き容量が不足")
+
+        monkeypatch.setenv("HF_HUB_CACHE", str(tmp_path))
+        monkeypatch.setattr("transcriber.ensure_free_space", check)
+        return required
+
+    def test_requires_model_size_plus_margin_before_download(self, monkeypatch, tmp_path):
+        required = self._setup(monkeypatch, tmp_path, free_mb=100_000)
+
+        ensure_model_disk_space(VoiceNoteConfig(whisper_model="large-v3", min_free_disk_mb=500))
+
+        assert required == [3090 + 500]
+
+    def test_skips_check_for_downloaded_model(self, monkeypatch, tmp_path):
+        required = self._setup(monkeypatch, tmp_path, free_mb=0)
+        (tmp_path / "models--Systran--faster-whisper-small").mkdir()
+
+        ensure_model_disk_space(VoiceNoteConfig(whisper_model="small"))
+
+        assert required == []
+
+    def test_skips_check_for_local_model_path(self, monkeypatch, tmp_path):
+        required = self._setup(monkeypatch, tmp_path, free_mb=0)
+
+        ensure_model_disk_space(VoiceNoteConfig(whisper_model=str(tmp_path / "my-model")))
+
+        assert required == []
+
+    def test_transcription_stops_before_loading_model(self, monkeypatch, tmp_path):
+        self._setup(monkeypatch, tmp_path, free_mb=0)
+
+        def fail_load(name, cpu_threads=0):
+            raise AssertionError("空き容量が足りないときはモデルをロードしない")
+
+        monkeypatch.setattr("transcriber.load_model", fail_load)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+
+        with pytest.raises(RuntimeError, match="空き容量が不足"):
+            transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig(whisper_model="tiny"))
+
+
+class TestTranscribeAudioEnglishOnlyModel:
+    def test_forces_english_and_warns_about_configured_language(self, monkeypatch):
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        messages: list[str] = []
+
+        transcribe_audio(
+            Path("/tmp/audio.mp3"),
+            VoiceNoteConfig(whisper_model="small.en", language="ja"),
+            progress_callback=messages.append,
+        )
+
+        assert model.kwargs["language"] == "en"
+        assert any(m.startswith("⚠") and "small.en" in m for m in messages)
+
+    def test_warns_that_language_detection_is_skipped(self, monkeypatch):
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        messages: list[str] = []
+
+        transcribe_audio(
+            Path("/tmp/audio.mp3"),
+            VoiceNoteConfig(whisper_model="small.en", detect_languages=["ja", "en"]),
+            progress_callback=messages.append,
+        )
+
+        assert model.kwargs["language"] == "en"
+        assert any(m.startswith("⚠") and "detect_languages" in m for m in messages)
+
+    def test_auto_detect_is_set_to_english_without_warning(self, monkeypatch):
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        messages: list[str] = []
+
+        transcribe_audio(
+            Path("/tmp/audio.mp3"),
+            VoiceNoteConfig(whisper_model="tiny.en"),
+            progress_callback=messages.append,
+        )
+
+        assert model.kwargs["language"] == "en"
+        assert model.kwargs["beam_size"] == 1
+        assert not any(m.startswith("⚠") for m in messages)
+        assert any("組み込みの既定値で beam_size 1" in m for m in messages)
+
+
+class TestTranscribeAudioDetectLanguages:
+    class _DetectingModel(_FakeModel):
+        def detect_language(self, audio):
+            # 全言語の確率では英語でも日本語でもなく中国語が最も高い
+            return "zh", 0.5, [("zh", 0.5), ("en", 0.2), ("ja", 0.3)]
+
+    def _transcribe(self, monkeypatch, model, config: VoiceNoteConfig) -> list[str]:
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        monkeypatch.setattr("transcriber._decode_audio", lambda path: [0.0] * 16000)
+        messages: list[str] = []
+        transcribe_audio(Path("/tmp/audio.mp3"), config, progress_callback=messages.append)
+        return messages
+
+    def test_picks_most_probable_candidate(self, monkeypatch):
+        model = self._DetectingModel()
+
+        messages = self._transcribe(
+            monkeypatch, model, VoiceNoteConfig(detect_languages=["ja", "en"])
+        )
+
+        assert model.kwargs["language"] == "ja"
+        assert any("言語を判定しました: ja" in m for m in messages)
+
+    def test_configured_language_skips_detection(self, monkeypatch):
+        model = self._DetectingModel()
+
+        self._transcribe(
+            monkeypatch, model, VoiceNoteConfig(language="en", detect_languages=["ja", "en"])
+        )
+
+        assert model.kwargs["language"] == "en"
+
+    def test_falls_back_to_auto_detect_when_unsupported(self, monkeypatch):
+        model = _FakeModel()
+
+        messages = self._transcribe(
+            monkeypatch, model, VoiceNoteConfig(detect_languages=["ja", "en"])
+        )
+
+        assert model.kwargs["language"] is None
+        assert any(m.startswith("⚠") and "ja, en" in m for m in messages)
+
+
+class TestTranscribeAudioMultilingual:
+    class _BilingualModel:
+        """無音（0.0）の区間を日本語、それ以外を英語と判定するモデル。"""
+
+        def __init__(self):
+            self.calls: list[dict] = []
+
+        def detect_language(self, audio):
+            language = "en" if audio[0] else "ja"
+            return language, 0.9, [(language, 0.9), ("zh", 0.1)]
+
+        def transcribe(self, audio, **kwargs):
+            self.calls.append(kwargs)
+            segment = types.SimpleNamespace(start=0.0, end=1.0, text=f" text-{kwargs['language']}")
+            return iter([segment]), _FakeInfo()
+
+    def _transcribe(self, monkeypatch, config: VoiceNoteConfig):
+        model = self._BilingualModel()
+        audio = [0.0] * 48000 + [1.0] * 32000 + [0.0] * 16000
+        chunks = [(0, 48000), (48000, 80000), (80000, 96000)]
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        monkeypatch.setattr("transcriber._decode_audio", lambda path: audio)
+        monkeypatch.setattr("transcriber._speech_chunks", lambda audio, config: chunks)
+        return model, transcribe_audio(Path("/tmp/audio.mp3"), config)
+
+    def test_tags_language_per_chunk(self, monkeypatch):
+        config = VoiceNoteConfig(multilingual=True, detect_languages=["ja", "en"])
+
+        model, result = self._transcribe(monkeypatch, config)
+
+        assert [s.language for s in result.segments] == ["ja", "en", "en"]
+        assert [s.start for s in result.segments] == [0.0, 3.0, 5.0]
+        assert result.text == "[ja] text-ja\n\n[en] text-en text-en"
+        assert result.language == "ja, en"
+        assert result.duration == 6.0
+        assert all(call["vad_filter"] is False for call in model.calls)
+
+    def test_short_chunk_keeps_previous_language(self, monkeypatch):
+        model, result = self._transcribe(monkeypatch, VoiceNoteConfig(multilingual=True))
+
+        # 最後の1秒の区間は無音（日本語と判定される）だが、短いため直前の英語のまま
+        assert model.calls[-1]["language"] == "en"
+
+    def test_configured_language_disables_multilingual(self, monkeypatch):
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+
+        result = transcribe_audio(
+            Path("/tmp/audio.mp3"), VoiceNoteConfig(multilingual=True, language="ja")
+        )
+
+        assert model.kwargs["language"] == "ja"
+        assert result.language == "ja"
+
+
+class TestTranscribeAudioChunked:
+    class _PerSecondModel:
+        """区間の1秒ごとに、その秒（元音声の時刻）を本文にしたセグメントを返すモデル。"""
+
+        def __init__(self):
+            self.calls: list[dict] = []
+            self.lengths: list[int] = []
+
+        def detect_language(self, audio):
+            return "ja", 0.9, [("ja", 0.9), ("en", 0.1)]
+
+        def transcribe(self, audio, **kwargs):
+            self.calls.append(kwargs)
+            self.lengths.append(len(audio) // TARGET_SAMPLE_RATE)
+            seconds = len(audio) // TARGET_SAMPLE_RATE
+            segments = [
+                types.SimpleNamespace(
+                    start=float(k), end=float(k + 1), text=f" s{audio[k * TARGET_SAMPLE_RATE]}"
+                )
+                for k in range(seconds)
+            ]
+            return iter(segments), _FakeInfo()
+
+    def test_stitches_overlapping_chunks_with_absolute_timestamps(self, monkeypatch):
+        model = self._PerSecondModel()
+        # 各サンプルの値はその秒（8秒の音声）
+        audio = [i // TARGET_SAMPLE_RATE for i in range(8 * TARGET_SAMPLE_RATE)]
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        monkeypatch.setattr("transcriber._decode_audio", lambda path: audio)
+
+        # 3秒ごとに区切り、2秒ずつ重ねる: 0〜5秒 / 3〜8秒 / 6〜8秒
+        result = transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig(chunk_minutes=0.05))
+
+        assert [s.text for s in result.segments] == [f"s{k}" for k in range(8)]
+        assert [s.start for s in result.segments] == [float(k) for k in range(8)]
+        assert len(model.calls) == 3
+        assert all(call["language"] == "ja" for call in model.calls)
+        assert result.language == "ja"
+        assert result.duration == 8.0
+
+    @pytest.mark.parametrize(("overlap", "lengths"), [(0.0, [3, 3, 2]), (4.0, [7, 5, 2])])
+    def test_overlap_is_configurable(self, monkeypatch, overlap: float, lengths: list[int]):
+        model = self._PerSecondModel()
+        audio = [i // TARGET_SAMPLE_RATE for i in range(8 * TARGET_SAMPLE_RATE)]
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+        monkeypatch.setattr("transcriber._decode_audio", lambda path: audio)
+
+        config = VoiceNoteConfig(chunk_minutes=0.05, chunk_overlap_seconds=overlap)
+        result = transcribe_audio(Path("/tmp/audio.mp3"), config)
+
+        assert model.lengths == lengths
+        # 重なりの長さによらず、各秒のセグメントが1回ずつ並ぶ
+        assert [s.text for s in result.segments] == [f"s{k}" for k in range(8)]
+
+
+class TestLoadModel:
+    def test_reuses_model_for_same_settings(self, monkeypatch):
+        loaded: list = []
+
+        def whisper_model(name, device, compute_type, cpu_threads):
+            loaded.append((name, cpu_threads))
+            return object()
+
+        monkeypatch.setitem(
+            sys.modules, "faster_whisper", types.SimpleNamespace(WhisperModel=whisper_model)
+        )
+        load_model.cache_clear()
+        try:
+            first = load_model("tiny", 2)
+            assert load_model("tiny", 2) is first
+            load_model("tiny", 4)
+        finally:
+            load_model.cache_clear()
+
+        assert loaded == [("tiny", 2), ("tiny", 4)]
+
+
+class TestTranscribeAudioModelLoading:
+    def test_preprocesses_while_model_is_loading(self, monkeypatch):
+        preprocessed = threading.Event()
+
+        def slow_load(name, cpu_threads=0):
+            # 前処理が先に終わらなければタイムアウトする（＝順番に実行されている）
+            assert preprocessed.wait(timeout=5)
+            return _FakeModel()
+
+        def preprocess(path, **kwargs):
+            preprocessed.set()
+            return path
+
+        monkeypatch.setattr("transcriber.load_model", slow_load)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: True)
+        monkeypatch.setattr("transcriber._preprocess_audio", preprocess)
+
+        result = transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig())
+
+        assert result.language == "ja"
+
+    def test_preprocess_failure_does_not_start_model_download(self, monkeypatch):
+        loaded: list[str] = []
+
+        def preprocess(path, **kwargs):
+            raise OSError("デコードできません")
+
+        monkeypatch.setattr(
+            "transcriber.load_model", lambda name, cpu_threads=0: loaded.append(name)
+        )
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: False)
+        monkeypatch.setattr("transcriber.ensure_model_disk_space", lambda config: None)
+        monkeypatch.setattr("transcriber._preprocess_audio", preprocess)
+
+        with pytest.raises(TranscriptionError, match="デコードできません"):
+            transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig())
+        assert loaded == []
+
+    def test_model_load_error_is_reported_and_temp_file_removed(self, monkeypatch, tmp_path):
+        temp_wav = tmp_path / "preprocessed.wav"
+        temp_wav.write_bytes(b"")
+
+        def broken_load(name, cpu_threads=0):
+            raise OSError("model.bin が壊れています")
+
+        monkeypatch.setattr("transcriber.load_model", broken_load)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: temp_wav)
+
+        with pytest.raises(RuntimeError, match="model.bin が壊れています"):
+            transcribe_audio(tmp_path / "audio.mp3", VoiceNoteConfig())
+        assert not temp_wav.exists()
+
+    def test_keep_temp_leaves_preprocessed_file_and_reports_path(self, monkeypatch, tmp_path):
+        temp_wav = tmp_path / "preprocessed.wav"
+        temp_wav.write_bytes(b"")
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: _FakeModel())
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: temp_wav)
+        messages: list[str] = []
+
+        config = VoiceNoteConfig(keep_temp=True)
+        transcribe_audio(tmp_path / "audio.mp3", config, progress_callback=messages.append)
+
+        assert temp_wav.exists()
+        assert f"前処理後の音声を残しました: {temp_wav}" in messages
+
+    @pytest.mark.parametrize(
+        ("downloaded", "error"), [(True, ModelLoadError), (False, ModelDownloadError)]
+    )
+    def test_model_load_error_distinguishes_download_failure(
+        self, monkeypatch, downloaded: bool, error: type
+    ):
+        def broken_load(name, cpu_threads=0):
+            raise OSError("接続できません")
+
+        monkeypatch.setattr("transcriber.load_model", broken_load)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: downloaded)
+        monkeypatch.setattr("transcriber.ensure_model_disk_space", lambda config: None)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+
+        with pytest.raises(error, match="接続できません") as excinfo:
+            transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig())
+        assert excinfo.type is error
+
+    def test_stalled_download_is_reported_as_timeout(self, monkeypatch):
+        class ReadTimeout(OSError):
+            pass
+
+        def stalled_load(name, cpu_threads=0):
+            try:
+                raise ReadTimeout("Read timed out.")
+            except ReadTimeout as e:
+                raise RuntimeError("ファイルを取得できません") from e
+
+        monkeypatch.delenv("HF_HUB_DOWNLOAD_TIMEOUT", raising=False)
+        monkeypatch.delenv("HF_HUB_ETAG_TIMEOUT", raising=False)
+        monkeypatch.setattr("transcriber.load_model", stalled_load)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: False)
+        monkeypatch.setattr("transcriber.ensure_model_disk_space", lambda config: None)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+
+        with pytest.raises(ModelDownloadTimeoutError, match="15 秒") as excinfo:
+            transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig(model_download_timeout=15))
+        assert isinstance(excinfo.value, ModelDownloadError)
+        assert os.environ["HF_HUB_DOWNLOAD_TIMEOUT"] == "15"
+
+    def test_preprocess_error_is_reported(self, monkeypatch):
+        def broken_preprocess(path, **kwargs):
+            raise ValueError("デコードできません")
+
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: _FakeModel())
+        monkeypatch.setattr("transcriber._preprocess_audio", broken_preprocess)
+
+        with pytest.raises(TranscriptionError, match="デコードできません"):
+            transcribe_audio(Path("/tmp/audio.mp3"), VoiceNoteConfig())
+
+
+class TestCollectSegments:
+    def _raw(self, *texts: str):
+        for i, text in enumerate(texts):
+            yield Segment(start=float(i), end=float(i + 1), text=f" {text} ")
+
+    def test_strips_text_and_notifies_each_segment(self):
+        received: list[Segment] = []
+
+        segments = _collect_segments(self._raw("a", "b"), on_segment=received.append)
+
+        assert segments == (Segment(0.0, 1.0, "a"), Segment(1.0, 2.0, "b"))
+        assert list(segments) == received
+
+    def test_keeps_completed_segments_when_decoding_fails(self):
+        def failing():
+            yield from self._raw("a", "b")
+            raise MemoryError("out of memory")
+
+        with pytest.raises(PartialTranscriptionError) as exc_info:
+            _collect_segments(failing())
+
+        assert [s.text for s in exc_info.value.segments] == ["a", "b"]
+        assert "out of memory" in str(exc_info.value)
+
+    def test_strips_lone_continuation_byte_and_warns(self):
+        # 先頭の 0x80 は前のバイトを失った継続バイト
+        text = b"\x80\xe4\xbb\x8a\xe6\x97\xa5\xe3\x81\xaf".decode("utf-8", "surrogateescape")
+        warnings: list[str] = []
+
+        segments = _collect_segments(self._raw(text, "晴れ\ufffd"), on_warning=warnings.append)
+
+        assert [s.text for s in segments] == ["今日は", "晴れ"]
+        assert len(warnings) == 1
+        assert "2個" in warnings[0]
+
+
+class TestSanitizeText:
+    def test_repairs_multibyte_character_kept_as_raw_bytes(self):
+        raw = "今日は" + b"\xe6\x99\xb4".decode("ascii", "surrogateescape") + "れ"
+
+        assert sanitize_text(raw) == ("今日は晴れ", 0)
+
+    def test_drops_truncated_sequence_and_lone_surrogate(self):
+        truncated = b"\xe6\x99".decode("ascii", "surrogateescape")
+
+        assert sanitize_text(f"晴れ{truncated}\ud800です") == ("晴れです", 2)
+
+    def test_leaves_valid_text_untouched(self):
+        assert sanitize_text("今日は、晴れです。") == ("今日は、晴れです。", 0)
+
+
+class TestSplitChapters:
+    def test_splits_at_gaps_at_least_min_gap(self):
+        segments = [
+            Segment(0.0, 4.0, "はじめ"),
+            Segment(5.0, 9.0, "つづき"),
+            Segment(40.0, 45.0, "次の話題"),
+        ]
+
+        assert split_chapters(segments, 30.0) == [(0.0, "はじめ つづき"), (40.0, "次の話題")]
+
+    def test_single_chapter_without_long_gaps(self):
+        segments = [Segment(3.0, 4.0, "a"), Segment(5.0, 6.0, "b")]
+
+        assert split_chapters(segments, 30.0) == [(3.0, "a b")]
+
+    def test_no_chapters_without_segments(self):
+        assert split_chapters([], 30.0) == []
+
+    def test_bullet_separator_puts_each_segment_on_its_own_item(self):
+        segments = [Segment(0.0, 1.0, " はじめ "), Segment(2.0, 3.0, ""), Segment(4.0, 5.0, "次")]
+
+        assert split_chapters(segments, 30.0, "bullet") == [(0.0, "- はじめ\n- 次")]
+
+    def test_blank_separator_leaves_empty_line_between_segments(self):
+        segments = [Segment(0.0, 1.0, "a"), Segment(2.0, 3.0, "b")]
+
+        assert split_chapters(segments, 30.0, "blank") == [(0.0, "a\n\nb")]
+
+
+class TestApplyDownloadTimeout:
+    def test_sets_env_and_already_imported_constants(self, monkeypatch):
+        constants = types.SimpleNamespace(HF_HUB_DOWNLOAD_TIMEOUT=10, DEFAULT_ETAG_TIMEOUT=10)
+        monkeypatch.setitem(sys.modules, "huggingface_hub.constants", constants)
+        monkeypatch.delenv("HF_HUB_DOWNLOAD_TIMEOUT", raising=False)
+        monkeypatch.delenv("HF_HUB_ETAG_TIMEOUT", raising=False)
+
+        apply_download_timeout(45.0)
+
+        assert os.environ["HF_HUB_DOWNLOAD_TIMEOUT"] == "45"
+        assert os.environ["HF_HUB_ETAG_TIMEOUT"] == "45"
+        assert constants.HF_HUB_DOWNLOAD_TIMEOUT == 45.0
+        assert constants.DEFAULT_ETAG_TIMEOUT == 45.0
+
+    def test_keeps_timeouts_set_by_the_user(self, monkeypatch):
+        constants = types.SimpleNamespace(HF_HUB_DOWNLOAD_TIMEOUT=120, DEFAULT_ETAG_TIMEOUT=10)
+        monkeypatch.setitem(sys.modules, "huggingface_hub.constants", constants)
+        monkeypatch.setenv("HF_HUB_DOWNLOAD_TIMEOUT", "120")
+        monkeypatch.delenv("HF_HUB_ETAG_TIMEOUT", raising=False)
+
+        apply_download_timeout(45.0)
+        apply_download_timeout(30.0)
+
+        assert os.environ["HF_HUB_DOWNLOAD_TIMEOUT"] == "120"
+        assert constants.HF_HUB_DOWNLOAD_TIMEOUT == 120
+        # 自分で設定した値は次の呼び出しで更新する
+        assert os.environ["HF_HUB_ETAG_TIMEOUT"] == "30"
+        assert constants.DEFAULT_ETAG_TIMEOUT == 30.0
+
+
+class TestPreloadModel:
+    def test_stalled_download_is_reported_as_timeout(self, monkeypatch):
+        def stalled_load(name, cpu_threads=0):
+            raise TimeoutError("Read timed out.")
+
+        monkeypatch.delenv("HF_HUB_DOWNLOAD_TIMEOUT", raising=False)
+        monkeypatch.delenv("HF_HUB_ETAG_TIMEOUT", raising=False)
+        monkeypatch.setattr("transcriber.load_model", stalled_load)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: False)
+        monkeypatch.setattr("transcriber.ensure_model_disk_space", lambda config: None)
+
+        with pytest.raises(ModelDownloadTimeoutError, match="20 秒"):
+            preload_model(VoiceNoteConfig(model_download_timeout=20))
+        assert os.environ["HF_HUB_DOWNLOAD_TIMEOUT"] == "20"
+
+    def test_returns_loaded_model(self, monkeypatch):
+        model = _FakeModel()
+        monkeypatch.setattr("transcriber.load_model", lambda name, cpu_threads=0: model)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: True)
+        monkeypatch.setattr("transcriber.ensure_model_disk_space", lambda config: None)
+
+        assert preload_model(VoiceNoteConfig()) is model
+
+
+class TestSplitResult:
+    def test_splits_into_results_with_own_text_and_duration(self):
+        segments = (
+            Segment(0.0, 4.0, "一つ目"),
+            Segment(5.0, 9.0, "続き"),
+            Segment(60.0, 63.0, "二つ目"),
+        )
+        result = TranscriptionResult(
+            text="一つ目 続き\n\n二つ目", model="tiny", duration=63.0, segments=segments
+        )
+
+        parts = split_result(result, 30.0)
+
+        assert [p.text for p in parts] == ["一つ目 続き", "二つ目"]
+        assert [p.duration for p in parts] == [9.0, 3.0]
+        # セグメントの時刻は元の録音のまま
+        assert parts[1].segments == (Segment(60.0, 63.0, "二つ目"),)
+        assert parts[1].model == "tiny"
+
+    def test_returns_result_itself_without_long_gaps(self):
+        result = TranscriptionResult(
+            text="a b", model="tiny", segments=(Segment(0.0, 1.0, "a"), Segment(2.0, 3.0, "b"))
+        )
+
+        assert split_result(result, 30.0) == [result]
+
+    def test_result_without_segments_is_not_split(self):
+        result = TranscriptionResult(text="本文", model="tiny")
+
+        assert split_result(result, 30.0) == [result]
+
+
+class TestMergeSegmentsLanguageTags:
+    def test_marks_language_switches_and_starts_new_paragraph(self):
+        segments = [
+            Segment(0.0, 1.0, "こんにちは", "ja"),
+            Segment(1.5, 2.0, "続き", "ja"),
+            Segment(2.5, 3.0, "Hello", "en"),
+        ]
+
+        assert _merge_segments(segments) == "[ja] こんにちは 続き\n\n[en] Hello"
+
+    def test_tags_bullets_after_marker(self):
+        segments = [Segment(0.0, 1.0, "はい", "ja"), Segment(1.0, 2.0, "Yes", "en")]
+
+        assert _merge_segments(segments, "bullet") == "- [ja] はい\n- [en] Yes"
+
+    def test_segments_without_language_are_not_tagged(self):
+        assert _merge_segments([Segment(0.0, 1.0, "a"), Segment(1.0, 2.0, "b")]) == "a b"
+
+    def test_to_dict_omits_missing_language(self):
+        assert Segment(0.0, 1.0, "a").to_dict() == {"start": 0.0, "end": 1.0, "text": "a"}
+        assert Segment(0.0, 1.0, "a", "en").to_dict()["language"] == "en"
+
+
+class TestVadParameters:
+    def test_returns_none_when_vad_disabled(self):
+        assert _vad_parameters(VoiceNoteConfig(vad_filter=False)) is None
+
+    def test_uses_min_silence_and_leaves_chunk_length_unbounded_by_default(self):
+        params = _vad_parameters(VoiceNoteConfig(vad_min_silence_ms=500))
+        assert params == {"min_silence_duration_ms": 500}
+
+    def test_limits_chunk_length_when_configured(self):
+        params = _vad_parameters(VoiceNoteConfig(vad_max_chunk_seconds=30.0))
+        assert params == {"min_silence_duration_ms": 2000, "max_speech_duration_s": 30.0}
+
+
+def _sine(freq: float, sample_rate: int, seconds: float = 2.0) -> np.ndarray:
+    t = np.arange(int(sample_rate * seconds)) / sample_rate
+    return np.sin(2 * np.pi * freq * t)
+
+
+def _rms(data: np.ndarray) -> float:
+    return float(np.sqrt(np.mean(data**2)))
+
+
+class TestApplyHighpass:
+    def test_attenuates_low_frequency_rumble(self):
+        rate = 16000
+        signal = _sine(20, rate)
+        filtered = _apply_highpass(signal, rate, 80)
+        # 過渡応答を除いた定常部分で比較する
+        steady = slice(rate // 2, None)
+        assert _rms(filtered[steady]) < 0.1 * _rms(signal[steady])
+
+    def test_passes_speech_band(self):
+        rate = 16000
+        signal = _sine(1000, rate)
+        filtered = _apply_highpass(signal, rate, 80)
+        steady = slice(rate // 2, None)
+        assert _rms(filtered[steady]) > 0.9 * _rms(signal[steady])
+
+
+class TestResample:
+    def test_empty_input_returns_empty(self):
+        result = _resample(np.zeros(0, dtype=np.float32), 44100)
+        assert len(result) == 0
+
+    def test_single_sample_does_not_fail(self):
+        result = _resample(np.array([0.5], dtype=np.float32), 44100)
+        assert len(result) == 1
+
+    def test_short_input_keeps_proportional_length(self):
+        n = MIN_RESAMPLE_SAMPLES - 1
+        result = _resample(np.ones(n, dtype=np.float32), 48000)
+        # 48kHz → 16kHz は 1/3 の長さになる（ゼロ埋め分は含まない）
+        assert len(result) == -(-n // 3)
+
+    def test_returns_input_unchanged_at_target_rate(self):
+        data = np.array([0.1, 0.2], dtype=np.float32)
+        assert _resample(data, TARGET_SAMPLE_RATE) is data
+
+    @pytest.mark.parametrize("quality", ["fast", "balanced", "high"])
+    def test_long_input_is_resampled(self, quality):
+        data = np.zeros(48000, dtype=np.float32)
+        assert len(_resample(data, 48000, quality)) == 16000
+
+    @pytest.mark.parametrize("quality", ["fast", "balanced", "high"])
+    def test_preserves_low_frequency_tone(self, quality):
+        data = _sine(440, 44100)
+        result = _resample(data, 44100, quality)
+        steady = slice(TARGET_SAMPLE_RATE // 2, -TARGET_SAMPLE_RATE // 2)
+        assert abs(_rms(result[steady]) - _rms(data)) < 0.05
+
+    def test_rejects_unknown_quality(self):
+        with pytest.raises(ValueError):
+            _resample(np.zeros(1000, dtype=np.float32), 44100, "ultra")
+
+    def test_resampler_cutoff_attenuates_tone_below_output_nyquist(self):
+        # cutoff 0.5 は出力のナイキスト周波数（8kHz）の半分の 4kHz で遮断する
+        data = _sine(6000, 48000)
+        result = _resample(data, 48000, "high", {"cutoff": 0.5, "half_length": 20})
+        steady = slice(TARGET_SAMPLE_RATE // 2, -TARGET_SAMPLE_RATE // 2)
+        assert _rms(result[steady]) < 0.1 * _rms(data)
+
+
+class TestCreateTempWav:
+    def test_creates_file_in_configured_dir(self, tmp_path: Path):
+        path = _create_temp_wav(str(tmp_path / "work"))
+        try:
+            assert path.parent == tmp_path / "work"
+            assert path.suffix == ".wav"
+        finally:
+            path.unlink(missing_ok=True)
+
+    def test_falls_back_to_system_temp_when_unwritable(self, tmp_path: Path):
+        blocker = tmp_path / "blocker"
+        blocker.write_text("")
+        warnings: list[str] = []
+        path = _create_temp_wav(str(blocker / "child"), notify=warnings.append)
+        try:
+            assert path.exists()
+            assert path.parent != blocker / "child"
+            assert len(warnings) == 1
+        finally:
+            path.unlink(missing_ok=True)
+
+    def test_uses_system_temp_by_default(self):
+        path = _create_temp_wav()
+        try:
+            assert path.exists()
+        finally:
+            path.unlink(missing_ok=True)
+
+
+class TestPreprocessCompressedAudio:
+    def test_passes_through_when_no_filtering_needed(self, monkeypatch, tmp_path: Path):
+        def fail_decode(path):
+            raise AssertionError("フィルタ不要ならデコードしてはいけない")
+
+        monkeypatch.setattr("transcriber._decode_audio", fail_decode)
+        audio = tmp_path / "memo.m4a"
+
+        assert _preprocess_audio(audio) == audio
+
+    def test_decodes_and_filters_when_highpass_enabled(self, monkeypatch, tmp_path: Path):
+        from scipy.io import wavfile
+
+        rumble = _sine(30, TARGET_SAMPLE_RATE).astype(np.float32) * 0.5
+        speech = _sine(1000, TARGET_SAMPLE_RATE).astype(np.float32) * 0.5
+        monkeypatch.setattr("transcriber._decode_audio", lambda path: rumble + speech)
+
+        out = _preprocess_audio(tmp_path / "memo.mp3", highpass_hz=100, temp_dir=str(tmp_path))
+        try:
+            rate, data = wavfile.read(str(out))
+            assert rate == TARGET_SAMPLE_RATE
+            assert len(data) == len(rumble)
+            # 2秒分なので FFT の分解能は 0.5Hz（30Hz → bin 60, 1kHz → bin 2000）
+            spectrum = np.abs(np.fft.rfft(data.astype(np.float64)))
+            assert spectrum[60] < 0.2 * spectrum[2000]
+        finally:
+            out.unlink(missing_ok=True)
+
+
+def _encode_audio(path: Path, codec: str, samples: np.ndarray, sample_rate: int) -> Path:
+    """-1〜1 のモノラルの samples を、PyAV で codec の圧縮音声ファイルにする。"""
+    import av
+
+    try:
+        av.Codec(codec, "w")
+    except av.codec.codec.UnknownCodecError:
+        pytest.skip(f"この環境の PyAV には {codec} のエンコーダがありません")
+    pcm = (samples * 32767).astype(np.int16).reshape(1, -1)
+    with av.open(str(path), "w") as container:
+        stream = container.add_stream(codec, rate=sample_rate)
+        stream.codec_context.layout = "mono"
+        frame = av.AudioFrame.from_ndarray(pcm, format="s16", layout="mono")
+        frame.sample_rate = sample_rate
+        frame.pts = 0
+        for packet in stream.encode(frame):
+            container.mux(packet)
+        for packet in stream.encode(None):
+            container.mux(packet)
+    return path
+
+
+class TestPreprocessRealCompressedAudio:
+    """実際に圧縮した音声を、faster-whisper（PyAV）のデコードから前処理まで通す。"""
+
+    def _preprocess(self, path: Path, tmp_path: Path) -> tuple[int, np.ndarray]:
+        from scipy.io import wavfile
+
+        out = _preprocess_audio(path, highpass_hz=50, temp_dir=str(tmp_path))
+        assert out != path
+        rate, data = wavfile.read(str(out))
+        return rate, data.astype(np.float64) / 32767
+
+    def test_flac_samples_match_source(self, tmp_path: Path):
+        source = 0.5 * _sine(440, TARGET_SAMPLE_RATE, seconds=1.0)
+        path = _encode_audio(tmp_path / "memo.flac", "flac", source, TARGET_SAMPLE_RATE)
+
+        rate, data = self._preprocess(path, tmp_path)
+
+        assert rate == TARGET_SAMPLE_RATE
+        assert len(data) == len(source)
+        # 可逆圧縮なので、同じハイパスフィルタをかけて正規化した元の音声と一致する
+        expected = _apply_highpass(source, TARGET_SAMPLE_RATE, 50)
+        expected /= np.max(np.abs(expected))
+        np.testing.assert_allclose(data, expected, atol=2e-3)
+
+    def test_mp3_keeps_length_and_tone(self, tmp_path: Path):
+        source = 0.5 * _sine(440, 44100, seconds=1.0)
+        path = _encode_audio(tmp_path / "memo.mp3", "mp3", source, 44100)
+
+        rate, data = self._preprocess(path, tmp_path)
+
+        assert rate == TARGET_SAMPLE_RATE
+        # 非可逆圧縮はエンコーダの遅延・パディングで長さが少し変わる
+        assert abs(len(data) - TARGET_SAMPLE_RATE) < 0.1 * TARGET_SAMPLE_RATE
+        # 1秒分なので FFT の分解能は約1Hz。音の高さと大きさ（正規化後の正弦波の RMS）を保つ
+        spectrum = np.abs(np.fft.rfft(data))
+        peak_hz = np.argmax(spectrum) * TARGET_SAMPLE_RATE / len(data)
+        assert abs(peak_hz - 440) < 2
+        steady = data[len(data) // 4 : -len(data) // 4]
+        assert _rms(steady) == pytest.approx(1 / np.sqrt(2), rel=0.1)
+
+
+class TestPreprocessStereoWav:
+    """16kHz のステレオ WAV はダウンミックスだけ行い、リサンプリングしない。"""
+
+    def _write_stereo(self, path: Path, dtype) -> np.ndarray:
+        from scipy.io import wavfile
+
+        left = _sine(440, TARGET_SAMPLE_RATE) * 0.5
+        # 右チャンネルは無音にして、チャンネルの取り違え・インターリーブのずれを検出できるようにする
+        stereo = np.column_stack([left, np.zeros_like(left)])
+        if dtype == np.int16:
+            stereo = (stereo * 32767).astype(np.int16)
+        else:
+            stereo = stereo.astype(np.float32)
+        wavfile.write(str(path), TARGET_SAMPLE_RATE, stereo)
+        return left
+
+    def _assert_downmixed_without_resampling(self, monkeypatch, tmp_path: Path, dtype):
+        from scipy.io import wavfile
+
+        def fail_resample(*args, **kwargs):
+            raise AssertionError("16kHz の入力をリサンプリングしてはいけない")
+
+        monkeypatch.setattr("transcriber._resample", fail_resample)
+        audio = tmp_path / "stereo.wav"
+        left = self._write_stereo(audio, dtype)
+
+        out = _preprocess_audio(audio, temp_dir=str(tmp_path))
+        try:
+            rate, data = wavfile.read(str(out))
+            assert rate == TARGET_SAMPLE_RATE
+            assert data.ndim == 1
+            assert len(data) == len(left)
+            # 左右の平均をピークで正規化しているので、左チャンネルと同じ波形になる
+            expected = left / np.max(np.abs(left))
+            assert np.allclose(data / 32767, expected, atol=1e-3)
+        finally:
+            out.unlink(missing_ok=True)
+
+    def test_float_stereo_is_downmixed_in_order(self, monkeypatch, tmp_path: Path):
+        self._assert_downmixed_without_resampling(monkeypatch, tmp_path, np.float32)
+
+    def test_int_stereo_is_downmixed_in_order(self, monkeypatch, tmp_path: Path):
+        self._assert_downmixed_without_resampling(monkeypatch, tmp_path, np.int16)
+
+    def test_four_channels_are_averaged(self, tmp_path: Path):
+        from scipy.io import wavfile
+
+        tone = _sine(440, TARGET_SAMPLE_RATE) * 0.5
+        # 4ch のうち1chだけ逆相にすると、平均は 0.5 倍の同相の波形になる
+        channels = np.column_stack([tone, tone, tone, -tone]).astype(np.float32)
+        audio = tmp_path / "quad.wav"
+        wavfile.write(str(audio), TARGET_SAMPLE_RATE, channels)
+
+        out = _preprocess_audio(audio, temp_dir=str(tmp_path))
+        try:
+            _, data = wavfile.read(str(out))
+            assert data.ndim == 1
+            assert len(data) == len(tone)
+            expected = tone / np.max(np.abs(tone))
+            assert np.allclose(data / 32767, expected, atol=1e-3)
+        finally:
+            out.unlink(missing_ok=True)
+
+
+class TestPreprocessSyntheticRecording:
+    """44.1kHz ステレオの合成音声を、Whisper 用の 16kHz モノラルに変換する一連の処理を通す。"""
+
+    def _speech_like(self, sample_rate: int, seconds: float) -> np.ndarray:
+        # 基本周波数 150Hz の倍音を 4Hz（音節程度の速さ）で振幅変調した、発話に近い信号
+        t = np.arange(int(sample_rate * seconds)) / sample_rate
+        voice = sum(np.sin(2 * np.pi * 150 * k * t) / k for k in range(1, 6))
+        envelope = 0.5 * (1 - np.cos(2 * np.pi * 4 * t))
+        return voice * envelope / 3
+
+    def test_outputs_mono_16khz_of_same_duration(self, tmp_path: Path):
+        from scipy.io import wavfile
+
+        rate = 44100
+        voice = self._speech_like(rate, 1.5)
+        stereo = (np.column_stack([voice, voice * 0.5]) * 32767).astype(np.int16)
+        audio = tmp_path / "synthetic.wav"
+        wavfile.write(str(audio), rate, stereo)
+
+        out = _preprocess_audio(audio, temp_dir=str(tmp_path))
+        try:
+            out_rate, data = wavfile.read(str(out))
+            assert out != audio
+            assert out_rate == TARGET_SAMPLE_RATE
+            assert data.ndim == 1
+            assert data.dtype == np.int16
+            assert len(data) == TARGET_SAMPLE_RATE * 3 // 2
+            assert np.max(np.abs(data)) > 0
+        finally:
+            out.unlink(missing_ok=True)
+
+
+class TestReadSampleRate:
+    def test_reads_wav_header(self, tmp_path: Path):
+        from scipy.io import wavfile
+
+        path = tmp_path / "a.wav"
+        wavfile.write(str(path), 44100, np.zeros(100, dtype=np.int16))
+
+        assert _read_sample_rate(path) == 44100
+
+    def test_returns_none_for_unreadable_file(self, tmp_path: Path):
+        path = tmp_path / "broken.m4a"
+        path.write_bytes(b"not audio")
+
+        assert _read_sample_rate(path) is None
+
+
+class TestReadTruncatedWav:
+    """ヘッダより短い（録音中のクラッシュなどで途切れた）WAV の扱い。"""
+
+    def _write_truncated(self, path: Path, channels: int, frames: int, kept_bytes: int):
+        with wave.open(str(path), "wb") as wav:
+            wav.setnchannels(channels)
+            wav.setsampwidth(2)
+            wav.setframerate(TARGET_SAMPLE_RATE)
+            wav.writeframes(b"\x01\x00" * channels * frames)
+        # 44 バイトのヘッダは残し、データの途中で切る（ヘッダのサイズは元のまま）
+        path.write_bytes(path.read_bytes()[: 44 + kept_bytes])
+
+    def test_warns_with_missing_length(self, tmp_path: Path):
+        path = tmp_path / "truncated.wav"
+        self._write_truncated(path, channels=1, frames=16000, kept_bytes=16000)
+        messages: list[str] = []
+
+        _, data = _read_wav(path, messages.append)
+
+        assert len(data) == 8000
+        assert len(messages) == 1
+        assert messages[0].startswith("⚠")
+        assert "8000サンプル・約0.5秒" in messages[0]
+
+    def test_complete_wav_has_no_warning(self, tmp_path: Path):
+        path = tmp_path / "complete.wav"
+        self._write_truncated(path, channels=1, frames=1600, kept_bytes=3200)
+        messages: list[str] = []
+
+        _read_wav(path, messages.append)
+
+        assert messages == []
+
+    def test_stereo_cut_mid_frame_is_reported_as_corrupt(self, tmp_path: Path):
+        path = tmp_path / "torn.wav"
+        self._write_truncated(path, channels=2, frames=1600, kept_bytes=3202)
+
+        with pytest.raises(ValueError, match="破損"):
+            _read_wav(path)
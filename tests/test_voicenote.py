@@ -0,0 +1,87 @@
+"""voicenote（ライブラリ API）モジュールのユニットテスト。"""
+
+import sys
+import types
+from pathlib import Path
+
+import pytest
+
+import pipeline
+import recorder
+import voicenote
+
+
+class TestPublicApi:
+    def test_all_names_are_exported(self):
+        assert all(hasattr(voicenote, name) for name in voicenote.__all__)
+
+    def test_reexports_pipeline_objects_unchanged(self):
+        assert voicenote.transcribe_and_save is pipeline.transcribe_and_save
+        assert voicenote.Segment is pipeline.Segment
+
+    def test_errors_share_voicenote_error_base(self):
+        errors = [getattr(voicenote, name) for name in voicenote.__all__ if name.endswith("Error")]
+
+        assert voicenote.VoiceNoteError in errors
+        assert all(issubclass(error, voicenote.VoiceNoteError) for error in errors)
+
+
+class TestEntryPointErrors:
+    """公開 API の各入口の失敗が VoiceNoteError のサブクラスで届くことを確認する。"""
+
+    def test_load_config_with_broken_file(self, tmp_path: Path):
+        path = tmp_path / "config.json"
+        path.write_text("{", encoding="utf-8")
+
+        with pytest.raises(voicenote.InvalidConfigError):
+            voicenote.load_config(path)
+
+    def test_save_config_to_unwritable_path(self, tmp_path: Path):
+        blocker = tmp_path / "blocker"
+        blocker.write_text("")
+
+        with pytest.raises(voicenote.ConfigSaveError):
+            voicenote.save_config(blocker / "config.json", voicenote.VoiceNoteConfig())
+
+    def test_start_recorder_with_unavailable_device(self, monkeypatch):
+        def busy_stream(**kwargs):
+            raise recorder.sd.PortAudioError("Device unavailable")
+
+        monkeypatch.setattr(recorder.sd, "InputStream", busy_stream)
+        monkeypatch.setattr(recorder, "negotiate_sample_rate", lambda d, r, dtype: (r, None))
+        monkeypatch.setattr(recorder, "negotiate_sample_format", lambda d: ("float32", None))
+
+        with pytest.raises(voicenote.DeviceUnavailableError):
+            voicenote.start_recorder(0)
+
+    def test_transcribe_without_openai_key(self, monkeypatch):
+        monkeypatch.delenv("OPENAI_API_KEY", raising=False)
+        monkeypatch.setitem(sys.modules, "openai", types.SimpleNamespace(OpenAI=None))
+        config = voicenote.VoiceNoteConfig(transcription_mode="openai", openai_api_key=None)
+
+        with pytest.raises(voicenote.InvalidConfigError):
+            voicenote.transcribe(Path("/tmp/audio.wav"), config)
+
+    def test_transcribe_with_broken_model(self, monkeypatch):
+        def broken_load(name, cpu_threads=0):
+            raise OSError("model.bin が壊れています")
+
+        monkeypatch.setattr("transcriber.load_model", broken_load)
+        monkeypatch.setattr("transcriber.is_model_downloaded", lambda name: True)
+        monkeypatch.setattr("transcriber._preprocess_audio", lambda path, **kwargs: path)
+
+        with pytest.raises(voicenote.ModelLoadError):
+            voicenote.transcribe(Path("/tmp/audio.wav"), voicenote.VoiceNoteConfig())
+
+    def test_transcribe_and_save_to_missing_folder(self, tmp_path: Path):
+        config = voicenote.VoiceNoteConfig(save_folder=str(tmp_path / "missing" / "notes"))
+
+        with pytest.raises(voicenote.SaveFolderUnavailableError):
+            voicenote.transcribe_and_save(tmp_path / "audio.wav", config)
+
+    def test_save_transcript_to_unwritable_folder(self, tmp_path: Path):
+        blocker = tmp_path / "blocker"
+        blocker.write_text("")
+
+        with pytest.raises(voicenote.NoteWriteError):
+            voicenote.save_transcript(blocker / "notes", "本文")
@@ -4,6 +4,8 @@
 (フレーキーの回避)。状態遷移・バリデーション・モード分岐を中心にテストする。
 """
 
+import threading
+from datetime import datetime
 from pathlib import Path
 
 import pytest
@@ -17,6 +19,8 @@ from gui.workflow import (
     validate_start,
     validate_transcribe_only,
 )
+from pipeline import EmptyTranscriptionError
+from transcriber import TranscriptionResult
 
 
 class ImmediateThread:
@@ -42,18 +46,40 @@ class DeferredThread:
 
 
 class FakeRecorder:
-    def __init__(self, device_id=None, fail_start=False, fail_get_data=False, data=None):
+    def __init__(
+        self,
+        device_id=None,
+        fail_start=False,
+        fail_get_data=False,
+        data=None,
+        sample_rate=16000,
+        fallback_warning=None,
+        buffer_warning=None,
+    ):
         self.device_id = device_id
+        self.sample_rate = sample_rate
+        self.fallback_warning = fallback_warning
+        self.buffer_warning = buffer_warning
         self._fail_start = fail_start
         self._fail_get_data = fail_get_data
         self._data = data if data is not None else [0.0] * 16000
         self.started = False
         self.stopped = False
+        self.started_at = None
+        self.interruptions: list[str] = []
+        self.lost = threading.Event()
+        self.clipped = threading.Event()
+        self.clipping_warning = "入力ゲインで音が割れています"
+
+    @property
+    def warnings(self) -> list[str]:
+        return [w for w in (self.fallback_warning, self.buffer_warning) if w]
 
     def start(self):
         if self._fail_start:
             raise RuntimeError("デバイスが使用できません")
         self.started = True
+        self.started_at = datetime(2026, 1, 16, 9, 0, 0)
 
     def stop(self):
         self.stopped = True
@@ -145,13 +171,54 @@ class TestRecordingWorkflowStart:
         assert wf.is_recording is False
         assert spy.recording_started == 0
 
+    def test_refuses_to_record_when_disk_is_nearly_full(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        def no_space(path, required_mb, purpose):
+            raise RuntimeError(f"空き容量が不足しているため{purpose}を中止しました")
+
+        monkeypatch.setattr(workflow_module, "ensure_free_space", no_space)
+        spy = SpyCallbacks()
+        recorder = FakeRecorder()
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build(), recorder_factory=lambda d: recorder)
+
+        error = wf.start(device_id=None, device_label="デバイスなし", rec_dest=tmp_path)
+
+        assert "録音を中止" in error
+        assert recorder.started is False
+        assert wf.is_recording is False
+
+    def test_sample_rate_fallback_is_logged(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(workflow_module.threading, "Thread", DeferredThread)
+        spy = SpyCallbacks()
+        recorder = FakeRecorder(sample_rate=48000, fallback_warning="16000Hz 非対応")
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build(), recorder_factory=lambda d: recorder)
+
+        wf.start(device_id=None, device_label="デバイスなし")
+
+        assert "⚠ 16000Hz 非対応" in spy.logs
+
+    def test_buffer_size_fallback_is_logged(self, monkeypatch: pytest.MonkeyPatch):
+        monkeypatch.setattr(workflow_module.threading, "Thread", DeferredThread)
+        spy = SpyCallbacks()
+        recorder = FakeRecorder(buffer_warning="バッファサイズ 64 フレームで録音できません")
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build(), recorder_factory=lambda d: recorder)
+
+        wf.start(device_id=None, device_label="デバイスなし")
+
+        assert "⚠ バッファサイズ 64 フレームで録音できません" in spy.logs
+
 
 class TestRecordingWorkflowStopAndProcess:
     def test_record_only_mode_skips_transcription(
         self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
     ):
         saved = tmp_path / "out.wav"
-        monkeypatch.setattr(workflow_module, "save_wav", lambda data, dest: saved)
+        monkeypatch.setattr(
+            workflow_module,
+            "save_wav",
+            lambda data, dest, sample_rate, audio_format, **kwargs: saved,
+        )
 
         def fail_transcribe(*args, **kwargs):
             raise AssertionError("RECORD_ONLY では transcribe_and_save が呼ばれてはならない")
@@ -180,11 +247,15 @@ class TestRecordingWorkflowStopAndProcess:
     ):
         saved_wav = tmp_path / "out.wav"
         saved_note = tmp_path / "out.md"
-        monkeypatch.setattr(workflow_module, "save_wav", lambda data, dest: saved_wav)
+        monkeypatch.setattr(
+            workflow_module,
+            "save_wav",
+            lambda data, dest, sample_rate, audio_format, **kwargs: saved_wav,
+        )
         monkeypatch.setattr(
             workflow_module,
             "transcribe_and_save",
-            lambda audio_file, config, progress_callback=None: saved_note,
+            lambda audio_file, config, progress_callback=None, recorded_at=None: saved_note,
         )
 
         spy = SpyCallbacks()
@@ -200,8 +271,60 @@ class TestRecordingWorkflowStopAndProcess:
         assert spy.done == [saved_note]
         assert spy.record_only_done == []
 
+    def test_passes_recording_start_time_to_transcription(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        received: list = []
+
+        def fake_transcribe(audio_file, config, progress_callback=None, recorded_at=None):
+            received.append(recorded_at)
+            return tmp_path / "out.md"
+
+        monkeypatch.setattr(
+            workflow_module,
+            "save_wav",
+            lambda data, dest, sample_rate, audio_format, **kwargs: tmp_path / "out.wav",
+        )
+        monkeypatch.setattr(workflow_module, "transcribe_and_save", fake_transcribe)
+
+        spy = SpyCallbacks()
+        recorder = FakeRecorder()
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build(), recorder_factory=lambda d: recorder)
+
+        monkeypatch.setattr(workflow_module.threading, "Thread", DeferredThread)
+        wf.start(device_id=None, device_label="デバイスなし")
+
+        monkeypatch.setattr(workflow_module.threading, "Thread", ImmediateThread)
+        wf.stop_and_process(tmp_path, MODE_RECORD_TRANSCRIBE)
+
+        assert received == [datetime(2026, 1, 16, 9, 0, 0)]
+
+    def test_saves_wav_at_recorder_sample_rate(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        saved_rates: list[int] = []
+
+        def fake_save_wav(data, dest, sample_rate, audio_format, progress_callback=None):
+            saved_rates.append(sample_rate)
+            return tmp_path / "out.wav"
+
+        monkeypatch.setattr(workflow_module, "save_wav", fake_save_wav)
+
+        spy = SpyCallbacks()
+        recorder = FakeRecorder(data=[0.0] * 48000, sample_rate=48000)
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build(), recorder_factory=lambda d: recorder)
+
+        monkeypatch.setattr(workflow_module.threading, "Thread", DeferredThread)
+        wf.start(device_id=None, device_label="デバイスなし")
+
+        monkeypatch.setattr(workflow_module.threading, "Thread", ImmediateThread)
+        wf.stop_and_process(tmp_path, MODE_RECORD_ONLY)
+
+        assert saved_rates == [48000]
+        assert any("1.0秒" in msg for msg in spy.logs)
+
     def test_save_wav_failure_reports_error(self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path):
-        def fail_save_wav(data, dest):
+        def fail_save_wav(data, dest, sample_rate, audio_format, progress_callback=None):
             raise OSError("ディスク書き込みエラー")
 
         monkeypatch.setattr(workflow_module, "save_wav", fail_save_wav)
@@ -286,7 +409,7 @@ class TestRecordingWorkflowTranscribeOnly:
         monkeypatch.setattr(
             workflow_module,
             "transcribe_and_save",
-            lambda audio_file, config, progress_callback=None: saved_note,
+            lambda audio_file, config, progress_callback=None, recorded_at=None: saved_note,
         )
 
         spy = SpyCallbacks()
@@ -313,6 +436,24 @@ class TestRecordingWorkflowTranscribeOnly:
         assert spy.done == []
         assert any("文字起こしエラー" in msg for msg in spy.errors)
 
+    def test_empty_transcription_is_reported_without_saving(
+        self, monkeypatch: pytest.MonkeyPatch, tmp_path: Path
+    ):
+        monkeypatch.setattr(workflow_module.threading, "Thread", ImmediateThread)
+
+        def empty(*args, **kwargs):
+            raise EmptyTranscriptionError(TranscriptionResult(text="", model="small"))
+
+        monkeypatch.setattr(workflow_module, "transcribe_and_save", empty)
+
+        spy = SpyCallbacks()
+        wf = RecordingWorkflow(VoiceNoteConfig(), spy.build())
+
+        wf.run_transcribe_only(tmp_path / "in.wav")
+
+        assert spy.done == []
+        assert spy.errors == ["文字起こし結果が空のため、ノートを保存しませんでした"]
+
 
 class TestRecordingWorkflowShutdown:
     def test_shutdown_stops_recorder_and_clears_state(self, monkeypatch: pytest.MonkeyPatch):
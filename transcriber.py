@@ -3,144 +3,1110 @@
 faster-whisperを使用したローカル文字起こし、またはOpenAI APIを使用したクラウド文字起こし
 """
 
+import importlib.metadata
+import itertools
+import logging
+import os
+import platform
+import re
+import sys
 import tempfile
-from collections.abc import Callable
+import time
+import wave
+from collections.abc import Callable, Iterator
+from concurrent.futures import ThreadPoolExecutor
+from dataclasses import asdict, dataclass, replace
+from datetime import datetime, timedelta
+from functools import lru_cache
 from pathlib import Path
 
 import numpy as np
 
-from config import VoiceNoteConfig, resolve_api_key
+from config import (
+    MODEL_DOWNLOAD_MB,
+    RESAMPLE_QUALITIES,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    builtin_beam_size_notice,
+    home_dir,
+    is_english_only_model,
+    model_language_warning,
+    resolve_api_key,
+    resolve_model_settings,
+)
+from disk_space import ensure_free_space
+from errors import (
+    ModelDownloadError,
+    ModelDownloadTimeoutError,
+    ModelLoadError,
+    TranscriptionError,
+    VoiceNoteError,
+)
+from formatter import remove_filler_words
+
+_logger = logging.getLogger("voicenote")
 
 PAUSE_THRESHOLD = 2.0
+# faster-whisper 既定の temperature フォールバック
+DEFAULT_TEMPERATURES = (0.0, 0.2, 0.4, 0.6, 0.8, 1.0)
 TARGET_SAMPLE_RATE = 16000
+OPENAI_MODEL = "whisper-1"
+# リサンプリングフィルタが安定して動作する最小サンプル数
+MIN_RESAMPLE_SAMPLES = 256
+# 品質ごとのFIRフィルタ半長（変換比の大きい方に対する倍率）。"high" は resample_poly の既定値
+_FILTER_HALF_LEN = {"balanced": 4, "high": 10}
+# CPU（int8・beam_size 5）で音声1秒の文字起こしにかかるおおよその秒数（手元の計測による概算）。
+# `.en` モデルは同じサイズの多言語モデルと同じ値を使う
+REALTIME_FACTORS = {
+    "tiny": 0.05,
+    "base": 0.1,
+    "small": 0.3,
+    "medium": 0.8,
+    "large-v2": 1.6,
+    "large-v3": 1.6,
+}
+# greedy（beam_size 1）の所要時間のビームサーチに対する割合
+GREEDY_TIME_RATIO = 0.6
+# detect_languages での言語判定に使う冒頭の秒数（Whisper の判定は最初の30秒を見る）
+LANGUAGE_DETECTION_SECONDS = 30
+# multilingual で言語を判定する最短の区間（秒）。これより短い区間は判定が不安定なため直前の言語を使う
+MULTILINGUAL_MIN_DETECT_SECONDS = 2.0
+# 文字起こし中に残り時間を通知する進捗の刻み（%）
+PROGRESS_STEP_PERCENT = 10
+
+
+@dataclass(frozen=True)
+class Segment:
+    """タイムスタンプ付きの文字起こし区間（秒単位）。"""
+
+    start: float
+    end: float
+    text: str
+    # multilingual で区間ごとに判定した言語（それ以外は None）
+    language: str | None = None
+
+    def to_dict(self) -> dict:
+        """JSON 保存用の dict に変換する。言語が判定されていなければ含めない。"""
+        data = asdict(self)
+        if self.language is None:
+            del data["language"]
+        return data
+
+
+class PartialTranscriptionError(TranscriptionError):
+    """文字起こしが途中で失敗した場合に送出する。それまでに得られたセグメントを保持する。"""
+
+    def __init__(self, message: str, segments: tuple[Segment, ...]):
+        super().__init__(message)
+        self.segments = segments
+
+
+@dataclass(frozen=True)
+class TranscriptionResult:
+    """文字起こし結果と、ノートの frontmatter に載せるメタデータ。"""
+
+    text: str
+    model: str
+    language: str | None = None
+    duration: float | None = None
+    sample_rate: int | None = None
+    segments: tuple[Segment, ...] = ()
+
+    def metadata(self) -> dict[str, str | int | float]:
+        """frontmatter 用のメタデータを返す。値が取得できなかった項目は含めない。"""
+        values = {
+            "duration": round(self.duration, 1) if self.duration is not None else None,
+            "model": self.model,
+            "language": self.language,
+            "sample_rate": self.sample_rate,
+        }
+        return {k: v for k, v in values.items() if v is not None}
+
+
+def _read_sample_rate(audio_path: Path) -> int | None:
+    """音声ファイルの元のサンプルレートを読む。読めない場合は None。
+
+    WAV はヘッダを直接読み、それ以外（MP3/M4A/FLAC など）は PyAV でストリーム情報を読む。
+    """
+    if audio_path.suffix.lower() == ".wav":
+        try:
+            with wave.open(str(audio_path), "rb") as wav:
+                return wav.getframerate()
+        except (wave.Error, OSError, EOFError):
+            return None
+    try:
+        import av
+
+        with av.open(str(audio_path)) as container:
+            return container.streams.audio[0].sample_rate
+    except Exception:
+        # PyAV はコンテナ・コーデックごとに様々な例外を送出するため、まとめて「不明」扱いにする
+        return None
+
+
+def _read_wav(
+    audio_path: Path, notify: Callable[[str], None] | None = None
+) -> tuple[int, np.ndarray]:
+    """WAV を scipy で読み込む。ヘッダより短い（途中で途切れた）ファイルは欠けた長さを警告する。
+
+    scipy は途切れたファイルも読めた分だけ返すため、ヘッダのフレーム数と比べて気付けるようにする。
+
+    Raises:
+        ValueError: 破損していて読み込めない場合（チャンネルの途中で途切れているなど）
+    """
+    from scipy.io import wavfile
+
+    try:
+        sample_rate, data = wavfile.read(str(audio_path))
+    except ValueError as e:
+        raise ValueError(
+            f"WAV ファイルを読み込めません（破損している可能性があります）: {e}"
+        ) from e
+    missing = _wav_frame_count(audio_path) - len(data)
+    if missing > 0:
+        message = (
+            f"⚠ WAV ファイルが途中で途切れています（{missing}サンプル・約{missing / sample_rate:.1f}秒"
+            "が欠けています）。読み込めた部分だけを文字起こしします"
+        )
+        _logger.warning(message)
+        if notify:
+            notify(message)
+    return sample_rate, data
+
+
+def _wav_frame_count(audio_path: Path) -> int:
+    """WAV ヘッダに記録されたフレーム数。PCM 以外などヘッダを読めない場合は 0。"""
+    try:
+        with wave.open(str(audio_path), "rb") as wav:
+            return wav.getnframes()
+    except (wave.Error, OSError, EOFError):
+        return 0
+
+
+def _decode_audio(audio_path: Path) -> np.ndarray:
+    """圧縮音声（MP3/M4A/FLAC/OGG など）を 16kHz モノラルの float32 にデコードする。
+
+    faster-whisper 同梱の PyAV ベースのデコーダを使うため、追加の依存は不要。
+    """
+    from faster_whisper import decode_audio
+
+    return decode_audio(str(audio_path), sampling_rate=TARGET_SAMPLE_RATE)
+
+
+def _apply_highpass(data: np.ndarray, sample_rate: int, cutoff_hz: float) -> np.ndarray:
+    """
+    2次バターワース（biquad）ハイパスフィルタで低域ノイズを除去する。
+    空調音や機材を触るノイズなど、Whisperが誤認識しやすい低域の成分を落とす。
+
+    Args:
+        data: モノラルの音声サンプル
+        sample_rate: サンプルレート
+        cutoff_hz: カットオフ周波数（ナイキスト周波数未満）
+
+    Returns:
+        フィルタ適用後のサンプル（float64）
+    """
+    from scipy.signal import butter, sosfilt
+
+    sos = butter(2, cutoff_hz, btype="highpass", fs=sample_rate, output="sos")
+    return sosfilt(sos, data.astype(np.float64))
+
+
+def _resample(
+    data: np.ndarray,
+    sample_rate: int,
+    quality: str = "high",
+    resampler: dict[str, float] | None = None,
+) -> np.ndarray:
+    """
+    モノラルの音声サンプルを16kHzにリサンプリングする。
+    空の入力は空のまま返し、極端に短い入力はゼロ埋めしてから変換する。
+
+    Args:
+        data: モノラルの音声サンプル
+        sample_rate: 入力のサンプルレート
+        quality: "fast"（線形補間）/ "balanced"（短いFIR）/ "high"（従来の高品質FIR）
+        resampler: FIRフィルタの half_length / cutoff / kaiser_beta の上書き（"fast" では使わない）
+
+    Returns:
+        16kHzのサンプル
+
+    Raises:
+        ValueError: quality が不正な場合
+    """
+    if quality not in RESAMPLE_QUALITIES:
+        raise ValueError(f"resample_quality は {', '.join(RESAMPLE_QUALITIES)} のいずれかです")
+    if sample_rate == TARGET_SAMPLE_RATE:
+        return data
+    if len(data) == 0:
+        return np.zeros(0, dtype=np.float64)
+
+    from math import ceil, gcd
+
+    from scipy.signal import firwin, resample_poly
+
+    g = gcd(TARGET_SAMPLE_RATE, sample_rate)
+    up, down = TARGET_SAMPLE_RATE // g, sample_rate // g
+    expected_len = ceil(len(data) * up / down)
+
+    if quality == "fast":
+        src_times = np.arange(len(data)) / sample_rate
+        dst_times = np.arange(expected_len) / TARGET_SAMPLE_RATE
+        return np.interp(dst_times, src_times, data)
+
+    # resample_poly 内部と同じ設計式で、品質に応じてフィルタ長だけを変える
+    resampler = resampler or {}
+    max_rate = max(up, down)
+    half_len = resampler.get("half_length", _FILTER_HALF_LEN[quality]) * max_rate
+    cutoff = resampler.get("cutoff", 1.0) / max_rate
+    taps = firwin(2 * half_len + 1, cutoff, window=("kaiser", resampler.get("kaiser_beta", 5.0)))
+
+    if len(data) < MIN_RESAMPLE_SAMPLES:
+        # 短すぎる入力は末尾をゼロ埋めして変換し、元の長さ相当に切り詰める
+        padded = np.pad(data.astype(np.float64), (0, MIN_RESAMPLE_SAMPLES - len(data)))
+        return resample_poly(padded, up, down, window=taps)[:expected_len]
+
+    return resample_poly(data, up, down, window=taps)
 
 
-def _preprocess_audio(audio_path: Path) -> Path:
+def _create_temp_wav(
+    temp_dir: str | None = None, notify: Callable[[str], None] | None = None
+) -> Path:
+    """
+    前処理済みWAVを書き出す一時ファイルを作成する。
+    temp_dir に書き込めない場合はシステムの一時ディレクトリにフォールバックする。
+
+    Args:
+        temp_dir: 一時ファイルの作成先（None ならシステムの一時ディレクトリ）
+        notify: フォールバック時の警告を受け取るコールバック
+
+    Returns:
+        作成した（空の）一時ファイルのパス。削除は呼び出し元が行う。
+    """
+    tmp = None
+    if temp_dir:
+        try:
+            target = Path(temp_dir).expanduser()
+            target.mkdir(parents=True, exist_ok=True)
+            tmp = tempfile.NamedTemporaryFile(suffix=".wav", delete=False, dir=target)  # noqa: SIM115
+        except OSError as e:
+            if notify:
+                notify(f"⚠ 一時フォルダに書き込めないため既定の場所を使用します: {e}")
+    if tmp is None:
+        tmp = tempfile.NamedTemporaryFile(suffix=".wav", delete=False)  # noqa: SIM115
+    tmp.close()
+    return Path(tmp.name)
+
+
+def _preprocess_audio(
+    audio_path: Path,
+    highpass_hz: float = 0.0,
+    resample_quality: str = "high",
+    resampler: dict[str, float] | None = None,
+    temp_dir: str | None = None,
+    notify: Callable[[str], None] | None = None,
+) -> Path:
     """
     音声ファイルをWhisper最適形式（16kHz・モノラル）に変換する。
     変換が不要な場合は元のパスをそのまま返す。
 
+    WAV は scipy で読み込み、それ以外の形式はハイパスフィルタが必要な場合のみ
+    `_decode_audio` でデコードする（不要なら faster-whisper が内部でデコードする）。
+
     Args:
         audio_path: 入力音声ファイルパス
+        highpass_hz: ハイパスフィルタのカットオフ周波数（0で無効）
+        resample_quality: リサンプリング品質（"fast"/"balanced"/"high"）
+        resampler: リサンプリングフィルタのパラメータの上書き（設定の resampler）
+        temp_dir: 変換後WAVの書き出し先（None ならシステムの一時ディレクトリ）
+        notify: 警告メッセージを受け取るコールバック
 
     Returns:
         変換後（または元）のファイルパス
     """
     from scipy.io import wavfile
 
-    if audio_path.suffix.lower() != ".wav":
+    if audio_path.suffix.lower() == ".wav":
+        sample_rate, data = _read_wav(audio_path, notify)
+    elif highpass_hz > 0:
+        sample_rate, data = TARGET_SAMPLE_RATE, _decode_audio(audio_path)
+    else:
         return audio_path
 
-    sample_rate, data = wavfile.read(str(audio_path))
-
     needs_resample = sample_rate != TARGET_SAMPLE_RATE
     needs_mono = data.ndim > 1
+    needs_highpass = highpass_hz > 0
 
-    if not needs_resample and not needs_mono:
+    if not needs_resample and not needs_mono and not needs_highpass:
         return audio_path
 
-    # モノラル変換
+    # モノラル変換（チャンネル数によらず全チャンネルを平均する）
     if needs_mono:
         data = data.mean(axis=1)
 
+    # ハイパスフィルタ（リサンプリング前の元レートで適用）
+    if needs_highpass:
+        data = _apply_highpass(data, sample_rate, highpass_hz)
+
     # リサンプリング
     if needs_resample:
-        from math import gcd
-
-        from scipy.signal import resample_poly
-
-        g = gcd(TARGET_SAMPLE_RATE, sample_rate)
-        data = resample_poly(data, TARGET_SAMPLE_RATE // g, sample_rate // g)
+        data = _resample(data, sample_rate, resample_quality, resampler)
 
     # float → int16に変換（wavfile書き込みのため）
     if data.dtype != np.int16:
-        max_val = np.max(np.abs(data))
+        max_val = np.max(np.abs(data)) if data.size else 0
         data = (data / max_val * 32767).astype(np.int16) if max_val > 0 else data.astype(np.int16)
 
-    # 呼び出し元で transcribe 後に unlink するため delete=False で開いて閉じる
-    tmp = tempfile.NamedTemporaryFile(suffix=".wav", delete=False)  # noqa: SIM115
-    tmp_path = Path(tmp.name)
-    tmp.close()
+    # 呼び出し元で transcribe 後に unlink する
+    tmp_path = _create_temp_wav(temp_dir, notify)
     wavfile.write(str(tmp_path), TARGET_SAMPLE_RATE, data)
     return tmp_path
 
 
-def _merge_segments(segments) -> str:
-    """セグメントを結合する（2秒以上の間隔で段落分け）"""
+def _temperature(config: VoiceNoteConfig) -> float | tuple[float, ...]:
+    """faster-whisper に渡す temperature を返す。
+
+    未指定なら faster-whisper 既定のフォールバック（0.0 で失敗と判定されたら 0.2 ずつ上げて
+    やり直す）を使い、指定されていればその値に固定する（0 なら常に決定的な出力）。
+    """
+    if config.temperature is None:
+        return DEFAULT_TEMPERATURES
+    return config.temperature
+
+
+def _decode_options(config: VoiceNoteConfig) -> dict:
+    """model.transcribe に渡すデコードの設定（言語・VAD 以外）を組み立てる。"""
+    return {
+        "beam_size": config.beam_size,
+        "best_of": config.best_of,
+        "temperature": _temperature(config),
+        "condition_on_previous_text": False,
+        "no_speech_threshold": config.no_speech_threshold,
+        # -1 は記号・効果音などの非音声トークンをまとめて抑制する faster-whisper の指定
+        "suppress_tokens": [-1] if config.suppress_non_speech else [],
+    }
+
+
+def _vad_parameters(config: VoiceNoteConfig) -> dict | None:
+    """VAD による音声区間分割のパラメータを組み立てる。VAD 無効時は None。
+
+    faster-whisper は区間ごとに文字起こしし、タイムスタンプを元音声の時刻に戻して返すため、
+    区間をまたいでもタイムスタンプは連続する。
+    """
+    if not config.vad_filter:
+        return None
+    params: dict = {"min_silence_duration_ms": config.vad_min_silence_ms}
+    if config.vad_max_chunk_seconds > 0:
+        params["max_speech_duration_s"] = config.vad_max_chunk_seconds
+    return params
+
+
+# surrogateescape で表された生のバイト（U+DC80〜U+DCFF）以外のサロゲート
+_STRAY_SURROGATE_RE = re.compile("[\ud800-\udc7f\udd00-\udfff]")
+
+
+def sanitize_text(text: str) -> tuple[str, int]:
+    """不正な UTF-8 に由来する文字を取り除き、(修復したテキスト, 取り除いた文字数) を返す。
+
+    surrogateescape で表された生のバイトは UTF-8 として読み直し、マルチバイト文字として
+    正しく並んでいれば元の文字に戻す。戻せないバイト（途中で切れたマルチバイト文字や孤立した
+    継続バイト）・孤立したサロゲート・置換文字（U+FFFD）は取り除く。
+    """
+    text = _STRAY_SURROGATE_RE.sub("\ufffd", text)
+    text = text.encode("utf-8", "surrogateescape").decode("utf-8", "replace")
+    return text.replace("\ufffd", ""), text.count("\ufffd")
+
+
+def _collect_segments(
+    raw_segments,
+    on_segment: Callable[[Segment], None] | None = None,
+    on_warning: Callable[[str], None] | None = None,
+) -> tuple[Segment, ...]:
+    """faster-whisper / OpenAI のセグメントを Segment に変換する。
+
+    faster-whisper のセグメントはジェネレータで逐次デコードされるため、途中で失敗しても
+    それまでのセグメントを `PartialTranscriptionError` に載せて失わないようにする。
+    本文の壊れた文字は sanitize_text で取り除き、取り除いた数を on_warning に通知する。
+    """
+    collected: list[Segment] = []
+    removed = 0
+    try:
+        for s in raw_segments:
+            language = getattr(s, "language", None)
+            text, count = sanitize_text(s.text.strip())
+            removed += count
+            segment = Segment(start=s.start, end=s.end, text=text.strip(), language=language)
+            collected.append(segment)
+            if on_segment:
+                on_segment(segment)
+    except Exception as e:
+        raise PartialTranscriptionError(f"文字起こしエラー: {e}", tuple(collected)) from e
+    if removed:
+        message = f"文字起こし結果から壊れた文字（不正な UTF-8）を{removed}個取り除きました"
+        _logger.warning(message)
+        if on_warning:
+            on_warning(f"⚠ {message}")
+    return tuple(collected)
+
+
+def _language_tagged(segments) -> list[tuple[Segment, str, bool]]:
+    """言語が前のセグメントから変わった箇所の本文に `[言語]` の目印を付ける。
+
+    (セグメント, 本文, 言語が変わったか) を返す。言語の無い（multilingual 以外の）セグメントは
+    本文をそのまま使う。
+    """
+    tagged = []
+    prev_language = None
+    for segment in segments:
+        text = segment.text.strip()
+        switched = bool(text) and segment.language not in (None, prev_language)
+        if switched:
+            text = f"[{segment.language}] {text}"
+            prev_language = segment.language
+        tagged.append((segment, text, switched))
+    return tagged
+
+
+def _merge_segments(segments, separator: str = "pause") -> str:
+    """セグメントを結合する。
+
+    separator が "pause"（既定）なら2秒以上の間隔で段落分けし、それ以外は1セグメントごとに
+    空行（"blank"）・改行（"single"）・`- ` の箇条書き（"bullet"）で区切る。
+    multilingual で言語が変わった箇所には `[en]` のような目印を付け、"pause" では段落も分ける。
+    """
+    tagged = _language_tagged(segments)
+    if separator != "pause":
+        lines = [text for _, text, _ in tagged if text]
+        if separator == "bullet":
+            lines = [f"- {line}" for line in lines]
+        return ("\n\n" if separator == "blank" else "\n").join(lines)
     result_parts = []
     prev_end = 0.0
-    for segment in segments:
+    for segment, text, switched in tagged:
         gap = segment.start - prev_end
-        if result_parts and gap >= PAUSE_THRESHOLD:
+        if result_parts and (gap >= PAUSE_THRESHOLD or switched):
             result_parts.append("\n\n")
         elif result_parts:
             result_parts.append(" ")
-        result_parts.append(segment.text.strip())
+        result_parts.append(text)
         prev_end = segment.end
     return "".join(result_parts).strip()
 
 
+def _normalize_for_dedupe(text: str) -> str:
+    """重複判定用に、大文字小文字・空白・句読点などの記号の違いを無視した文字列にする。"""
+    return "".join(c for c in text.lower() if c.isalnum())
+
+
+def dedupe_segments(segments) -> tuple[Segment, ...]:
+    """同じ（正規化すると一致する）テキストが連続するセグメントを1つにまとめる。
+
+    無音・ノイズ区間で Whisper が同じフレーズを繰り返す（ハルシネーション）対策。
+    まとめたセグメントの終了時刻は連続の最後のものに合わせる。
+    """
+    deduped: list[Segment] = []
+    prev_key = None
+    for segment in segments:
+        key = _normalize_for_dedupe(segment.text)
+        if deduped and key == prev_key:
+            deduped[-1] = replace(deduped[-1], end=segment.end)
+        else:
+            deduped.append(segment)
+        prev_key = key
+    return tuple(deduped)
+
+
+def filter_hallucinations(
+    segments, phrases: list[str], scope: str
+) -> tuple[tuple[Segment, ...], tuple[Segment, ...]]:
+    """既知のハルシネーションの定型句だけのセグメントを取り除き、(残す, 除いた) を返す。
+
+    比較は dedupe と同じく大文字小文字・空白・句読点の違いを無視する。
+    scope が "last" なら末尾に続くセグメントだけ、"all" ならすべてのセグメントが対象。
+    """
+    known = {_normalize_for_dedupe(p) for p in phrases} - {""}
+    kept = list(segments)
+    if scope == "last":
+        cut = len(kept)
+        while cut > 0 and _normalize_for_dedupe(kept[cut - 1].text) in known:
+            cut -= 1
+        return tuple(kept[:cut]), tuple(kept[cut:])
+    removed = tuple(s for s in kept if _normalize_for_dedupe(s.text) in known)
+    return tuple(s for s in kept if s not in removed), removed
+
+
+def remove_fillers(segments, words: list[str]) -> tuple[Segment, ...]:
+    """セグメントの本文から、単独で現れるフィラー（「えーと、」など）を取り除く。
+
+    規則は rule 整形と同じ（formatter.remove_filler_words）。フィラーだけのセグメントは取り除く。
+    """
+    if not any(w.strip() for w in words):
+        return tuple(segments)
+    cleaned: list[Segment] = []
+    for segment in segments:
+        text = remove_filler_words(segment.text, words)
+        if text:
+            cleaned.append(replace(segment, text=text))
+    return tuple(cleaned)
+
+
+def split_chapters(
+    segments, min_gap: float, separator: str = "pause"
+) -> list[tuple[float, str]]:
+    """セグメント間の無音が min_gap 秒以上の箇所で区切り、(開始秒, 本文) の章に分ける。
+
+    章の本文は separator（segment_separator）でセグメントを結合する。
+    """
+    chapters = _group_by_gap(segments, min_gap)
+    return [(chapter[0].start, _merge_segments(chapter, separator)) for chapter in chapters]
+
+
+def split_result(
+    result: TranscriptionResult, min_gap: float, separator: str = "pause"
+) -> list[TranscriptionResult]:
+    """セグメント間の無音が min_gap 秒以上の箇所で、結果を別々のノート用の結果に分ける。
+
+    各部分の本文は separator（segment_separator）でセグメントを結合し、duration はその部分の
+    最初のセグメントの開始から最後のセグメントの終了までの長さにする。セグメントの時刻は
+    元の録音のまま（録音へのリンクや `[mm:ss]` が録音の位置と合うように）。
+    セグメントが無い・分ける箇所が無い場合は result だけを返す。
+    """
+    groups = _group_by_gap(result.segments, min_gap)
+    if len(groups) <= 1:
+        return [result]
+    return [
+        replace(
+            result,
+            text=_merge_segments(group, separator),
+            duration=group[-1].end - group[0].start,
+            segments=tuple(group),
+        )
+        for group in groups
+    ]
+
+
+def _group_by_gap(segments, min_gap: float) -> list[list[Segment]]:
+    """セグメント間の無音が min_gap 秒以上の箇所でセグメントを分ける。"""
+    groups: list[list[Segment]] = []
+    prev_end = 0.0
+    for segment in segments:
+        if not groups or segment.start - prev_end >= min_gap:
+            groups.append([])
+        groups[-1].append(segment)
+        prev_end = segment.end
+    return groups
+
+
+def package_version(name: str) -> str:
+    """インストール済みパッケージのバージョンを返す。見つからなければ "未インストール"。"""
+    try:
+        return importlib.metadata.version(name)
+    except importlib.metadata.PackageNotFoundError:
+        return "未インストール"
+
+
+def backend_info() -> dict[str, str]:
+    """バグ報告用に、文字起こしバックエンドのバージョンと利用可能な計算資源を返す。
+
+    ローカルモードは常に CPU (int8) で実行するため、CUDA デバイス数は参考情報。
+    """
+    info = {
+        "voicenote": package_version("voicenote"),
+        "python": f"{platform.python_version()} ({platform.system()} {platform.machine()})",
+        "faster-whisper": package_version("faster-whisper"),
+        "ctranslate2": package_version("ctranslate2"),
+        "openai": package_version("openai"),
+    }
+    try:
+        import ctranslate2
+
+        info["cuda_devices"] = str(ctranslate2.get_cuda_device_count())
+        info["cpu_compute_types"] = ", ".join(
+            sorted(ctranslate2.get_supported_compute_types("cpu"))
+        )
+    except Exception as e:
+        info["cuda_devices"] = f"取得できません ({e})"
+    return info
+
+
+def model_cache_dir() -> Path:
+    """faster-whisper（Hugging Face Hub）がモデルをダウンロードするフォルダを返す。
+
+    ホームフォルダが決められない環境では XDG_CACHE_HOME、それも無ければカレントフォルダの
+    .cache を使う。
+    """
+    if os.environ.get("HF_HUB_CACHE"):
+        return Path(os.environ["HF_HUB_CACHE"]).expanduser()
+    if os.environ.get("HF_HOME"):
+        return Path(os.environ["HF_HOME"]).expanduser() / "hub"
+    home = home_dir()
+    if home is not None:
+        return home / ".cache" / "huggingface" / "hub"
+    xdg = os.environ.get("XDG_CACHE_HOME", "").strip()
+    cache = Path(xdg) if xdg else Path.cwd() / ".cache"
+    return cache / "huggingface" / "hub"
+
+
+# apply_download_timeout が設定する環境変数と、対応する huggingface_hub.constants の定数
+_DOWNLOAD_TIMEOUT_ENV = {
+    "HF_HUB_DOWNLOAD_TIMEOUT": "HF_HUB_DOWNLOAD_TIMEOUT",
+    "HF_HUB_ETAG_TIMEOUT": "DEFAULT_ETAG_TIMEOUT",
+}
+# apply_download_timeout が最後に書き込んだ環境変数の値（ユーザーが設定した値と区別する）
+_applied_download_timeouts: dict[str, str] = {}
+
+
+def apply_download_timeout(seconds: float):
+    """モデルのダウンロード（Hugging Face Hub）の接続・受信のタイムアウトを seconds 秒にする。
+
+    huggingface_hub は環境変数をインポート時に読むため、インポート済みなら定数も書き換える。
+    途切れた接続で待ち続けず、この秒数で失敗させる。
+    ユーザーが環境変数（HF_HUB_DOWNLOAD_TIMEOUT / HF_HUB_ETAG_TIMEOUT）を設定している場合は
+    そちらを優先し、書き換えない。
+    """
+    value = f"{seconds:g}"
+    constants = sys.modules.get("huggingface_hub.constants")
+    for env_name, constant_name in _DOWNLOAD_TIMEOUT_ENV.items():
+        current = os.environ.get(env_name)
+        if current is not None and current != _applied_download_timeouts.get(env_name):
+            continue
+        os.environ[env_name] = value
+        _applied_download_timeouts[env_name] = value
+        if constants is not None:
+            setattr(constants, constant_name, seconds)
+
+
+def _is_timeout(error: BaseException) -> bool:
+    """例外（またはその原因の例外）がタイムアウトか。
+
+    requests・urllib3・httpx のタイムアウト例外を、それらをインポートせずに名前で判定する。
+    """
+    seen: set[int] = set()
+    current: BaseException | None = error
+    while current is not None and id(current) not in seen:
+        seen.add(id(current))
+        if isinstance(current, TimeoutError) or any(
+            "Timeout" in cls.__name__ for cls in type(current).__mro__
+        ):
+            return True
+        current = current.__cause__ or current.__context__
+    return False
+
+
+def is_model_downloaded(model_name: str) -> bool:
+    """モデルがダウンロード済み（またはローカルのフォルダを指定したもの）か。"""
+    if Path(model_name).expanduser().is_dir():
+        return True
+    # faster-whisper は Systran/faster-whisper-{モデル名} を Hugging Face Hub からダウンロードする
+    return (model_cache_dir() / f"models--Systran--faster-whisper-{model_name}").is_dir()
+
+
+def ensure_model_disk_space(config: VoiceNoteConfig):
+    """未ダウンロードのモデルなら、ダウンロード先にモデルのサイズ + min_free_disk_mb の空きがあるか確認する。
+
+    ローカルのフォルダを指定したモデルや、サイズの分からないモデルは確認しない。
+
+    Raises:
+        InsufficientDiskSpaceError: 空き容量が足りない場合
+    """
+    size_mb = MODEL_DOWNLOAD_MB.get(config.whisper_model)
+    if size_mb is None or config.min_free_disk_mb <= 0:
+        return
+    if is_model_downloaded(config.whisper_model):
+        return
+    ensure_free_space(
+        model_cache_dir(),
+        size_mb + config.min_free_disk_mb,
+        f"モデル '{config.whisper_model}' のダウンロード",
+    )
+
+
+def _model_load_error(
+    error: Exception, config: VoiceNoteConfig, downloaded: bool
+) -> ModelLoadError:
+    """モデルのロードで起きた例外を、ダウンロード済みか・タイムアウトかで分けた例外にする。"""
+    model_name = config.whisper_model
+    if downloaded:
+        return ModelLoadError(f"モデル '{model_name}' をロードできません: {error}")
+    if _is_timeout(error):
+        return ModelDownloadTimeoutError(
+            f"モデル '{model_name}' のダウンロードが "
+            f"{config.model_download_timeout:g} 秒応答しなかったため中断しました: {error}"
+            "（回線を確認して再実行するか、model_download_timeout を延ばしてください）"
+        )
+    return ModelDownloadError(
+        f"モデル '{model_name}' をダウンロードできません: {error}"
+        "（ネットワーク接続と Hugging Face Hub へのアクセスを確認してください）"
+    )
+
+
+def preload_model(config: VoiceNoteConfig):
+    """設定のモデルを transcribe_audio と同じ手順でロードしておく（--loop の開始時など）。
+
+    ダウンロード先の空き容量を確認し、未ダウンロードなら model_download_timeout を適用してから
+    ロードする。ロードしたモデルは load_model にキャッシュされ、続く文字起こしで使い回される。
+
+    Raises:
+        ModelDownloadTimeoutError: モデルのダウンロードが model_download_timeout 秒止まった時
+        ModelDownloadError: モデルをダウンロードできない時
+        ModelLoadError: ダウンロード済みのモデルをロードできない時
+        InsufficientDiskSpaceError: モデルのダウンロード先の空き容量が足りない時
+    """
+    config = resolve_model_settings(config)
+    ensure_model_disk_space(config)
+    downloaded = is_model_downloaded(config.whisper_model)
+    if not downloaded:
+        apply_download_timeout(config.model_download_timeout)
+    try:
+        return load_model(config.whisper_model, config.cpu_threads)
+    except Exception as e:
+        raise _model_load_error(e, config, downloaded) from e
+
+
+def estimate_transcription_seconds(
+    model_name: str, audio_seconds: float, beam_size: int
+) -> float | None:
+    """ローカルモードの文字起こしにかかる時間の目安（秒）。ローカルパスのモデルなどは None。"""
+    factor = REALTIME_FACTORS.get(model_name.removesuffix(".en"))
+    if factor is None:
+        return None
+    if beam_size <= 1:
+        factor *= GREEDY_TIME_RATIO
+    return audio_seconds * factor
+
+
+def _format_eta(seconds: float) -> str:
+    """所要時間の目安を「約N分」の形で表す。"""
+    if seconds < 60:
+        return "1分未満"
+    return f"約{round(seconds / 60)}分"
+
+
+def detect_language(model, audio_path: Path, candidates: list[str]) -> str | None:
+    """冒頭の音声から言語を判定し、candidates の中で最も確率の高い言語を返す。
+
+    faster-whisper の detect_language が全言語の確率を返すので、候補以外を除いて選ぶ。
+    判定できない場合（候補の言語の確率が無いなど）は None。
+    """
+    audio = _decode_audio(audio_path)[: LANGUAGE_DETECTION_SECONDS * TARGET_SAMPLE_RATE]
+    return _rank_language(model, audio, candidates)
+
+
+def _rank_language(model, audio: np.ndarray, candidates: list[str] | None) -> str | None:
+    """audio の言語を判定し、candidates（None なら全言語）の中で最も確率の高い言語を返す。"""
+    _, _, probabilities = model.detect_language(audio)
+    ranked = [(p, lang) for lang, p in probabilities if candidates is None or lang in candidates]
+    return max(ranked)[1] if ranked else None
+
+
+def _speech_chunks(audio: np.ndarray, config: VoiceNoteConfig) -> list[tuple[int, int]]:
+    """VAD で無音を区切りにした音声区間を (開始, 終了) のサンプル位置で返す。
+
+    vad_filter が無効でも区間分割には VAD を使う（vad_min_silence_ms などの設定に従う）。
+    """
+    from faster_whisper.vad import VadOptions, get_speech_timestamps
+
+    options = VadOptions(**_vad_parameters(replace(config, vad_filter=True)))
+    return [(ts["start"], ts["end"]) for ts in get_speech_timestamps(audio, options)]
+
+
+def _transcribe_multilingual(model, audio: np.ndarray, config: VoiceNoteConfig):
+    """無音で区切った区間ごとに言語を判定して文字起こしし、言語付きの Segment を順に返す。
+
+    言語の候補は detect_languages（空なら全言語）。短い区間は直前の区間の言語で文字起こしする。
+    タイムスタンプは元音声の時刻に戻す。
+    """
+    candidates = config.detect_languages or None
+    language = None
+    for start, end in _speech_chunks(audio, config):
+        chunk = audio[start:end]
+        if language is None or len(chunk) >= MULTILINGUAL_MIN_DETECT_SECONDS * TARGET_SAMPLE_RATE:
+            language = _rank_language(model, chunk, candidates) or language
+        offset = start / TARGET_SAMPLE_RATE
+        raw_segments, _ = model.transcribe(
+            chunk, language=language, vad_filter=False, **_decode_options(config)
+        )
+        for s in raw_segments:
+            yield Segment(offset + s.start, offset + s.end, s.text, language)
+
+
+def _is_pcm16_mono_wav(audio_path: Path) -> bool:
+    """16kHz・モノラル・16bit PCM の WAV（前処理後の一時WAVなど）か。"""
+    if audio_path.suffix.lower() != ".wav":
+        return False
+    try:
+        with wave.open(str(audio_path), "rb") as wav:
+            return (
+                wav.getframerate() == TARGET_SAMPLE_RATE
+                and wav.getnchannels() == 1
+                and wav.getsampwidth() == 2
+            )
+    except (wave.Error, OSError, EOFError):
+        return False
+
+
+def _audio_chunks(
+    audio_path: Path, chunk_seconds: float, overlap_seconds: float
+) -> tuple[float, Iterator[tuple[float, np.ndarray]]]:
+    """16kHz モノラルの音声を区間に分け、(長さ秒, (開始秒, 区間の音声) のイテレータ) を返す。
+
+    区間は chunk_seconds ごとに始まり、overlap_seconds だけ次の区間に重なる
+    （区切りで切れた単語も、次の区間で続けて文字起こしできるようにする）。
+    16bit PCM の WAV は区間ごとに必要な部分だけを読み、長い録音でも全体をメモリに載せない。
+    それ以外の形式は全体をデコードしてから区切るため、音声全体（16kHz の float32）のメモリを使う。
+    リサンプリング・ハイパスフィルタが必要な WAV も、その前の `_preprocess_audio` で全体を読み込む。
+    """
+    step = max(int(chunk_seconds * TARGET_SAMPLE_RATE), 1)
+    span = step + int(overlap_seconds * TARGET_SAMPLE_RATE)
+    if not _is_pcm16_mono_wav(audio_path):
+        audio = _decode_audio(audio_path)
+        chunks = (
+            (start / TARGET_SAMPLE_RATE, audio[start : start + span])
+            for start in range(0, len(audio), step)
+        )
+        return len(audio) / TARGET_SAMPLE_RATE, chunks
+
+    total = _wav_frame_count(audio_path)
+
+    def read_chunks():
+        with wave.open(str(audio_path), "rb") as wav:
+            for start in range(0, total, step):
+                wav.setpos(start)
+                samples = np.frombuffer(wav.readframes(span), dtype="<i2")
+                # 途中で途切れた WAV はヘッダのフレーム数より短い
+                if not len(samples):
+                    return
+                yield start / TARGET_SAMPLE_RATE, samples.astype(np.float32) / 32768.0
+
+    return total / TARGET_SAMPLE_RATE, read_chunks()
+
+
+def _transcribe_chunked(
+    model, audio_path: Path, config: VoiceNoteConfig
+) -> tuple[float, str | None, Iterator[Segment]]:
+    """長い音声を chunk_minutes ごとの、chunk_overlap_seconds ずつ重なる区間に分けて文字起こしする。
+
+    長い録音でもメモリ使用量を抑えられる。
+
+    (長さ秒, 言語, 元音声の時刻に戻した Segment のイテレータ) を返す。言語を指定しない場合は
+    最初の区間で判定し、すべての区間をその言語で文字起こしする。
+    区間の重なりで同じ発話が2回出てこないよう、区間の終わり以降に始まるセグメントは
+    次の区間に任せ、前の区間で採用したセグメントの終わりより前に中心があるセグメントは捨てる。
+    """
+    chunk_seconds = config.chunk_minutes * 60
+    duration, chunks = _audio_chunks(audio_path, chunk_seconds, config.chunk_overlap_seconds)
+    first = next(chunks, None)
+    if first is None:
+        return duration, config.language, iter(())
+    language = config.language or _rank_language(
+        model, first[1][: LANGUAGE_DETECTION_SECONDS * TARGET_SAMPLE_RATE], None
+    )
+
+    def segments():
+        covered = 0.0
+        for offset, chunk in itertools.chain([first], chunks):
+            raw_segments, _ = model.transcribe(
+                chunk,
+                language=language,
+                vad_filter=config.vad_filter,
+                vad_parameters=_vad_parameters(config),
+                **_decode_options(config),
+            )
+            for s in raw_segments:
+                start, end = offset + s.start, offset + s.end
+                if start >= offset + chunk_seconds:
+                    break
+                if (start + end) / 2 < covered:
+                    continue
+                covered = end
+                yield Segment(start, end, s.text)
+
+    return duration, language, segments()
+
+
+@lru_cache(maxsize=1)
+def load_model(model_name: str, cpu_threads: int = 0):
+    """
+    faster-whisperのモデルをロードする。
+    直前と同じモデル・スレッド数は再ロードせずに使い回す（連続録音や GUI での2回目以降を高速化）。
+    cpu_threads が 0 の場合は CTranslate2 の既定スレッド数を使う。
+    """
+    from faster_whisper import WhisperModel
+
+    return WhisperModel(model_name, device="cpu", compute_type="int8", cpu_threads=cpu_threads)
+
+
 def transcribe_audio(
     audio_path: Path,
-    model_name: str,
+    config: VoiceNoteConfig,
     progress_callback: Callable[[str], None] | None = None,
-    vad_filter: bool = True,
-) -> str:
+    on_segment: Callable[[Segment], None] | None = None,
+) -> TranscriptionResult:
     """
     faster-whisperで音声を文字起こしする
 
     Args:
         audio_path: 音声ファイルのパス
-        model_name: 使用するWhisperモデル名
+        config: 設定（`whisper_model` `vad_filter` `vad_min_silence_ms` `vad_max_chunk_seconds`
+            `no_speech_threshold` `suppress_non_speech` `highpass_hz` `resample_quality`
+            `resampler` `temp_dir` `keep_temp` `chunk_minutes` `chunk_overlap_seconds`
+            `model_download_timeout` と、
+            モデル別上書き後の `language` `beam_size` `cpu_threads` を参照）
         progress_callback: 進捗メッセージを受け取るコールバック（GUIから渡す）
-        vad_filter: 音声区間検出フィルタの有効/無効（無音・ノイズを除去してループを抑制）
+        on_segment: セグメントが1つ確定するたびに呼ばれるコールバック
 
     Returns:
-        文字起こしされたテキスト
+        文字起こし結果（テキストと言語・長さなどのメタデータ）
 
     Raises:
-        RuntimeError: 文字起こし失敗時
+        ModelDownloadTimeoutError: モデルのダウンロードが model_download_timeout 秒止まった時
+        ModelDownloadError: モデルをダウンロードできない時
+        ModelLoadError: ダウンロード済みのモデルをロードできない時
+        InsufficientDiskSpaceError: モデルのダウンロード先の空き容量が足りない時
+        PartialTranscriptionError: セグメントのデコード途中で失敗した時
+        TranscriptionError: その他の文字起こし失敗時
     """
 
     def notify(msg: str):
         if progress_callback:
             progress_callback(msg)
 
+    # 小さいモデルの組み込みの既定値は beam_size を下げるため、黙って精度を変えないよう知らせる
+    beam_size_notice = builtin_beam_size_notice(config)
+    if beam_size_notice:
+        _logger.info(beam_size_notice)
+        notify(beam_size_notice)
+    config = resolve_model_settings(config)
+    model_name = config.whisper_model
+    if is_english_only_model(model_name) and config.language != "en":
+        language_warning = model_language_warning(config)
+        if language_warning:
+            notify(f"⚠ {language_warning}")
+        config = replace(config, language="en")
     notify(f"モデル '{model_name}' をロード中...")
 
     preprocessed_path = None
+    # ダウンロード済みのモデルはロード（大きいモデルほど時間がかかる）と音声の前処理を並行して行う
+    loader = ThreadPoolExecutor(max_workers=1, thread_name_prefix="voicenote-model")
     try:
-        from faster_whisper import WhisperModel
-
-        model = WhisperModel(model_name, device="cpu", compute_type="int8")
+        ensure_model_disk_space(config)
+        downloaded = is_model_downloaded(model_name)
+        if downloaded:
+            model_future = loader.submit(load_model, model_name, config.cpu_threads)
+        else:
+            apply_download_timeout(config.model_download_timeout)
         notify("音声ファイルを最適化中...")
 
-        preprocessed_path = _preprocess_audio(audio_path)
+        preprocessed_path = _preprocess_audio(
+            audio_path,
+            highpass_hz=config.highpass_hz,
+            resample_quality=config.resample_quality,
+            resampler=config.resampler,
+            temp_dir=config.temp_dir,
+            notify=notify,
+        )
+        if not downloaded:
+            # ダウンロードは前処理が成功してから始める。途中で止められないダウンロードが
+            # 前処理の失敗後も続き、終了時にスレッドの完了（数GBの受信）を待つことになるため
+            model_future = loader.submit(load_model, model_name, config.cpu_threads)
+        try:
+            model = model_future.result()
+        except Exception as e:
+            raise _model_load_error(e, config, downloaded) from e
+        # 言語を指定しない場合のみ、区間ごとに言語を判定する
+        multilingual = config.multilingual and config.language is None
+        if config.language is None and config.detect_languages and not multilingual:
+            candidates = ", ".join(config.detect_languages)
+            try:
+                language = detect_language(model, preprocessed_path, config.detect_languages)
+            except Exception as e:
+                # 古い faster-whisper には detect_language が無い。判定は文字起こし側に任せる
+                language = None
+                notify(f"⚠ 候補（{candidates}）からの言語判定に失敗したため自動判定します: {e}")
+            if language:
+                notify(f"言語を判定しました: {language}（候補: {candidates}）")
+                config = replace(config, language=language)
         notify("文字起こし中...")
+        started = time.monotonic()
 
-        segments, _ = model.transcribe(
-            str(preprocessed_path),
-            beam_size=5,
-            condition_on_previous_text=False,
-            vad_filter=vad_filter,
-        )
+        if multilingual:
+            notify("区間ごとに言語を判定しながら文字起こしします（実験的な機能）")
+            audio = _decode_audio(preprocessed_path)
+            raw_segments = _transcribe_multilingual(model, audio, config)
+            duration, language = len(audio) / TARGET_SAMPLE_RATE, None
+        elif config.chunk_minutes > 0:
+            notify(f"{config.chunk_minutes:g}分ごとに区切って文字起こしします")
+            duration, language, raw_segments = _transcribe_chunked(
+                model, preprocessed_path, config
+            )
+        else:
+            raw_segments, info = model.transcribe(
+                str(preprocessed_path),
+                language=config.language,
+                vad_filter=config.vad_filter,
+                vad_parameters=_vad_parameters(config),
+                **_decode_options(config),
+            )
+            duration, language = info.duration, info.language
+        estimate = estimate_transcription_seconds(model_name, duration, config.beam_size)
+        if estimate is not None:
+            finish = datetime.now() + timedelta(seconds=estimate)
+            notify(
+                f"文字起こし中...（音声 {duration / 60:.1f}分、目安 {_format_eta(estimate)}・"
+                f"{finish:%H:%M} 頃に完了）"
+            )
+        next_percent = PROGRESS_STEP_PERCENT
+
+        def on_decoded(segment: Segment):
+            # 実際の進み具合から残り時間を見積もり直す
+            nonlocal next_percent
+            if on_segment:
+                on_segment(segment)
+            percent = int(segment.end / duration * 100) if duration else 0
+            if percent < next_percent or percent >= 100:
+                return
+            next_percent = (percent // PROGRESS_STEP_PERCENT + 1) * PROGRESS_STEP_PERCENT
+            remaining = (time.monotonic() - started) * (100 - percent) / percent
+            notify(f"文字起こし中... {percent}%（残り{_format_eta(remaining)}）")
+
+        segments = _collect_segments(raw_segments, on_segment=on_decoded, on_warning=notify)
         transcription = _merge_segments(segments)
+        if multilingual:
+            # 判定された言語を出てきた順に並べる
+            languages = dict.fromkeys(s.language for s in segments if s.language)
+            language = ", ".join(languages) or None
 
         notify("文字起こし完了")
-        return transcription
+        return TranscriptionResult(
+            text=transcription,
+            model=model_name,
+            language=language,
+            duration=duration,
+            sample_rate=_read_sample_rate(audio_path),
+            segments=segments,
+        )
 
+    except VoiceNoteError:
+        raise
     except Exception as e:
-        raise RuntimeError(f"文字起こしエラー: {e}") from e
+        raise TranscriptionError(f"文字起こしエラー: {e}") from e
     finally:
+        # 前処理が失敗した場合はロードの完了を待たない（ロード結果は load_model にキャッシュされる）
+        loader.shutdown(wait=False)
         if preprocessed_path and preprocessed_path != audio_path:
-            preprocessed_path.unlink(missing_ok=True)
+            if config.keep_temp:
+                notify(f"前処理後の音声を残しました: {preprocessed_path}")
+            else:
+                preprocessed_path.unlink(missing_ok=True)
+        elif preprocessed_path and config.keep_temp:
+            notify(f"前処理は不要だったため、元の音声をそのまま文字起こししました: {audio_path}")
 
 
 def transcribe_audio_openai(
     audio_path: Path,
     api_key: str | None,
     progress_callback: Callable[[str], None] | None = None,
-) -> str:
+) -> TranscriptionResult:
     """
     OpenAI Whisper APIで音声を文字起こしする
 
@@ -150,16 +1116,17 @@ def transcribe_audio_openai(
         progress_callback: 進捗メッセージを受け取るコールバック（GUIから渡す）
 
     Returns:
-        文字起こしされたテキスト
+        文字起こし結果（テキストと言語・長さなどのメタデータ）
 
     Raises:
-        ValueError: APIキー未設定、ファイルサイズ超過
-        RuntimeError: API呼び出し失敗時
+        InvalidConfigError: APIキー未設定
+        ValueError: ファイルサイズ超過
+        TranscriptionError: API呼び出し失敗時
     """
     from openai import OpenAI
 
     if not api_key:
-        raise ValueError("OpenAI APIキーが設定されていません")
+        raise InvalidConfigError("OpenAI APIキーが設定されていません")
 
     file_size_mb = audio_path.stat().st_size / (1024 * 1024)
     if file_size_mb > 25:
@@ -178,31 +1145,69 @@ def transcribe_audio_openai(
         client = OpenAI(api_key=api_key)
         with open(audio_path, "rb") as audio_file:
             response = client.audio.transcriptions.create(
-                model="whisper-1",
+                model=OPENAI_MODEL,
                 file=audio_file,
                 response_format="verbose_json",
             )
 
-        transcription = _merge_segments(response.segments)
+        segments = _collect_segments(response.segments or [], on_warning=notify)
+        transcription = _merge_segments(segments)
         notify("文字起こし完了")
-        return transcription
+        return TranscriptionResult(
+            text=transcription,
+            model=OPENAI_MODEL,
+            language=getattr(response, "language", None),
+            duration=getattr(response, "duration", None),
+            sample_rate=_read_sample_rate(audio_path),
+            segments=segments,
+        )
 
     except Exception as e:
-        raise RuntimeError(f"OpenAI APIエラー: {e}") from e
+        raise TranscriptionError(f"OpenAI APIエラー: {e}") from e
 
 
 def transcribe(
     audio_path: Path,
     config: VoiceNoteConfig,
     progress_callback: Callable[[str], None] | None = None,
-) -> str:
-    """設定の transcription_mode に応じて local/openai の文字起こしをディスパッチする"""
+    on_segment: Callable[[Segment], None] | None = None,
+) -> TranscriptionResult:
+    """設定の transcription_mode に応じて local/openai の文字起こしをディスパッチする
+
+    `on_segment` はセグメントが確定するたびに呼ばれる（リアルタイム表示などに使う）。
+    ローカルモードは逐次デコードしながら、OpenAI API は一括で返るため応答を受け取った後に順に呼ぶ。
+    受け取るのは dedupe などのフィルタをかける前のセグメント。
+    `dedupe` が有効なら、連続する重複セグメントをまとめてから本文を組み立て直す。
+    `hallucination_filter` が有効なら、既知のハルシネーションの定型句を取り除いて通知する。
+    `filler_filter` が有効なら、`filler_words` のフィラーを取り除く。
+    本文は `segment_separator` に従ってセグメントを結合する。
+    """
     if config.transcription_mode == "openai":
         api_key = resolve_api_key(config)
-        return transcribe_audio_openai(audio_path, api_key, progress_callback=progress_callback)
-    return transcribe_audio(
-        audio_path,
-        config.whisper_model,
-        progress_callback=progress_callback,
-        vad_filter=config.vad_filter,
-    )
+        result = transcribe_audio_openai(audio_path, api_key, progress_callback=progress_callback)
+        if on_segment:
+            for segment in result.segments:
+                on_segment(segment)
+    else:
+        result = transcribe_audio(
+            audio_path, config, progress_callback=progress_callback, on_segment=on_segment
+        )
+    if config.dedupe and result.segments:
+        segments = dedupe_segments(result.segments)
+        result = replace(result, segments=segments, text=_merge_segments(segments))
+    if config.hallucination_filter != "off" and result.segments:
+        segments, removed = filter_hallucinations(
+            result.segments, config.hallucination_phrases, config.hallucination_filter
+        )
+        if removed:
+            phrases = "、".join(f"「{s.text}」" for s in removed)
+            if progress_callback:
+                progress_callback(f"⚠ ハルシネーションとみられる定型句を除きました: {phrases}")
+            result = replace(result, segments=segments, text=_merge_segments(segments))
+    if config.filler_filter and result.segments:
+        segments = remove_fillers(result.segments, config.filler_words)
+        result = replace(result, segments=segments, text=_merge_segments(segments))
+    if config.segment_separator != "pause" and result.segments:
+        text = _merge_segments(result.segments, config.segment_separator)
+        result = replace(result, text=text)
+    return result
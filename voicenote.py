@@ -0,0 +1,90 @@
+"""
+voicenote のライブラリ API
+
+他のアプリケーション（独自の GUI など）から録音→文字起こし→ノート保存を呼び出すための
+公開インターフェース。main.py / main_cli.py と同じ処理をこのモジュール経由で利用できる。
+
+    import voicenote
+
+    config = voicenote.load_config(voicenote.CONFIG_PATH) or voicenote.VoiceNoteConfig()
+    audio, rate = voicenote.record_audio(stop_event=stop_event)
+    wav = voicenote.save_wav(audio, Path("recordings"), rate)
+    note = voicenote.transcribe_and_save(wav, config)
+
+セグメントが確定するたびに受け取りたい場合（リアルタイム表示など）は on_segment を渡す。
+結果をまとめて返す点は変わらないため、逐次表示と保存を1回の呼び出しで行える。
+
+    saved = voicenote.transcribe_to_note(wav, config, on_segment=lambda s: show(s.start, s.text))
+
+録音・設定・モデル・文字起こし・ノートの保存の失敗は VoiceNoteError のサブクラスで送出するので、
+失敗の種類ごとに捕捉できる（DeviceUnavailableError・ModelDownloadError・
+SaveFolderUnavailableError・NoteWriteError・ConfigSaveError など）。
+ただし引数の値が不正な場合（save_wav の保存形式、save_transcript の保存形式・タグ、
+OpenAI API に送る 25MB 超のファイル）は ValueError を送出する。
+
+ここに無いモジュール内部の関数は予告なく変更することがある。
+"""
+
+from config import (
+    CONFIG_PATH,
+    ConfigSaveError,
+    InvalidConfigError,
+    VoiceNoteConfig,
+    load_config,
+    save_config,
+)
+from disk_space import InsufficientDiskSpaceError
+from errors import (
+    DeviceNotFoundError,
+    DeviceUnavailableError,
+    ModelDownloadError,
+    ModelDownloadTimeoutError,
+    ModelLoadError,
+    TranscriptionError,
+    VoiceNoteError,
+)
+from note_writer import NoteWriteError, SaveFolderUnavailableError, save_transcript
+from pipeline import (
+    EmptyTranscriptionError,
+    SavedTranscript,
+    save_result,
+    save_wav,
+    transcribe_and_save,
+    transcribe_to_note,
+)
+from recorder import ThreadedRecorder, list_devices, record_audio, start_recorder
+from transcriber import PartialTranscriptionError, Segment, TranscriptionResult, transcribe
+
+__all__ = [
+    "CONFIG_PATH",
+    "ConfigSaveError",
+    "DeviceNotFoundError",
+    "DeviceUnavailableError",
+    "EmptyTranscriptionError",
+    "InsufficientDiskSpaceError",
+    "InvalidConfigError",
+    "ModelDownloadError",
+    "ModelDownloadTimeoutError",
+    "ModelLoadError",
+    "NoteWriteError",
+    "PartialTranscriptionError",
+    "SaveFolderUnavailableError",
+    "SavedTranscript",
+    "Segment",
+    "ThreadedRecorder",
+    "TranscriptionError",
+    "TranscriptionResult",
+    "VoiceNoteConfig",
+    "VoiceNoteError",
+    "list_devices",
+    "load_config",
+    "record_audio",
+    "save_config",
+    "save_result",
+    "save_transcript",
+    "save_wav",
+    "start_recorder",
+    "transcribe",
+    "transcribe_and_save",
+    "transcribe_to_note",
+]